@@ -13,11 +13,14 @@ use sqlx::{
 use strum_macros::{ Display, EnumString };
 
 use super::get_pool;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
 use crate::database::bands;
+use crate::database::search::SearchResultType;
 use crate::database::songs::Song;
 use crate::util::filesystem;
 use crate::util::format::to_snake_case;
-use crate::util::sql::sanitize_like_clause_value;
+use crate::util::search_key::create_search_key;
+use crate::util::search_index::{ find_search_index_entries, mark_search_index_stale };
 
 pub static ALBUM_3D_BASE_DIRECTORY: &str = "uploads/assets/images/album-3d";
 pub static ALBUM_3D_BASE_URL: &str = "/assets/images/album-3d";
@@ -112,109 +115,32 @@ pub struct Album {
     pub album_type: AlbumType,
     pub publisher: String,
     pub cover_picture_filename: String,
-    pub song0: Option<i32>,
-    pub song1: Option<i32>,
-    pub song2: Option<i32>,
-    pub song3: Option<i32>,
-    pub song4: Option<i32>,
-    pub song5: Option<i32>,
-    pub song6: Option<i32>,
-    pub song7: Option<i32>,
-    pub song8: Option<i32>,
-    pub song9: Option<i32>,
-    pub song10: Option<i32>,
-    pub song11: Option<i32>,
-    pub song12: Option<i32>,
-    pub song13: Option<i32>,
-    pub song14: Option<i32>,
-    pub song15: Option<i32>,
-    pub song16: Option<i32>,
-    pub song17: Option<i32>,
-    pub song18: Option<i32>,
-    pub song19: Option<i32>,
-    pub song20: Option<i32>,
-    pub song21: Option<i32>,
-    pub song22: Option<i32>,
-    pub song23: Option<i32>,
-    pub song24: Option<i32>,
-    pub song25: Option<i32>,
-    pub song26: Option<i32>,
-    pub song27: Option<i32>,
-    pub song28: Option<i32>,
-    pub song29: Option<i32>,
-    pub song30: Option<i32>,
-    pub song31: Option<i32>,
-    pub song32: Option<i32>,
-    pub song33: Option<i32>,
-    pub song34: Option<i32>,
-    pub song35: Option<i32>,
-    pub song36: Option<i32>,
-    pub song37: Option<i32>,
-    pub song38: Option<i32>,
-    pub song39: Option<i32>,
     pub release_day: NaiveDate,
+    #[sqlx(skip)]
+    pub tracks: Vec<AlbumTrack>,
 }
 impl Album {
     pub fn song_ids(&self) -> Vec<i32> {
-        vec![
-            self.song0, self.song1, self.song2, self.song3, self.song4, self.song5, self.song6, self.song7, self.song8, self.song9,
-            self.song10, self.song11, self.song12, self.song13, self.song14, self.song15, self.song16, self.song17, self.song18, self.song19,
-            self.song20, self.song21, self.song22, self.song23, self.song24, self.song25, self.song26, self.song27, self.song28, self.song29,
-            self.song30, self.song31, self.song32, self.song33, self.song34, self.song35, self.song36, self.song37, self.song38, self.song39,
-        ]
-            .into_iter()
-            .filter_map(|value| match value {
-                Some(value) if value != 0 => Some(value),
-                _ => None,
-            })
-            .collect()
+        self.tracks.iter().map(|track| track.song_id).collect()
     }
-    pub fn populate_song_ids(mut self, songs: Vec<Option<i32>>) -> Self {
-        let none: &Option<i32> = &None;
-        self.song0 = *songs.get(0).unwrap_or_else(|| none);
-        self.song1 = *songs.get(1).unwrap_or_else(|| none);
-        self.song2 = *songs.get(2).unwrap_or_else(|| none);
-        self.song3 = *songs.get(3).unwrap_or_else(|| none);
-        self.song4 = *songs.get(4).unwrap_or_else(|| none);
-        self.song5 = *songs.get(5).unwrap_or_else(|| none);
-        self.song6 = *songs.get(6).unwrap_or_else(|| none);
-        self.song7 = *songs.get(7).unwrap_or_else(|| none);
-        self.song8 = *songs.get(8).unwrap_or_else(|| none);
-        self.song9 = *songs.get(9).unwrap_or_else(|| none);
-        self.song10 = *songs.get(10).unwrap_or_else(|| none);
-        self.song11 = *songs.get(11).unwrap_or_else(|| none);
-        self.song12 = *songs.get(12).unwrap_or_else(|| none);
-        self.song13 = *songs.get(13).unwrap_or_else(|| none);
-        self.song14 = *songs.get(14).unwrap_or_else(|| none);
-        self.song15 = *songs.get(15).unwrap_or_else(|| none);
-        self.song16 = *songs.get(16).unwrap_or_else(|| none);
-        self.song17 = *songs.get(17).unwrap_or_else(|| none);
-        self.song18 = *songs.get(18).unwrap_or_else(|| none);
-        self.song19 = *songs.get(19).unwrap_or_else(|| none);
-        self.song20 = *songs.get(20).unwrap_or_else(|| none);
-        self.song21 = *songs.get(21).unwrap_or_else(|| none);
-        self.song22 = *songs.get(22).unwrap_or_else(|| none);
-        self.song23 = *songs.get(23).unwrap_or_else(|| none);
-        self.song24 = *songs.get(24).unwrap_or_else(|| none);
-        self.song25 = *songs.get(25).unwrap_or_else(|| none);
-        self.song26 = *songs.get(26).unwrap_or_else(|| none);
-        self.song27 = *songs.get(27).unwrap_or_else(|| none);
-        self.song28 = *songs.get(28).unwrap_or_else(|| none);
-        self.song29 = *songs.get(29).unwrap_or_else(|| none);
-        self.song30 = *songs.get(30).unwrap_or_else(|| none);
-        self.song31 = *songs.get(31).unwrap_or_else(|| none);
-        self.song32 = *songs.get(32).unwrap_or_else(|| none);
-        self.song33 = *songs.get(33).unwrap_or_else(|| none);
-        self.song34 = *songs.get(34).unwrap_or_else(|| none);
-        self.song35 = *songs.get(35).unwrap_or_else(|| none);
-        self.song36 = *songs.get(36).unwrap_or_else(|| none);
-        self.song37 = *songs.get(37).unwrap_or_else(|| none);
-        self.song38 = *songs.get(38).unwrap_or_else(|| none);
-        self.song39 = *songs.get(39).unwrap_or_else(|| none);
-        self
+    pub fn disc_count(&self) -> i32 {
+        self.tracks.iter().map(|track| track.disc_number).max().unwrap_or(1)
     }
 }
 
+/**
+ * Position of a song in an album's track list. Tracks are ordered by disc, then by track
+ * number, and the same song can appear on more than one album.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct AlbumTrack {
+    pub album_id: i32,
+    pub song_id: i32,
+    pub disc_number: i32,
+    pub track_number: i32,
+}
+
 #[allow(unused)]
 #[derive(Debug, Default, Clone, FromRow)]
 pub struct AlbumSummary {
@@ -237,7 +163,7 @@ pub async fn get_album_by_slug_and_band_id(album_slug: &str, band_id: i32) -> Re
         );
     }
 
-    let result = sqlx::query_as::<MySql, Album>(r#"
+    let mut result = sqlx::query_as::<MySql, Album>(r#"
         SELECT * FROM albums
         WHERE album_slug=? AND band=? AND is_deleted=0
         LIMIT 1;
@@ -247,6 +173,8 @@ pub async fn get_album_by_slug_and_band_id(album_slug: &str, band_id: i32) -> Re
         .fetch_one(get_pool())
         .await?;
 
+    result.tracks = get_album_tracks(result.id).await?;
+
     Ok(
         result
     )
@@ -278,13 +206,16 @@ pub async fn get_album_by_song_slug(song_slug: &str, band_slug: &str) -> Result<
         );
     }
 
-    let result = sqlx::query_as::<MySql, Album>(r#"
-        SELECT albums.*, songs.song_slug, bands.band_slug
+    // A song can be on several albums, prefer the one it was originally released on.
+    let mut result = sqlx::query_as::<MySql, Album>(r#"
+        SELECT albums.*
         FROM albums
-        JOIN songs ON songs.album = albums.id
+        JOIN album_tracks ON album_tracks.album_id = albums.id
+        JOIN songs ON album_tracks.song_id = songs.id
         JOIN bands ON songs.band = bands.id
         WHERE songs.song_slug=? AND bands.band_slug=?
             AND bands.is_deleted=0 AND albums.is_deleted=0
+        ORDER BY (songs.album = albums.id) DESC, albums.release_day ASC
         LIMIT 1;
     "#)
         .bind(song_slug)
@@ -292,6 +223,8 @@ pub async fn get_album_by_song_slug(song_slug: &str, band_slug: &str) -> Result<
         .fetch_one(get_pool())
         .await?;
 
+    result.tracks = get_album_tracks(result.id).await?;
+
     Ok(
         result
     )
@@ -314,7 +247,7 @@ pub async fn get_album_summaries_by_band_id(id: i32) -> Result<Vec<AlbumSummary>
 }
 
 pub async fn get_albums_by_band_id(id: i32) -> Result<Vec<Album>, Box<dyn Error>> {
-    let result = sqlx::query_as::<MySql, Album>(r#"
+    let mut result = sqlx::query_as::<MySql, Album>(r#"
         SELECT * FROM albums
         WHERE band=? AND is_deleted=0
         ORDER BY album_type ASC, release_day ASC
@@ -324,6 +257,78 @@ pub async fn get_albums_by_band_id(id: i32) -> Result<Vec<Album>, Box<dyn Error>
         .fetch_all(get_pool())
         .await?;
 
+    let tracks = sqlx::query_as::<MySql, AlbumTrack>(r#"
+        SELECT album_tracks.album_id, album_tracks.song_id, album_tracks.disc_number, album_tracks.track_number
+        FROM album_tracks
+        JOIN albums ON album_tracks.album_id = albums.id
+        WHERE albums.band=? AND albums.is_deleted=0
+        ORDER BY album_tracks.album_id ASC, album_tracks.disc_number ASC, album_tracks.track_number ASC;
+    "#)
+        .bind(id)
+        .fetch_all(get_pool())
+        .await?;
+
+    let mut tracks_by_album_id: HashMap<i32, Vec<AlbumTrack>> = HashMap::new();
+    for track in tracks {
+        tracks_by_album_id.entry(track.album_id).or_default().push(track);
+    }
+    for album in &mut result {
+        album.tracks = tracks_by_album_id.remove(&album.id).unwrap_or_default();
+    }
+
+    Ok(
+        result
+    )
+}
+
+pub async fn get_album_tracks(album_id: i32) -> Result<Vec<AlbumTrack>, Box<dyn Error>> {
+    let result = sqlx::query_as::<MySql, AlbumTrack>(r#"
+        SELECT album_id, song_id, disc_number, track_number FROM album_tracks
+        WHERE album_id=?
+        ORDER BY disc_number ASC, track_number ASC;
+    "#)
+        .bind(album_id)
+        .fetch_all(get_pool())
+        .await?;
+
+    Ok(
+        result
+    )
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct AlbumTrackListing {
+    pub disc_number: i32,
+    pub track_number: i32,
+    pub song_slug: String,
+    pub song_name: String,
+    pub tab_count: i32,
+    pub has_translation: bool,
+}
+
+pub async fn get_album_track_listing(album_id: i32) -> Result<Vec<AlbumTrackListing>, Box<dyn Error>> {
+    let result = sqlx::query_as::<MySql, AlbumTrackListing>(r#"
+        SELECT
+            album_tracks.disc_number,
+            album_tracks.track_number,
+            songs.song_slug,
+            songs.song_name,
+            songs.tab_count,
+            (SELECT EXISTS (
+                SELECT 1
+                FROM lyrics
+                WHERE lyrics.song = songs.id AND lyrics.is_deleted=0
+            )) AS has_translation
+        FROM album_tracks
+        JOIN songs ON album_tracks.song_id = songs.id
+        WHERE album_tracks.album_id=? AND songs.is_deleted=0
+        ORDER BY album_tracks.disc_number ASC, album_tracks.track_number ASC;
+    "#)
+        .bind(album_id)
+        .fetch_all(get_pool())
+        .await?;
+
     Ok(
         result
     )
@@ -348,7 +353,16 @@ pub async fn find_albums_by_name(search: &str) -> Result<Vec<AlbumSearchResult>,
         );
     }
 
-    let result = sqlx::query_as::<MySql, AlbumSearchResult>(r#"
+    let album_ids: Vec<i32> = find_search_index_entries(Some(&SearchResultType::Albums), search, 15).await
+        .into_iter()
+        .map(|album| album.id)
+        .collect();
+    if album_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids_group = album_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(", ");
+
+    let result = sqlx::query_as::<MySql, AlbumSearchResult>(format!(r#"
         SELECT
             albums.album_slug,
             albums.album_name,
@@ -356,10 +370,9 @@ pub async fn find_albums_by_name(search: &str) -> Result<Vec<AlbumSearchResult>,
             bands.band_name
         FROM albums
         JOIN bands ON albums.band = bands.id
-        WHERE albums.album_name LIKE ? AND albums.is_deleted=0
-        LIMIT 15;
-    "#)
-        .bind(format!("%{}%", sanitize_like_clause_value(search)))
+        WHERE albums.id IN ({}) AND albums.is_deleted=0
+        ORDER BY FIELD(albums.id, {})
+    "#, ids_group, ids_group).as_str())
         .fetch_all(get_pool())
         .await?;
 
@@ -507,14 +520,15 @@ pub async fn create_album(
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Album>(r#"
         INSERT INTO albums (
-            username, post_time, band, album_slug, album_name, album_type, publisher, cover_picture_filename, release_day
+            username, post_time, band, album_slug, album_name, search_key, album_type, publisher, cover_picture_filename, release_day
         )
-        VALUES (?, NOW(), ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, NOW(), ?, ?, ?, ?, ?, ?, ?, ?)
     "#)
         .bind(album.username)
         .bind(album.band)
         .bind(album.album_slug)
-        .bind(album.album_name)
+        .bind(&album.album_name)
+        .bind(create_search_key(&album.album_name))
         .bind(i64::from(album.album_type))
         .bind(album.publisher)
         .bind(album.cover_picture_filename)
@@ -524,6 +538,7 @@ pub async fn create_album(
 
     match result {
         Ok(_) => {
+            mark_search_index_stale();
             Ok(())
         }
         Err(e) => {
@@ -535,52 +550,56 @@ pub async fn create_album(
 pub async fn update_album(
     album: Album
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let result = sqlx::query_as::<MySql, Album>(r#"
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
         UPDATE albums
-        SET album_slug=?, album_name=?, album_type=?, publisher=?, cover_picture_filename=?, release_day=?,
-            song0=?, song1=?, song2=?, song3=?, song4=?, song5=?, song6=?, song7=?, song8=?, song9=?,
-            song10=?, song11=?, song12=?, song13=?, song14=?, song15=?, song16=?, song17=?, song18=?, song19=?,
-            song20=?, song21=?, song22=?, song23=?, song24=?, song25=?, song26=?, song27=?, song28=?, song29=?,
-            song30=?, song31=?, song32=?, song33=?, song34=?, song35=?, song36=?, song37=?, song38=?, song39=?
+        SET album_slug=?, album_name=?, search_key=?, album_type=?, publisher=?, cover_picture_filename=?, release_day=?
         WHERE id=? AND is_deleted=0
         LIMIT 1
     "#)
         .bind(album.album_slug)
-        .bind(album.album_name)
+        .bind(&album.album_name)
+        .bind(create_search_key(&album.album_name))
         .bind(i64::from(album.album_type))
         .bind(album.publisher)
         .bind(album.cover_picture_filename)
         .bind(album.release_day)
-        .bind(album.song0).bind(album.song1).bind(album.song2).bind(album.song3).bind(album.song4).bind(album.song5).bind(album.song6).bind(album.song7).bind(album.song8).bind(album.song9)
-        .bind(album.song10).bind(album.song11).bind(album.song12).bind(album.song13).bind(album.song14).bind(album.song15).bind(album.song16).bind(album.song17).bind(album.song18).bind(album.song19)
-        .bind(album.song20).bind(album.song21).bind(album.song22).bind(album.song23).bind(album.song24).bind(album.song25).bind(album.song26).bind(album.song27).bind(album.song28).bind(album.song29)
-        .bind(album.song30).bind(album.song31).bind(album.song32).bind(album.song33).bind(album.song34).bind(album.song35).bind(album.song36).bind(album.song37).bind(album.song38).bind(album.song39)
         .bind(album.id)
-        .fetch_optional(get_pool())
-        .await;
+        .execute(&mut *transaction)
+        .await?;
 
-    match result {
-        Ok(_) => {
-            Ok(())
-        }
-        Err(e) => {
-            Err(Box::new(e))
-        }
+    sqlx::query(r#"
+        DELETE FROM album_tracks
+        WHERE album_id=?
+    "#)
+        .bind(album.id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for track in &album.tracks {
+        sqlx::query(r#"
+            INSERT INTO album_tracks (album_id, song_id, disc_number, track_number)
+            VALUES (?, ?, ?, ?)
+        "#)
+            .bind(album.id)
+            .bind(track.song_id)
+            .bind(track.disc_number)
+            .bind(track.track_number)
+            .execute(&mut *transaction)
+            .await?;
     }
+
+    transaction.commit().await?;
+    mark_search_index_stale();
+
+    Ok(())
 }
 
 pub async fn mark_album_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let album = sqlx::query_as::<MySql, Album>(r#"
-        SELECT * FROM albums
-        WHERE id=?
-        LIMIT 1;
-    "#)
-        .bind(id)
-        .fetch_one(get_pool())
-        .await?;
-
     let delete_result = sqlx::query_as::<MySql, Album>(r#"
         UPDATE albums
         SET is_deleted=1
@@ -593,40 +612,25 @@ pub async fn mark_album_for_deletion(
 
     match delete_result {
         Ok(_) => {
-            let song_ids = album.song_ids();
-            let song_ids_by_album = sqlx::query_as::<MySql, Album>(r#"
-                SELECT * from albums
-                WHERE is_deleted=0
-                LIMIT 100;
+            // Songs on this album that no other remaining album lists are orphaned.
+            let _ = sqlx::query_as::<MySql, Song>(r#"
+                UPDATE songs
+                SET is_deleted=1
+                WHERE id IN (
+                    SELECT song_id FROM album_tracks WHERE album_id=?
+                ) AND NOT EXISTS (
+                    SELECT 1
+                    FROM album_tracks AS other_tracks
+                    JOIN albums AS other_albums ON other_tracks.album_id = other_albums.id
+                    WHERE other_tracks.song_id = songs.id AND other_albums.is_deleted=0
+                )
             "#)
-                .fetch_all(get_pool())
-                .await?
-                .into_iter()
-                .map(|album| album.song_ids())
-                .collect::<Vec<_>>();
-            for song_id in &song_ids {
-                let mut is_song_referenced: bool = false;
-                for other_album_song_ids in &song_ids_by_album {
-                    if other_album_song_ids.iter().find(|check_song_id| &song_id == check_song_id).is_some() {
-                        is_song_referenced = true;
-                        break;
-                    }
-                }
+                .bind(id)
+                .fetch_optional(get_pool())
+                .await;
+            mark_search_index_stale();
 
-                if !is_song_referenced {
-                    let _ = sqlx::query_as::<MySql, Song>(r#"
-                        UPDATE songs
-                        SET is_deleted=1
-                        WHERE id=?
-                        LIMIT 1
-                    "#)
-                        .bind(song_id)
-                        .fetch_optional(get_pool())
-                        .await;
-                }
-            }
-
-            Ok(())
+            queue_deletion(DeletionContentType::Album, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
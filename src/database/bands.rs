@@ -6,7 +6,10 @@ use sqlx::{
 };
 use super::get_pool;
 
-use crate::util::sql::sanitize_like_clause_value;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
+use crate::database::search::SearchResultType;
+use crate::util::search_key::create_search_key;
+use crate::util::search_index::{ find_search_index_entries, mark_search_index_stale };
 
 #[allow(unused)]
 #[derive(Debug, Default, Clone, FromRow)]
@@ -42,6 +45,21 @@ pub async fn get_band_by_slug(band_slug: &str) -> Result<Band, Box<dyn Error>> {
     )
 }
 
+/**
+ * Albums waiting in the deletion queue count too, since they could be restored without their band.
+ */
+pub async fn band_by_id_is_empty(id: i32) -> Result<(), Box<dyn Error>> {
+    let result = sqlx::query("SELECT id FROM albums WHERE band=? LIMIT 1")
+        .bind(id)
+        .fetch_optional(get_pool())
+        .await?;
+
+    match result {
+        Some(_) => Err(Box::new(io::Error::new(io::ErrorKind::Other, "Album found."))),
+        None => Ok(())
+    }
+}
+
 pub async fn find_bands_by_name(search: &str) -> Result<Vec<Band>, Box<dyn Error>> {
     if search.len() > 200 {
         return Err(
@@ -51,12 +69,20 @@ pub async fn find_bands_by_name(search: &str) -> Result<Vec<Band>, Box<dyn Error
         );
     }
 
-    let result = sqlx::query_as::<MySql, Band>(r#"
+    let band_ids: Vec<i32> = find_search_index_entries(Some(&SearchResultType::Bands), search, 15).await
+        .into_iter()
+        .map(|band| band.id)
+        .collect();
+    if band_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids_group = band_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(", ");
+
+    let result = sqlx::query_as::<MySql, Band>(format!(r#"
         SELECT * from bands
-        WHERE band_name LIKE ? AND is_deleted=0
-        LIMIT 15;
-    "#)
-        .bind(format!("%{}%", sanitize_like_clause_value(search)))
+        WHERE id IN ({}) AND is_deleted=0
+        ORDER BY FIELD(id, {})
+    "#, ids_group, ids_group).as_str())
         .fetch_all(get_pool())
         .await?;
 
@@ -68,17 +94,19 @@ pub async fn find_bands_by_name(search: &str) -> Result<Vec<Band>, Box<dyn Error
 pub async fn create_band(band: Band) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Band>(r#"
         INSERT INTO bands (
-            band_slug, band_name
+            band_slug, band_name, search_key
         )
-        VALUES (?, ?)
+        VALUES (?, ?, ?)
     "#)
         .bind(band.band_slug)
-        .bind(band.band_name)
+        .bind(&band.band_name)
+        .bind(create_search_key(&band.band_name))
         .fetch_optional(get_pool())
         .await;
 
     match result {
         Ok(_) => {
+            mark_search_index_stale();
             Ok(())
         }
         Err(e) => {
@@ -90,18 +118,20 @@ pub async fn create_band(band: Band) -> Result<(), Box<dyn Error + Send + Sync>>
 pub async fn update_band(band: Band) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Band>(r#"
         UPDATE bands
-        SET band_slug=?, band_name=?
+        SET band_slug=?, band_name=?, search_key=?
         WHERE id=? AND is_deleted=0
         LIMIT 1
     "#)
         .bind(band.band_slug)
-        .bind(band.band_name)
+        .bind(&band.band_name)
+        .bind(create_search_key(&band.band_name))
         .bind(band.id)
         .fetch_optional(get_pool())
         .await;
 
     match result {
         Ok(_) => {
+            mark_search_index_stale();
             Ok(())
         }
         Err(e) => {
@@ -112,6 +142,7 @@ pub async fn update_band(band: Band) -> Result<(), Box<dyn Error + Send + Sync>>
 
 pub async fn mark_band_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let delete_result = sqlx::query_as::<MySql, Band>(r#"
         UPDATE bands
@@ -125,7 +156,8 @@ pub async fn mark_band_for_deletion(
 
     match delete_result {
         Ok(_) => {
-            Ok(())
+            mark_search_index_stale();
+            queue_deletion(DeletionContentType::Band, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
@@ -0,0 +1,230 @@
+use std::error::Error;
+use chrono::NaiveDateTime;
+use sqlx::{
+    FromRow,
+    MySql,
+    Row,
+    Type,
+};
+use strum_macros::{ Display, EnumString };
+
+use super::get_pool;
+use super::comments::{ CommentSectionName, COMMENT_VISIBILITY_HIDDEN };
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq, Type)]
+#[sqlx(type_name = "reason")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CommentReportReason {
+    Spam,
+    Harassment,
+    HateSpeech,
+    Explicit,
+    Misinformation,
+    OffTopic,
+    #[default]
+    Other,
+}
+impl CommentReportReason {
+    pub fn all() -> Vec<CommentReportReason> {
+        vec![
+            CommentReportReason::Spam,
+            CommentReportReason::Harassment,
+            CommentReportReason::HateSpeech,
+            CommentReportReason::Explicit,
+            CommentReportReason::Misinformation,
+            CommentReportReason::OffTopic,
+            CommentReportReason::Other,
+        ]
+    }
+
+    pub fn as_display(&self) -> &'static str {
+        match self {
+            CommentReportReason::Spam => "Spam or advertising",
+            CommentReportReason::Harassment => "Harassment or bullying",
+            CommentReportReason::HateSpeech => "Hate speech",
+            CommentReportReason::Explicit => "Sexual or violent content",
+            CommentReportReason::Misinformation => "Misinformation",
+            CommentReportReason::OffTopic => "Off topic",
+            CommentReportReason::Other => "Something else",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq, Type)]
+#[sqlx(type_name = "status")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CommentReportStatus {
+    #[default]
+    Open,
+    Dismissed,
+    Hidden,
+    Deleted,
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct CommentReport {
+    pub id: i32,
+    pub comment_id: i32,
+    pub username: String,
+    pub ip_address: String,
+    pub report_time: NaiveDateTime,
+    pub reason: CommentReportReason,
+    pub details: String,
+    pub status: CommentReportStatus,
+}
+
+/**
+ * A reported comment along with a summary of its open reports, for the moderator queue.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, FromRow)]
+pub struct ReportedComment {
+    pub comment_id: i32,
+    pub username: String,
+    pub post_time: NaiveDateTime,
+    pub section: CommentSectionName,
+    pub section_tag_id: Option<i32>,
+    pub comment: String,
+    pub visibility: Option<i32>,
+    pub report_count: i64,
+    pub first_report_time: NaiveDateTime,
+}
+
+pub async fn get_open_comment_report_count() -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query(r#"
+            SELECT COUNT(DISTINCT comment_reports.comment_id)
+            FROM comment_reports
+            JOIN comments ON comments.id = comment_reports.comment_id
+            WHERE comment_reports.status = 'open' AND comments.is_deleted = 0
+        "#)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn get_reported_comments_in_range(
+    start: u32,
+    length: u32,
+) -> Result<Vec<ReportedComment>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, ReportedComment>(r#"
+            SELECT
+                comments.id AS comment_id,
+                comments.username,
+                comments.post_time,
+                comments.section,
+                comments.section_tag_id,
+                comments.comment,
+                comments.visibility,
+                COUNT(comment_reports.id) AS report_count,
+                MIN(comment_reports.report_time) AS first_report_time
+            FROM comment_reports
+            JOIN comments ON comments.id = comment_reports.comment_id
+            WHERE comment_reports.status = 'open' AND comments.is_deleted = 0
+            GROUP BY comments.id
+            ORDER BY report_count DESC, first_report_time ASC
+            LIMIT ? OFFSET ?
+        "#)
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Comments a moderator hid, most recently hidden first, so they can be shown again or deleted.
+ * The report count includes the resolved reports that led to hiding it.
+ */
+pub async fn get_hidden_comments(
+    length: u32,
+) -> Result<Vec<ReportedComment>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, ReportedComment>(r#"
+            SELECT
+                comments.id AS comment_id,
+                comments.username,
+                comments.post_time,
+                comments.section,
+                comments.section_tag_id,
+                comments.comment,
+                comments.visibility,
+                COUNT(comment_reports.id) AS report_count,
+                MIN(comment_reports.report_time) AS first_report_time
+            FROM comment_reports
+            JOIN comments ON comments.id = comment_reports.comment_id
+            WHERE comments.visibility = ? AND comments.is_deleted = 0
+            GROUP BY comments.id
+            ORDER BY MAX(comment_reports.resolve_time) DESC
+            LIMIT ?
+        "#)
+            .bind(COMMENT_VISIBILITY_HIDDEN)
+            .bind(length)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn get_open_comment_reports_by_comment_id(
+    comment_id: i32,
+) -> Result<Vec<CommentReport>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, CommentReport>(r#"
+            SELECT id, comment_id, username, ip_address, report_time, reason, details, status
+            FROM comment_reports
+            WHERE comment_id = ? AND status = 'open'
+            ORDER BY report_time ASC
+            LIMIT 50
+        "#)
+            .bind(comment_id)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Returns false if the member already reported the comment.
+ */
+pub async fn create_comment_report(
+    report: &CommentReport,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query(r#"
+        INSERT IGNORE INTO comment_reports (
+            comment_id, username, ip_address, report_time, reason, details, status
+        )
+        VALUES (?, ?, ?, NOW(), ?, ?, 'open')
+    "#)
+        .bind(report.comment_id)
+        .bind(&report.username)
+        .bind(&report.ip_address)
+        .bind(&report.reason)
+        .bind(&report.details)
+        .execute(get_pool())
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn resolve_comment_reports(
+    comment_id: i32,
+    status: &CommentReportStatus,
+    resolved_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        UPDATE comment_reports SET status = ?, resolved_by = ?, resolve_time = NOW()
+        WHERE comment_id = ? AND status = 'open'
+    "#)
+        .bind(status)
+        .bind(resolved_by)
+        .bind(comment_id)
+        .execute(get_pool())
+        .await?;
+
+    Ok(())
+}
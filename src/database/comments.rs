@@ -12,7 +12,7 @@ use strum_macros::{ Display, EnumString };
 use super::QueryOrder;
 use super::get_pool;
 
-#[derive(Debug, Default, Display, EnumString, Type)]
+#[derive(Clone, Debug, Default, Display, EnumString, Type)]
 #[sqlx(type_name = "section")]
 #[sqlx(rename_all = "lowercase")]
 #[strum(serialize_all = "snake_case")]
@@ -27,8 +27,14 @@ pub enum CommentSectionName {
     Chatbox,
 }
 
+/**
+ * Value of "comments.visibility" for comments hidden by a moderator.
+ */
+pub const COMMENT_VISIBILITY_HIDDEN: i32 = 1;
+pub const COMMENT_VISIBILITY_VISIBLE: i32 = 0;
+
 #[allow(unused)]
-#[derive(Debug, Default, FromRow)]
+#[derive(Clone, Debug, Default, FromRow)]
 pub struct Comment {
     pub id: i32,
     pub username: String,
@@ -41,6 +47,31 @@ pub struct Comment {
     pub visibility: Option<i32>,
     pub likes: Option<i32>,
     pub profile_picture_filename: String,
+    #[sqlx(default)]
+    pub edit_time: Option<NaiveDateTime>,
+    #[sqlx(default)]
+    pub is_deleted: bool,
+    #[sqlx(default)]
+    pub viewer_has_liked: bool,
+}
+impl Comment {
+    /**
+     * Hidden comments can still be found by id so their author or a moderator can delete them,
+     * but they can't be liked, reported or replied to.
+     */
+    pub fn is_hidden(&self) -> bool {
+        self.visibility.unwrap_or(0) == COMMENT_VISIBILITY_HIDDEN
+    }
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, FromRow)]
+pub struct CommentEdit {
+    pub id: i32,
+    pub comment_id: i32,
+    pub username: String,
+    pub edit_time: NaiveDateTime,
+    pub previous_comment: String,
 }
 
 pub struct CommentWithReplies {
@@ -48,17 +79,54 @@ pub struct CommentWithReplies {
     pub replies: Vec<CommentWithReplies>,
 }
 
+/**
+ * Hides comments written by members that the viewer has blocked. It expects the
+ * comment author to be joined as "users", and binds the viewer's user id (-1 for guests).
+ */
+const HIDE_BLOCKED_AUTHORS: &str = r#"
+    NOT EXISTS (
+        SELECT 1 FROM users AS viewers
+        WHERE viewers.id = ?
+            AND JSON_CONTAINS(COALESCE(viewers.blocklist, JSON_ARRAY()), CAST(users.id AS JSON))
+    )
+"#;
+
+/**
+ * Deleted and hidden comments are still listed while they have replies, so that the
+ * thread stays intact. They are rendered as a placeholder.
+ */
+const IS_VISIBLE_IN_THREAD: &str = r#"
+    (
+        (comments.is_deleted = 0 AND COALESCE(comments.visibility, 0) = 0)
+        OR EXISTS (
+            SELECT 1 FROM comments AS replies
+            WHERE replies.reply_id = comments.id
+                AND replies.is_deleted = 0
+                AND COALESCE(replies.visibility, 0) = 0
+        )
+    )
+"#;
+
 pub async fn get_comments_count(
     section: &CommentSectionName,
     section_tag_id: Option<i32>,
     reply_id: i32,
+    viewer_id: Option<i32>,
 ) -> Result<u32, Box<dyn Error>> {
     if let Some(section_tag_id) = section_tag_id {
         return Ok(
-            u32::try_from(sqlx::query("SELECT COUNT(*) FROM comments WHERE section=? AND section_tag_id=? AND reply_id=? AND is_deleted=0")
+            u32::try_from(sqlx::query(
+                format!(r#"
+                    SELECT COUNT(*) FROM comments
+                    LEFT JOIN users ON comments.username = users.username AND comments.username <> 'Guest'
+                    WHERE comments.section=? AND comments.section_tag_id=? AND comments.reply_id=? AND {}
+                        AND {}
+                "#, IS_VISIBLE_IN_THREAD, HIDE_BLOCKED_AUTHORS).as_str()
+            )
                 .bind(section)
                 .bind(section_tag_id)
                 .bind(reply_id)
+                .bind(viewer_id.unwrap_or(-1))
                 .fetch_one(get_pool())
                 .await?
                 .get::<i64, usize>(0)
@@ -66,9 +134,17 @@ pub async fn get_comments_count(
         );
     }
     Ok(
-        u32::try_from(sqlx::query("SELECT COUNT(*) FROM comments WHERE section=? AND reply_id=? AND is_deleted=0")
+        u32::try_from(sqlx::query(
+            format!(r#"
+                SELECT COUNT(*) FROM comments
+                LEFT JOIN users ON comments.username = users.username AND comments.username <> 'Guest'
+                WHERE comments.section=? AND comments.reply_id=? AND {}
+                    AND {}
+            "#, IS_VISIBLE_IN_THREAD, HIDE_BLOCKED_AUTHORS).as_str()
+        )
             .bind(section)
             .bind(reply_id)
+            .bind(viewer_id.unwrap_or(-1))
             .fetch_one(get_pool())
             .await?
             .get::<i64, usize>(0)
@@ -102,8 +178,13 @@ pub async fn get_comments_in_range(
     section_tag_id: Option<i32>,
     reply_id: i32,
     query_order: &QueryOrder,
+    viewer_id: Option<i32>,
 ) -> Result<Vec<Comment>, Box<dyn Error>> {
-    let order = if query_order == &QueryOrder::Asc { "ASC" } else { "DESC" };
+    let order = match query_order {
+        QueryOrder::Asc => "comments.post_time ASC",
+        QueryOrder::Desc => "comments.post_time DESC",
+        QueryOrder::Top => "comments.likes DESC, comments.post_time DESC",
+    };
     if let Some(section_tag_id) = section_tag_id {
         return Ok(
             sqlx::query_as::<MySql, Comment>(
@@ -113,20 +194,28 @@ pub async fn get_comments_in_range(
                         CASE
                             WHEN comments.username = 'Guest' THEN 'Guest.jpeg'
                             ELSE users.profile_picture_filename 
-                        END AS profile_picture_filename
+                        END AS profile_picture_filename,
+                        (SELECT EXISTS (
+                            SELECT 1
+                            FROM comment_likes
+                            WHERE comment_likes.comment_id = comments.id AND comment_likes.user_id = ?
+                        )) AS viewer_has_liked
                     FROM comments
                     LEFT JOIN users ON comments.username = users.username AND comments.username <> 'Guest'
                     WHERE comments.section = ?
                         AND comments.section_tag_id = ?
                         AND comments.reply_id = ?
-                        AND comments.is_deleted = 0
-                    ORDER BY comments.post_time {}
+                        AND {}
+                        AND {}
+                    ORDER BY {}
                     LIMIT ? OFFSET ?;
-                "#, order).as_str()
+                "#, IS_VISIBLE_IN_THREAD, HIDE_BLOCKED_AUTHORS, order).as_str()
             )
+                .bind(viewer_id.unwrap_or(-1))
                 .bind(section)
                 .bind(section_tag_id)
                 .bind(reply_id)
+                .bind(viewer_id.unwrap_or(-1))
                 .bind(length)
                 .bind(start)
                 .fetch_all(get_pool())
@@ -141,18 +230,26 @@ pub async fn get_comments_in_range(
                     CASE
                         WHEN comments.username = 'Guest' THEN 'Guest.jpeg'
                         ELSE users.profile_picture_filename
-                    END AS profile_picture_filename
+                    END AS profile_picture_filename,
+                    (SELECT EXISTS (
+                        SELECT 1
+                        FROM comment_likes
+                        WHERE comment_likes.comment_id = comments.id AND comment_likes.user_id = ?
+                    )) AS viewer_has_liked
                 FROM comments
                 LEFT JOIN users ON comments.username = users.username AND comments.username <> 'Guest'
                 WHERE comments.section = ?
                     AND comments.reply_id = ?
-                    AND comments.is_deleted = 0
-                ORDER BY comments.post_time {}
+                    AND {}
+                    AND {}
+                ORDER BY {}
                 LIMIT ? OFFSET ?;
-            "#, order).as_str()
+            "#, IS_VISIBLE_IN_THREAD, HIDE_BLOCKED_AUTHORS, order).as_str()
         )
+            .bind(viewer_id.unwrap_or(-1))
             .bind(section)
             .bind(reply_id)
+            .bind(viewer_id.unwrap_or(-1))
             .bind(length)
             .bind(start)
             .fetch_all(get_pool())
@@ -166,16 +263,17 @@ pub async fn get_comments_in_range_with_replies(
     section: &CommentSectionName,
     section_tag_id: Option<i32>,
     reply_id: i32,
+    query_order: &QueryOrder,
+    viewer_id: Option<i32>,
 ) -> Result<Vec<CommentWithReplies>, Box<dyn Error>> {
-    let query_order = if reply_id > -1 { &QueryOrder::Asc } else { &QueryOrder::Desc };
-    let comments = get_comments_in_range(start, length, section, section_tag_id, reply_id, query_order).await?;
+    let comments = get_comments_in_range(start, length, section, section_tag_id, reply_id, query_order, viewer_id).await?;
     
     let futures = comments
         .into_iter()
         .map(|comment| {
             async move {
                 let replies = get_comments_in_range_with_replies(
-                    0, 30, section, section_tag_id, comment.id,
+                    0, 30, section, section_tag_id, comment.id, &QueryOrder::Asc, viewer_id,
                 ).await.unwrap();
                 CommentWithReplies { comment, replies }
             }
@@ -213,3 +311,164 @@ pub async fn create_comment(
         }
     }
 }
+
+/**
+ * Records a like from the user. Returns false if the user already liked the comment.
+ */
+pub async fn like_comment(
+    comment_id: i32,
+    user_id: i32,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    let inserted = sqlx::query(r#"
+        INSERT IGNORE INTO comment_likes (comment_id, user_id, like_time)
+        VALUES (?, ?, NOW())
+    "#)
+        .bind(comment_id)
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected() > 0;
+
+    if inserted {
+        sqlx::query("UPDATE comments SET likes = COALESCE(likes, 0) + 1 WHERE id = ?")
+            .bind(comment_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(inserted)
+}
+
+/**
+ * Removes the user's like. Returns false if the user hadn't liked the comment.
+ */
+pub async fn unlike_comment(
+    comment_id: i32,
+    user_id: i32,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    let deleted = sqlx::query("DELETE FROM comment_likes WHERE comment_id = ? AND user_id = ?")
+        .bind(comment_id)
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected() > 0;
+
+    if deleted {
+        sqlx::query("UPDATE comments SET likes = GREATEST(COALESCE(likes, 0) - 1, 0) WHERE id = ?")
+            .bind(comment_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(deleted)
+}
+
+pub async fn get_comment_edits(
+    comment_id: i32,
+) -> Result<Vec<CommentEdit>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, CommentEdit>(r#"
+            SELECT id, comment_id, username, edit_time, previous_comment
+            FROM comment_edits
+            WHERE comment_id = ?
+            ORDER BY id DESC
+            LIMIT 100
+        "#)
+            .bind(comment_id)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Replaces the comment text, keeping the previous text in the edit history.
+ */
+pub async fn update_comment(
+    comment_id: i32,
+    username: &str,
+    comment: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    let previous_comment = sqlx::query("SELECT comment FROM comments WHERE id = ? AND is_deleted = 0 FOR UPDATE")
+        .bind(comment_id)
+        .fetch_one(&mut *transaction)
+        .await?
+        .get::<String, usize>(0);
+
+    if previous_comment == comment {
+        transaction.rollback().await?;
+        return Ok(());
+    }
+
+    sqlx::query(r#"
+        INSERT INTO comment_edits (comment_id, username, edit_time, previous_comment)
+        VALUES (?, ?, NOW(), ?)
+    "#)
+        .bind(comment_id)
+        .bind(username)
+        .bind(&previous_comment)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query("UPDATE comments SET comment = ?, edit_time = NOW() WHERE id = ?")
+        .bind(comment)
+        .bind(comment_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_comment(
+    comment_id: i32,
+    deleted_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        UPDATE comments SET is_deleted = 1, delete_time = NOW(), deleted_by = ?
+        WHERE id = ? AND is_deleted = 0
+    "#)
+        .bind(deleted_by)
+        .bind(comment_id)
+        .execute(get_pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_comment_visibility(
+    comment_id: i32,
+    visibility: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("UPDATE comments SET visibility = ? WHERE id = ?")
+        .bind(visibility)
+        .bind(comment_id)
+        .execute(get_pool())
+        .await?;
+
+    Ok(())
+}
+
+pub async fn has_user_liked_comment(
+    comment_id: i32,
+    user_id: i32,
+) -> Result<bool, Box<dyn Error>> {
+    Ok(
+        sqlx::query("SELECT COUNT(*) FROM comment_likes WHERE comment_id = ? AND user_id = ?")
+            .bind(comment_id)
+            .bind(user_id)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0) > 0
+    )
+}
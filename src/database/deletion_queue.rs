@@ -0,0 +1,641 @@
+use std::error::Error;
+use std::io;
+use chrono::NaiveDateTime;
+use sqlx::{
+    FromRow,
+    MySql,
+    MySqlConnection,
+    Row,
+    Type,
+};
+use strum_macros::{ Display, EnumString };
+
+use super::get_pool;
+use crate::util::image_upload;
+use crate::util::search_index::mark_search_index_stale;
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq, Type)]
+#[sqlx(type_name = "content_type")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DeletionContentType {
+    #[default]
+    Band,
+    Album,
+    Lyrics,
+    Tabs,
+    Photo,
+    PhotoAlbum,
+    Video,
+    VideoCategory,
+}
+impl DeletionContentType {
+    pub fn all() -> Vec<DeletionContentType> {
+        vec![
+            DeletionContentType::Band,
+            DeletionContentType::Album,
+            DeletionContentType::Lyrics,
+            DeletionContentType::Tabs,
+            DeletionContentType::Photo,
+            DeletionContentType::PhotoAlbum,
+            DeletionContentType::Video,
+            DeletionContentType::VideoCategory,
+        ]
+    }
+
+    pub fn as_display(&self) -> &'static str {
+        match self {
+            DeletionContentType::Band => "Band",
+            DeletionContentType::Album => "Album",
+            DeletionContentType::Lyrics => "Lyrics",
+            DeletionContentType::Tabs => "Tabs",
+            DeletionContentType::Photo => "Photo",
+            DeletionContentType::PhotoAlbum => "Photo Album",
+            DeletionContentType::Video => "Video",
+            DeletionContentType::VideoCategory => "Video Category",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq, Type)]
+#[sqlx(type_name = "status")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum QueuedDeletionStatus {
+    #[default]
+    Pending,
+    Approved,
+    Undone,
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct QueuedDeletion {
+    pub id: i32,
+    pub content_type: DeletionContentType,
+    pub content_id: i32,
+    pub username: String,
+    pub request_time: NaiveDateTime,
+    pub status: QueuedDeletionStatus,
+    pub description: String,
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct TrashedContent {
+    pub content_id: i32,
+    pub description: String,
+    pub deleted_by: String,
+    pub delete_time: Option<NaiveDateTime>,
+}
+
+/**
+ * Describes the deleted content in the queue, since the rows are hidden everywhere else.
+ */
+const QUEUED_DELETION_COLUMNS: &str = r#"
+    deletion_queue.id,
+    deletion_queue.content_type,
+    deletion_queue.content_id,
+    deletion_queue.username,
+    deletion_queue.request_time,
+    deletion_queue.status,
+    COALESCE(CASE deletion_queue.content_type
+        WHEN 'band' THEN (SELECT band_name FROM bands WHERE bands.id = deletion_queue.content_id)
+        WHEN 'album' THEN (
+            SELECT CONCAT(albums.album_name, ' (', bands.band_name, ')')
+            FROM albums JOIN bands ON albums.band = bands.id
+            WHERE albums.id = deletion_queue.content_id
+        )
+        WHEN 'lyrics' THEN (
+            SELECT CONCAT(songs.song_name, ' by ', lyrics.username)
+            FROM lyrics JOIN songs ON lyrics.song = songs.id
+            WHERE lyrics.id = deletion_queue.content_id
+        )
+        WHEN 'tabs' THEN (
+            SELECT CONCAT(songs.song_name, ' (', tabs.tab_type, ') by ', tabs.username)
+            FROM tabs JOIN songs ON tabs.song = songs.id
+            WHERE tabs.id = deletion_queue.content_id
+        )
+        WHEN 'photo' THEN (SELECT title FROM photos WHERE photos.id = deletion_queue.content_id)
+        WHEN 'photo_album' THEN (SELECT title FROM photo_albums WHERE photo_albums.id = deletion_queue.content_id)
+        WHEN 'video' THEN (SELECT title FROM videos WHERE videos.id = deletion_queue.content_id)
+        WHEN 'video_category' THEN (SELECT title FROM video_categories WHERE video_categories.id = deletion_queue.content_id)
+    END, '') AS description
+"#;
+
+pub async fn get_pending_deletions_count() -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM deletion_queue WHERE status = 'pending'")
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn get_pending_deletions_in_range(
+    start: u32,
+    length: u32,
+) -> Result<Vec<QueuedDeletion>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, QueuedDeletion>(
+            format!(r#"
+                SELECT {}
+                FROM deletion_queue
+                WHERE deletion_queue.status = 'pending'
+                ORDER BY deletion_queue.request_time ASC, deletion_queue.id ASC
+                LIMIT ? OFFSET ?
+            "#, QUEUED_DELETION_COLUMNS).as_str()
+        )
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn get_queued_deletion_by_id(id: i32) -> Result<QueuedDeletion, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, QueuedDeletion>(
+            format!(r#"
+                SELECT {}
+                FROM deletion_queue
+                WHERE deletion_queue.id = ?
+                LIMIT 1
+            "#, QUEUED_DELETION_COLUMNS).as_str()
+        )
+            .bind(id)
+            .fetch_one(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Called by the mark_*_for_deletion functions, after the content was hidden.
+ */
+pub async fn queue_deletion(
+    content_type: DeletionContentType,
+    content_id: i32,
+    username: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+        VALUES (?, ?, ?, NOW(), 'pending')
+    "#)
+        .bind(content_type)
+        .bind(content_id)
+        .bind(username)
+        .execute(get_pool())
+        .await?;
+
+    Ok(())
+}
+
+async fn mark_queued_deletions_reviewed(
+    connection: &mut MySqlConnection,
+    content_type: &DeletionContentType,
+    content_id: i32,
+    status: QueuedDeletionStatus,
+    reviewed_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        UPDATE deletion_queue SET status = ?, reviewed_by = ?, review_time = NOW()
+        WHERE content_type = ? AND content_id = ? AND status = 'pending'
+    "#)
+        .bind(status)
+        .bind(reviewed_by)
+        .bind(content_type)
+        .bind(content_id)
+        .execute(connection)
+        .await?;
+
+    Ok(())
+}
+
+fn content_not_deleted_error() -> Box<dyn Error + Send + Sync> {
+    Box::new(
+        io::Error::new(io::ErrorKind::Other, "The content is not marked for deletion.")
+    )
+}
+
+pub async fn approve_queued_deletion(
+    queued_deletion: &QueuedDeletion,
+    reviewed_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    purge_deleted_content(&queued_deletion.content_type, queued_deletion.content_id, reviewed_by).await
+}
+
+pub async fn undo_queued_deletion(
+    queued_deletion: &QueuedDeletion,
+    reviewed_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    restore_deleted_content(&queued_deletion.content_type, queued_deletion.content_id, reviewed_by).await
+}
+
+/**
+ * Removes the deleted content for good, including uploaded images.
+ */
+pub async fn purge_deleted_content(
+    content_type: &DeletionContentType,
+    content_id: i32,
+    reviewed_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    let removed_images = delete_content_permanently(&mut *transaction, content_type, content_id).await?;
+    mark_queued_deletions_reviewed(&mut *transaction, content_type, content_id, QueuedDeletionStatus::Approved, reviewed_by).await?;
+
+    transaction.commit().await?;
+
+    // Failures are logged by remove_permanent_image, the rows are already gone at this point.
+    for (permanent_path, filename) in removed_images {
+        let _ = image_upload::remove_permanent_image(permanent_path, &filename).await;
+    }
+
+    Ok(())
+}
+
+/**
+ * Restores the deleted content, and anything that was hidden along with it.
+ */
+pub async fn restore_deleted_content(
+    content_type: &DeletionContentType,
+    content_id: i32,
+    reviewed_by: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    restore_content(&mut *transaction, content_type, content_id).await?;
+    mark_queued_deletions_reviewed(&mut *transaction, content_type, content_id, QueuedDeletionStatus::Undone, reviewed_by).await?;
+
+    transaction.commit().await?;
+    if matches!(content_type, DeletionContentType::Band | DeletionContentType::Album) {
+        mark_search_index_stale();
+    }
+
+    Ok(())
+}
+
+/**
+ * Content with a pending deletion that was requested more than "retention_days" ago.
+ */
+pub async fn get_expired_deleted_content(
+    retention_days: u32,
+) -> Result<Vec<(DeletionContentType, i32)>, Box<dyn Error + Send + Sync>> {
+    Ok(
+        sqlx::query_as::<MySql, (DeletionContentType, i32)>(r#"
+            SELECT content_type, content_id
+            FROM deletion_queue
+            WHERE status = 'pending'
+            GROUP BY content_type, content_id
+            HAVING MAX(request_time) < NOW() - INTERVAL ? DAY
+        "#)
+            .bind(retention_days)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+fn get_content_table(content_type: &DeletionContentType) -> &'static str {
+    match content_type {
+        DeletionContentType::Band => "bands",
+        DeletionContentType::Album => "albums",
+        DeletionContentType::Lyrics => "lyrics",
+        DeletionContentType::Tabs => "tabs",
+        DeletionContentType::Photo => "photos",
+        DeletionContentType::PhotoAlbum => "photo_albums",
+        DeletionContentType::Video => "videos",
+        DeletionContentType::VideoCategory => "video_categories",
+    }
+}
+
+/**
+ * Same as the descriptions in QUEUED_DELETION_COLUMNS, for a row of the content table aliased as "content".
+ */
+fn get_content_description_column(content_type: &DeletionContentType) -> &'static str {
+    match content_type {
+        DeletionContentType::Band => "content.band_name",
+        DeletionContentType::Album => r#"
+            CONCAT(content.album_name, ' (', COALESCE((SELECT band_name FROM bands WHERE bands.id = content.band), ''), ')')
+        "#,
+        DeletionContentType::Lyrics => r#"
+            CONCAT(COALESCE((SELECT song_name FROM songs WHERE songs.id = content.song), ''), ' by ', content.username)
+        "#,
+        DeletionContentType::Tabs => r#"
+            CONCAT(COALESCE((SELECT song_name FROM songs WHERE songs.id = content.song), ''), ' (', content.tab_type, ') by ', content.username)
+        "#,
+        _ => "content.title",
+    }
+}
+
+pub async fn get_trashed_content_count(
+    content_type: &DeletionContentType,
+) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query(
+            format!("SELECT COUNT(*) FROM {} WHERE is_deleted = 1", get_content_table(content_type)).as_str()
+        )
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+/**
+ * Soft-deleted rows of one content type, with who deleted them and when. Content that was
+ * deleted before the deletion queue existed may not have either.
+ */
+pub async fn get_trashed_content_in_range(
+    content_type: &DeletionContentType,
+    start: u32,
+    length: u32,
+) -> Result<Vec<TrashedContent>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, TrashedContent>(
+            format!(r#"
+                SELECT
+                    content.id AS content_id,
+                    COALESCE({}, '') AS description,
+                    COALESCE(deletion_queue.username, '') AS deleted_by,
+                    deletion_queue.request_time AS delete_time
+                FROM {} AS content
+                LEFT JOIN deletion_queue ON deletion_queue.id = (
+                    SELECT MAX(latest_deletion.id)
+                    FROM deletion_queue AS latest_deletion
+                    WHERE latest_deletion.content_type = ? AND latest_deletion.content_id = content.id
+                )
+                WHERE content.is_deleted = 1
+                ORDER BY deletion_queue.request_time DESC, content.id DESC
+                LIMIT ? OFFSET ?
+            "#, get_content_description_column(content_type), get_content_table(content_type)).as_str()
+        )
+            .bind(content_type)
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Deletes the rows of soft-deleted content. Returns the uploaded images that belonged
+ * to it as (folder, filename) pairs, which should be removed once the transaction commits.
+ */
+async fn delete_content_permanently(
+    connection: &mut MySqlConnection,
+    content_type: &DeletionContentType,
+    content_id: i32,
+) -> Result<Vec<(&'static str, String)>, Box<dyn Error + Send + Sync>> {
+    let mut removed_images: Vec<(&'static str, String)> = Vec::new();
+
+    match content_type {
+        DeletionContentType::Band => {
+            // Albums still in the trash count too, they could be restored without their band.
+            let album_count = sqlx::query("SELECT COUNT(*) FROM albums WHERE band = ?")
+                .bind(content_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .get::<i64, usize>(0);
+            if album_count > 0 {
+                return Err(
+                    Box::new(
+                        io::Error::new(io::ErrorKind::Other, "The band still has albums.")
+                    )
+                );
+            }
+        },
+        DeletionContentType::Album => {
+            let cover_picture_filename = sqlx::query("SELECT cover_picture_filename FROM albums WHERE id = ? AND is_deleted = 1")
+                .bind(content_id)
+                .fetch_optional(&mut *connection)
+                .await?
+                .ok_or_else(content_not_deleted_error)?
+                .get::<String, usize>(0);
+            removed_images.push(("album-covers", cover_picture_filename));
+
+            // Songs that were orphaned when the album was marked for deletion go with it.
+            let orphaned_song_ids: Vec<i32> = sqlx::query(r#"
+                SELECT songs.id
+                FROM songs
+                JOIN album_tracks ON album_tracks.song_id = songs.id
+                WHERE album_tracks.album_id = ? AND songs.is_deleted = 1 AND NOT EXISTS (
+                    SELECT 1 FROM album_tracks AS other_tracks
+                    WHERE other_tracks.song_id = songs.id AND other_tracks.album_id <> ?
+                )
+            "#)
+                .bind(content_id)
+                .bind(content_id)
+                .fetch_all(&mut *connection)
+                .await?
+                .iter()
+                .map(|row| row.get::<i32, usize>(0))
+                .collect();
+
+            if orphaned_song_ids.len() > 0 {
+                let ids_group = orphaned_song_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+                sqlx::query(format!(r#"
+                    DELETE lyrics_translations FROM lyrics_translations
+                    JOIN lyrics ON lyrics_translations.lyrics_id = lyrics.id
+                    WHERE lyrics.song IN ({})
+                "#, ids_group).as_str())
+                    .execute(&mut *connection)
+                    .await?;
+                sqlx::query(format!(r#"
+                    DELETE lyrics_revision_translations FROM lyrics_revision_translations
+                    JOIN lyrics_revisions ON lyrics_revision_translations.revision_id = lyrics_revisions.id
+                    JOIN lyrics ON lyrics_revisions.lyrics_id = lyrics.id
+                    WHERE lyrics.song IN ({})
+                "#, ids_group).as_str())
+                    .execute(&mut *connection)
+                    .await?;
+                sqlx::query(format!(r#"
+                    DELETE lyrics_revisions FROM lyrics_revisions
+                    JOIN lyrics ON lyrics_revisions.lyrics_id = lyrics.id
+                    WHERE lyrics.song IN ({})
+                "#, ids_group).as_str())
+                    .execute(&mut *connection)
+                    .await?;
+                sqlx::query(format!(r#"
+                    DELETE tab_revisions FROM tab_revisions
+                    JOIN tabs ON tab_revisions.tab_id = tabs.id
+                    WHERE tabs.song IN ({})
+                "#, ids_group).as_str())
+                    .execute(&mut *connection)
+                    .await?;
+                for (table, column) in [("lyrics", "song"), ("tabs", "song"), ("songs", "id")] {
+                    sqlx::query(format!("DELETE FROM {} WHERE {} IN ({})", table, column, ids_group).as_str())
+                        .execute(&mut *connection)
+                        .await?;
+                }
+            }
+
+            sqlx::query("DELETE FROM album_tracks WHERE album_id = ?")
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        DeletionContentType::Photo => {
+            let photo_filename = sqlx::query("SELECT photo_filename FROM photos WHERE id = ? AND is_deleted = 1")
+                .bind(content_id)
+                .fetch_optional(&mut *connection)
+                .await?
+                .ok_or_else(content_not_deleted_error)?
+                .get::<String, usize>(0);
+            removed_images.push(("photos", photo_filename));
+        },
+        DeletionContentType::PhotoAlbum => {
+            // A photo restored after the album was marked would be left without an album.
+            let live_photo_count = sqlx::query("SELECT COUNT(*) FROM photos WHERE album = ? AND is_deleted = 0")
+                .bind(content_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .get::<i64, usize>(0);
+            if live_photo_count > 0 {
+                return Err(
+                    Box::new(
+                        io::Error::new(io::ErrorKind::Other, "The photo album still has photos.")
+                    )
+                );
+            }
+            let photo_filenames = sqlx::query(r#"
+                SELECT photos.photo_filename
+                FROM photos
+                JOIN photo_albums ON photos.album = photo_albums.id
+                WHERE photo_albums.id = ? AND photo_albums.is_deleted = 1 AND photos.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .fetch_all(&mut *connection)
+                .await?;
+            for row in photo_filenames {
+                removed_images.push(("photos", row.get::<String, usize>(0)));
+            }
+            sqlx::query(r#"
+                DELETE photos FROM photos
+                JOIN photo_albums ON photos.album = photo_albums.id
+                WHERE photo_albums.id = ? AND photo_albums.is_deleted = 1 AND photos.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        DeletionContentType::VideoCategory => {
+            let live_video_count = sqlx::query("SELECT COUNT(*) FROM videos WHERE category = ? AND is_deleted = 0")
+                .bind(content_id)
+                .fetch_one(&mut *connection)
+                .await?
+                .get::<i64, usize>(0);
+            if live_video_count > 0 {
+                return Err(
+                    Box::new(
+                        io::Error::new(io::ErrorKind::Other, "The video category still has videos.")
+                    )
+                );
+            }
+            sqlx::query(r#"
+                DELETE videos FROM videos
+                JOIN video_categories ON videos.category = video_categories.id
+                WHERE video_categories.id = ? AND video_categories.is_deleted = 1 AND videos.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        DeletionContentType::Lyrics => {
+            sqlx::query(r#"
+                DELETE lyrics_translations FROM lyrics_translations
+                JOIN lyrics ON lyrics_translations.lyrics_id = lyrics.id
+                WHERE lyrics.id = ? AND lyrics.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+            sqlx::query(r#"
+                DELETE lyrics_revision_translations FROM lyrics_revision_translations
+                JOIN lyrics_revisions ON lyrics_revision_translations.revision_id = lyrics_revisions.id
+                JOIN lyrics ON lyrics_revisions.lyrics_id = lyrics.id
+                WHERE lyrics.id = ? AND lyrics.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+            sqlx::query(r#"
+                DELETE lyrics_revisions FROM lyrics_revisions
+                JOIN lyrics ON lyrics_revisions.lyrics_id = lyrics.id
+                WHERE lyrics.id = ? AND lyrics.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        DeletionContentType::Tabs => {
+            sqlx::query(r#"
+                DELETE tab_revisions FROM tab_revisions
+                JOIN tabs ON tab_revisions.tab_id = tabs.id
+                WHERE tabs.id = ? AND tabs.is_deleted = 1
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        _ => {},
+    }
+
+    let deleted = sqlx::query(format!("DELETE FROM {} WHERE id = ? AND is_deleted = 1", get_content_table(content_type)).as_str())
+        .bind(content_id)
+        .execute(&mut *connection)
+        .await?
+        .rows_affected() > 0;
+    if !deleted {
+        return Err(content_not_deleted_error());
+    }
+
+    Ok(removed_images)
+}
+
+/**
+ * Clears "is_deleted" on the content, and recomputes data that was derived when it was deleted.
+ */
+async fn restore_content(
+    connection: &mut MySqlConnection,
+    content_type: &DeletionContentType,
+    content_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let restored = sqlx::query(format!("UPDATE {} SET is_deleted = 0 WHERE id = ? AND is_deleted = 1", get_content_table(content_type)).as_str())
+        .bind(content_id)
+        .execute(&mut *connection)
+        .await?
+        .rows_affected() > 0;
+    if !restored {
+        return Err(content_not_deleted_error());
+    }
+
+    match content_type {
+        DeletionContentType::Album => {
+            // Songs orphaned by mark_album_for_deletion belong to the album again.
+            sqlx::query(r#"
+                UPDATE songs
+                SET is_deleted = 0
+                WHERE is_deleted = 1 AND id IN (
+                    SELECT song_id FROM album_tracks WHERE album_id = ?
+                )
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        DeletionContentType::Tabs => {
+            sqlx::query(r#"
+                UPDATE songs
+                SET tab_count = (SELECT COUNT(*) FROM tabs WHERE tabs.song = songs.id AND tabs.is_deleted = 0)
+                WHERE id = (SELECT song FROM tabs WHERE tabs.id = ?)
+            "#)
+                .bind(content_id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
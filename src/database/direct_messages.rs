@@ -0,0 +1,214 @@
+use std::error::Error;
+use chrono::NaiveDateTime;
+use sqlx::{
+    FromRow,
+    MySql,
+    Row,
+};
+
+use super::get_pool;
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct DirectMessage {
+    pub id: i32,
+    pub conversation_id: i32,
+    pub sender_id: i32,
+    pub recipient_id: i32,
+    pub post_time: NaiveDateTime,
+    pub message: String,
+    pub is_read: bool,
+    pub sender_username: String,
+    pub sender_profile_picture_filename: String,
+}
+
+/**
+ * A conversation as seen by one of its two members.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct ConversationSummary {
+    pub id: i32,
+    pub last_message_time: NaiveDateTime,
+    pub other_username: String,
+    pub other_profile_picture_filename: String,
+    pub last_message: String,
+    pub unread_count: i64,
+}
+
+fn order_user_ids(user_id: i32, other_user_id: i32) -> (i32, i32) {
+    if user_id < other_user_id { (user_id, other_user_id) } else { (other_user_id, user_id) }
+}
+
+pub async fn get_conversations_count(user_id: i32) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM conversations WHERE user_a_id=? OR user_b_id=?")
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn get_conversations_in_range(
+    user_id: i32,
+    start: u32,
+    length: u32,
+) -> Result<Vec<ConversationSummary>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, ConversationSummary>(r#"
+            SELECT
+                conversations.id,
+                conversations.last_message_time,
+                users.username AS other_username,
+                users.profile_picture_filename AS other_profile_picture_filename,
+                COALESCE((
+                    SELECT message FROM direct_messages
+                    WHERE direct_messages.conversation_id = conversations.id
+                    ORDER BY direct_messages.id DESC
+                    LIMIT 1
+                ), '') AS last_message,
+                (
+                    SELECT COUNT(*) FROM direct_messages
+                    WHERE direct_messages.conversation_id = conversations.id
+                        AND direct_messages.recipient_id = ?
+                        AND direct_messages.is_read = 0
+                ) AS unread_count
+            FROM conversations
+            INNER JOIN users ON users.id = IF(conversations.user_a_id = ?, conversations.user_b_id, conversations.user_a_id)
+            WHERE (conversations.user_a_id = ? OR conversations.user_b_id = ?)
+                AND users.is_deleted = 0
+            ORDER BY conversations.last_message_time DESC
+            LIMIT ? OFFSET ?
+        "#)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn get_conversation_id(user_id: i32, other_user_id: i32) -> Result<Option<i32>, Box<dyn Error>> {
+    let (user_a_id, user_b_id) = order_user_ids(user_id, other_user_id);
+    Ok(
+        sqlx::query("SELECT id FROM conversations WHERE user_a_id=? AND user_b_id=? LIMIT 1")
+            .bind(user_a_id)
+            .bind(user_b_id)
+            .fetch_optional(get_pool())
+            .await?
+            .map(|row| row.get::<i32, usize>(0))
+    )
+}
+
+pub async fn get_direct_messages_count(conversation_id: i32) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM direct_messages WHERE conversation_id=?")
+            .bind(conversation_id)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+/**
+ * Messages of a conversation, newest first.
+ */
+pub async fn get_direct_messages_in_range(
+    conversation_id: i32,
+    start: u32,
+    length: u32,
+) -> Result<Vec<DirectMessage>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, DirectMessage>(r#"
+            SELECT
+                direct_messages.*,
+                users.username AS sender_username,
+                users.profile_picture_filename AS sender_profile_picture_filename
+            FROM direct_messages
+            LEFT JOIN users ON users.id = direct_messages.sender_id
+            WHERE direct_messages.conversation_id = ?
+            ORDER BY direct_messages.id DESC
+            LIMIT ? OFFSET ?
+        "#)
+            .bind(conversation_id)
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn get_unread_direct_message_count(user_id: i32) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM direct_messages WHERE recipient_id=? AND is_read=0")
+            .bind(user_id)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn mark_conversation_read(
+    conversation_id: i32,
+    recipient_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("UPDATE direct_messages SET is_read=1 WHERE conversation_id=? AND recipient_id=? AND is_read=0")
+        .bind(conversation_id)
+        .bind(recipient_id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+/**
+ * Creates the conversation between the two members if this is their first message.
+ */
+pub async fn create_direct_message(
+    sender_id: i32,
+    recipient_id: i32,
+    message: &str,
+) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    let (user_a_id, user_b_id) = order_user_ids(sender_id, recipient_id);
+
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
+        INSERT INTO conversations (user_a_id, user_b_id, create_time, last_message_time)
+        VALUES (?, ?, NOW(), NOW())
+        ON DUPLICATE KEY UPDATE last_message_time=NOW()
+    "#)
+        .bind(user_a_id)
+        .bind(user_b_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    let conversation_id = sqlx::query("SELECT id FROM conversations WHERE user_a_id=? AND user_b_id=? LIMIT 1")
+        .bind(user_a_id)
+        .bind(user_b_id)
+        .fetch_one(&mut *transaction)
+        .await?
+        .get::<i32, usize>(0);
+
+    sqlx::query(r#"
+        INSERT INTO direct_messages (conversation_id, sender_id, recipient_id, post_time, message, is_read)
+        VALUES (?, ?, ?, NOW(), ?, 0)
+    "#)
+        .bind(conversation_id)
+        .bind(sender_id)
+        .bind(recipient_id)
+        .bind(message)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(conversation_id)
+}
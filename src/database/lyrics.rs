@@ -1,10 +1,17 @@
+use std::collections::HashMap;
 use std::error::Error;
+use std::io;
+use std::str::FromStr;
 use chrono::{ NaiveDateTime };
 use sqlx::{
     FromRow,
     MySql,
+    MySqlConnection,
+    Row,
 };
+use strum_macros::{ Display, EnumString };
 use super::get_pool;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
 
 #[allow(unused)]
 #[derive(Debug, Default, Clone, FromRow)]
@@ -15,8 +22,107 @@ pub struct Lyrics {
     pub song: i32,
     pub kanji_content: String,
     pub romaji_content: String,
-    pub english_content: String,
     pub comment: String,
+    pub line_timestamps: String,
+    pub video_url: String,
+    #[sqlx(skip)]
+    pub translations: Vec<LyricsTranslation>,
+}
+impl Lyrics {
+    pub fn get_translation(&self, language: &LyricsLanguage) -> &str {
+        find_translation(&self.translations, language)
+    }
+
+    /**
+     * An empty translation removes the language from the lyrics when they are saved.
+     */
+    pub fn set_translation(&mut self, language: &LyricsLanguage, content: String) {
+        let language_code = language.to_string();
+        match self.translations.iter_mut().find(|translation| translation.language_code == language_code) {
+            Some(translation) => translation.content = content,
+            None => self.translations.push(LyricsTranslation { language_code, content }),
+        }
+    }
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct LyricsRevision {
+    pub id: i32,
+    pub lyrics_id: i32,
+    pub username: String,
+    pub revision_time: NaiveDateTime,
+    pub kanji_content: String,
+    pub romaji_content: String,
+    pub comment: String,
+    pub line_timestamps: String,
+    pub video_url: String,
+    #[sqlx(skip)]
+    pub translations: Vec<LyricsTranslation>,
+}
+impl LyricsRevision {
+    pub fn get_translation(&self, language: &LyricsLanguage) -> &str {
+        find_translation(&self.translations, language)
+    }
+}
+
+/**
+ * Languages that lyrics can be translated into, stored by their ISO 639-1 code.
+ */
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum LyricsLanguage {
+    #[default]
+    En,
+    Es,
+    Pt,
+    Fr,
+    Zh,
+}
+impl LyricsLanguage {
+    pub fn all() -> Vec<LyricsLanguage> {
+        vec![
+            LyricsLanguage::En,
+            LyricsLanguage::Es,
+            LyricsLanguage::Pt,
+            LyricsLanguage::Fr,
+            LyricsLanguage::Zh,
+        ]
+    }
+
+    /**
+     * Unknown codes fall back to English.
+     */
+    pub fn from_code(language_code: &str) -> LyricsLanguage {
+        LyricsLanguage::from_str(language_code).unwrap_or_default()
+    }
+
+    pub fn as_display(&self) -> &'static str {
+        match self {
+            LyricsLanguage::En => "English",
+            LyricsLanguage::Es => "Español",
+            LyricsLanguage::Pt => "Português",
+            LyricsLanguage::Fr => "Français",
+            LyricsLanguage::Zh => "中文",
+        }
+    }
+
+    pub fn as_badge(&self) -> &'static str {
+        match self {
+            LyricsLanguage::En => "EN",
+            LyricsLanguage::Es => "ES",
+            LyricsLanguage::Pt => "PT",
+            LyricsLanguage::Fr => "FR",
+            LyricsLanguage::Zh => "ZH",
+        }
+    }
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct LyricsTranslation {
+    pub language_code: String,
+    pub content: String,
 }
 
 #[allow(unused)]
@@ -26,21 +132,158 @@ pub struct RecentLyricTranslation {
     pub song_name: String,
     pub album_slug: String,
     pub band_slug: String,
+    pub language_code: String,
     pub post_time: NaiveDateTime,
 }
 
-pub async fn get_recent_lyric_translations_by_band_id(band_id: i32) -> Result<Vec<RecentLyricTranslation>, Box<dyn Error>> {
+pub async fn get_recent_lyric_translations_by_band_id(
+    band_id: i32,
+    language: &LyricsLanguage,
+) -> Result<Vec<RecentLyricTranslation>, Box<dyn Error>> {
     let result = sqlx::query_as::<MySql, RecentLyricTranslation>(r#"
-        SELECT lyrics.post_time, songs.song_slug, songs.song_name, albums.album_slug, bands.band_slug
-        FROM lyrics
+        SELECT lyrics_translations.post_time, lyrics_translations.language_code,
+            songs.song_slug, songs.song_name, albums.album_slug, bands.band_slug
+        FROM lyrics_translations
+        JOIN lyrics ON lyrics_translations.lyrics_id = lyrics.id
         JOIN songs ON lyrics.song = songs.id
         JOIN albums ON songs.album = albums.id
         JOIN bands ON songs.band = bands.id
-        WHERE bands.id=? AND lyrics.is_deleted=0 AND songs.is_deleted=0
-        ORDER BY lyrics.post_time DESC
+        WHERE bands.id=? AND lyrics_translations.language_code=? AND lyrics.is_deleted=0 AND songs.is_deleted=0
+        ORDER BY lyrics_translations.post_time DESC
         LIMIT 5;
     "#)
         .bind(band_id)
+        .bind(language.to_string())
+        .fetch_all(get_pool())
+        .await?;
+
+    Ok(
+        result
+    )
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct LyricsContentSearchResult {
+    pub song_name: String,
+    pub song_slug: String,
+    pub album_slug: String,
+    pub band_slug: String,
+    pub band_name: String,
+    pub username: String,
+    pub kanji_content: String,
+    pub romaji_content: String,
+    pub translation_language_code: String,
+    pub translation_content: String,
+}
+
+/**
+ * Matches the search on the lyric content and on the translations. The relevance of both
+ * is added together, and one of the matching translations is kept to show its matching line.
+ */
+static LYRICS_CONTENT_MATCHES_SQL: &str = r#"
+    SELECT lyrics_id, SUM(relevance) AS relevance, MAX(translation_id) AS translation_id
+    FROM (
+        SELECT id AS lyrics_id, NULL AS translation_id,
+            MATCH (kanji_content, romaji_content) AGAINST (? IN BOOLEAN MODE) AS relevance
+        FROM lyrics
+        WHERE MATCH (kanji_content, romaji_content) AGAINST (? IN BOOLEAN MODE)
+        UNION ALL
+        SELECT lyrics_id, id AS translation_id,
+            MATCH (content) AGAINST (? IN BOOLEAN MODE) AS relevance
+        FROM lyrics_translations
+        WHERE MATCH (content) AGAINST (? IN BOOLEAN MODE)
+    ) AS content_matches
+    GROUP BY lyrics_id
+"#;
+
+/**
+ * Searches shorter than the ngram size of the full-text index can't match anything.
+ */
+const LYRICS_CONTENT_SEARCH_MIN_LENGTH: usize = 2;
+
+/**
+ * The search is quoted as a phrase in boolean mode, so its ngrams have to appear next to
+ * each other in the lyrics. In natural language mode any lyrics sharing a couple of
+ * characters with the search would match.
+ */
+fn create_lyrics_content_search_phrase(search: &str) -> Result<Option<String>, Box<dyn Error>> {
+    if search.len() > 200 {
+        return Err(
+            Box::new(
+                io::Error::new(io::ErrorKind::Other, "Search term was too long.")
+            )
+        );
+    }
+    let phrase = search.replace('"', " ").split_whitespace().collect::<Vec<&str>>().join(" ");
+    if phrase.chars().count() < LYRICS_CONTENT_SEARCH_MIN_LENGTH {
+        return Ok(None);
+    }
+    Ok(Some(format!("\"{}\"", phrase)))
+}
+
+pub async fn get_lyrics_content_search_count(search: &str) -> Result<u32, Box<dyn Error>> {
+    let Some(phrase) = create_lyrics_content_search_phrase(search)? else {
+        return Ok(0);
+    };
+    Ok(
+        u32::try_from(sqlx::query(format!(r#"
+            SELECT COUNT(*)
+            FROM ({}) AS lyrics_matches
+            JOIN lyrics ON lyrics.id = lyrics_matches.lyrics_id
+            JOIN songs ON lyrics.song = songs.id
+            WHERE lyrics.is_deleted=0 AND songs.is_deleted=0
+        "#, LYRICS_CONTENT_MATCHES_SQL).as_str())
+            .bind(&phrase)
+            .bind(&phrase)
+            .bind(&phrase)
+            .bind(&phrase)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+/**
+ * Lyrics whose kanji, rōmaji or translations contain the search, best matches first.
+ */
+pub async fn find_lyrics_by_content(
+    search: &str,
+    start: u32,
+    length: u32,
+) -> Result<Vec<LyricsContentSearchResult>, Box<dyn Error>> {
+    let Some(phrase) = create_lyrics_content_search_phrase(search)? else {
+        return Ok(Vec::new());
+    };
+    let result = sqlx::query_as::<MySql, LyricsContentSearchResult>(format!(r#"
+        SELECT
+            songs.song_name,
+            songs.song_slug,
+            albums.album_slug,
+            bands.band_slug,
+            bands.band_name,
+            lyrics.username,
+            lyrics.kanji_content,
+            lyrics.romaji_content,
+            COALESCE(lyrics_translations.language_code, '') AS translation_language_code,
+            COALESCE(lyrics_translations.content, '') AS translation_content
+        FROM ({}) AS lyrics_matches
+        JOIN lyrics ON lyrics.id = lyrics_matches.lyrics_id
+        JOIN songs ON lyrics.song = songs.id
+        JOIN albums ON songs.album = albums.id
+        JOIN bands ON songs.band = bands.id
+        LEFT JOIN lyrics_translations ON lyrics_translations.id = lyrics_matches.translation_id
+        WHERE lyrics.is_deleted=0 AND songs.is_deleted=0
+        ORDER BY lyrics_matches.relevance DESC, songs.song_name ASC, lyrics.post_time ASC
+        LIMIT ? OFFSET ?;
+    "#, LYRICS_CONTENT_MATCHES_SQL).as_str())
+        .bind(&phrase)
+        .bind(&phrase)
+        .bind(&phrase)
+        .bind(&phrase)
+        .bind(length)
+        .bind(start)
         .fetch_all(get_pool())
         .await?;
 
@@ -50,7 +293,7 @@ pub async fn get_recent_lyric_translations_by_band_id(band_id: i32) -> Result<Ve
 }
 
 pub async fn get_lyrics_by_song_id(song_id: i32) -> Result<Vec<Lyrics>, Box<dyn Error>> {
-    let result = sqlx::query_as::<MySql, Lyrics>(r#"
+    let mut result = sqlx::query_as::<MySql, Lyrics>(r#"
         SELECT * FROM lyrics
         WHERE song=? AND is_deleted=0
         ORDER BY lyrics.post_time ASC
@@ -60,13 +303,19 @@ pub async fn get_lyrics_by_song_id(song_id: i32) -> Result<Vec<Lyrics>, Box<dyn
         .fetch_all(get_pool())
         .await?;
 
+    let lyrics_ids: Vec<i32> = result.iter().map(|lyrics| lyrics.id).collect();
+    let mut translations_by_lyrics_id = get_translations_by_parent_id("lyrics_translations", "lyrics_id", &lyrics_ids).await?;
+    for lyrics in &mut result {
+        lyrics.translations = translations_by_lyrics_id.remove(&lyrics.id).unwrap_or_default();
+    }
+
     Ok(
         result
     )
 }
 
 pub async fn get_lyrics_by_username_and_song_id(username: &str, song_id: i32) -> Result<Lyrics, Box<dyn Error>> {
-    let result = sqlx::query_as::<MySql, Lyrics>(r#"
+    let mut result = sqlx::query_as::<MySql, Lyrics>(r#"
         SELECT * FROM lyrics
         WHERE username=? AND song=? AND is_deleted=0
         ORDER BY lyrics.post_time ASC
@@ -77,6 +326,10 @@ pub async fn get_lyrics_by_username_and_song_id(username: &str, song_id: i32) ->
         .fetch_one(get_pool())
         .await?;
 
+    result.translations = get_translations_by_parent_id("lyrics_translations", "lyrics_id", &vec![result.id]).await?
+        .remove(&result.id)
+        .unwrap_or_default();
+
     Ok(
         result
     )
@@ -85,60 +338,217 @@ pub async fn get_lyrics_by_username_and_song_id(username: &str, song_id: i32) ->
 pub async fn create_lyrics(
     lyrics: Lyrics,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let result = sqlx::query_as::<MySql, Lyrics>(r#"
+    let mut transaction = get_pool().begin().await?;
+
+    let lyrics_id = sqlx::query(r#"
         INSERT INTO lyrics (
-            username, post_time, song, kanji_content, romaji_content, english_content, comment
+            username, post_time, song, kanji_content, romaji_content, comment, line_timestamps, video_url
         )
-        VALUES (?, NOW(), ?, ?, ?, ?, ?)
+        VALUES (?, NOW(), ?, ?, ?, ?, ?, ?)
     "#)
         .bind(&lyrics.username)
         .bind(&lyrics.song)
-        .bind(lyrics.kanji_content)
-        .bind(lyrics.romaji_content)
-        .bind(lyrics.english_content)
-        .bind(lyrics.comment)
-        .fetch_optional(get_pool())
-        .await;
+        .bind(&lyrics.kanji_content)
+        .bind(&lyrics.romaji_content)
+        .bind(&lyrics.comment)
+        .bind(&lyrics.line_timestamps)
+        .bind(&lyrics.video_url)
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_id();
 
-    match result {
-        Ok(_) => {
-            Ok(())
-        }
-        Err(e) => {
-            Err(Box::new(e))
-        }
-    }
+    let lyrics = Lyrics { id: i32::try_from(lyrics_id)?, ..lyrics };
+    save_lyrics_translations(&mut *transaction, &lyrics).await?;
+    insert_lyrics_revision(&mut *transaction, &lyrics, &lyrics.username).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
 }
 
+/**
+ * "edited_by" may be a moderator with the EditLyrics permission, not the translator.
+ */
 pub async fn update_lyrics(
     lyrics: Lyrics,
+    edited_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let result = sqlx::query_as::<MySql, Lyrics>(r#"
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
         UPDATE lyrics
-        SET kanji_content=?, romaji_content=?, english_content=?, comment=?
+        SET kanji_content=?, romaji_content=?, comment=?, line_timestamps=?, video_url=?
         WHERE id=?
         LIMIT 1
     "#)
-        .bind(lyrics.kanji_content)
-        .bind(lyrics.romaji_content)
-        .bind(lyrics.english_content)
-        .bind(lyrics.comment)
+        .bind(&lyrics.kanji_content)
+        .bind(&lyrics.romaji_content)
+        .bind(&lyrics.comment)
+        .bind(&lyrics.line_timestamps)
+        .bind(&lyrics.video_url)
         .bind(lyrics.id)
-        .fetch_optional(get_pool())
-        .await;
+        .execute(&mut *transaction)
+        .await?;
 
-    match result {
-        Ok(_) => {
-            Ok(())
+    save_lyrics_translations(&mut *transaction, &lyrics).await?;
+    insert_lyrics_revision(&mut *transaction, &lyrics, edited_by).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/**
+ * Translations that were cleared are removed, so they no longer count towards the language.
+ */
+async fn save_lyrics_translations(
+    connection: &mut MySqlConnection,
+    lyrics: &Lyrics,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for translation in &lyrics.translations {
+        if translation.content.trim().is_empty() {
+            sqlx::query(r#"
+                DELETE FROM lyrics_translations
+                WHERE lyrics_id=? AND language_code=?
+            "#)
+                .bind(lyrics.id)
+                .bind(&translation.language_code)
+                .execute(&mut *connection)
+                .await?;
+        } else {
+            sqlx::query(r#"
+                INSERT INTO lyrics_translations (lyrics_id, language_code, post_time, content)
+                VALUES (?, ?, NOW(), ?)
+                ON DUPLICATE KEY UPDATE content=VALUES(content)
+            "#)
+                .bind(lyrics.id)
+                .bind(&translation.language_code)
+                .bind(&translation.content)
+                .execute(&mut *connection)
+                .await?;
         }
-        Err(e) => {
-            Err(Box::new(e))
+    }
+
+    Ok(())
+}
+
+async fn insert_lyrics_revision(
+    connection: &mut MySqlConnection,
+    lyrics: &Lyrics,
+    username: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let revision_id = sqlx::query(r#"
+        INSERT INTO lyrics_revisions (
+            lyrics_id, username, revision_time, kanji_content, romaji_content, comment, line_timestamps, video_url
+        )
+        VALUES (?, ?, NOW(), ?, ?, ?, ?, ?)
+    "#)
+        .bind(lyrics.id)
+        .bind(username)
+        .bind(&lyrics.kanji_content)
+        .bind(&lyrics.romaji_content)
+        .bind(&lyrics.comment)
+        .bind(&lyrics.line_timestamps)
+        .bind(&lyrics.video_url)
+        .execute(&mut *connection)
+        .await?
+        .last_insert_id();
+
+    for translation in &lyrics.translations {
+        if translation.content.trim().is_empty() {
+            continue;
         }
+        sqlx::query(r#"
+            INSERT INTO lyrics_revision_translations (revision_id, language_code, content)
+            VALUES (?, ?, ?)
+        "#)
+            .bind(revision_id)
+            .bind(&translation.language_code)
+            .bind(&translation.content)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Newest first, the first revision has the current content of the lyrics.
+ */
+pub async fn get_lyrics_revisions(lyrics_id: i32) -> Result<Vec<LyricsRevision>, Box<dyn Error>> {
+    let mut result = sqlx::query_as::<MySql, LyricsRevision>(r#"
+        SELECT * FROM lyrics_revisions
+        WHERE lyrics_id=?
+        ORDER BY revision_time DESC, id DESC
+    "#)
+        .bind(lyrics_id)
+        .fetch_all(get_pool())
+        .await?;
+
+    let revision_ids: Vec<i32> = result.iter().map(|revision| revision.id).collect();
+    let mut translations_by_revision_id = get_translations_by_parent_id("lyrics_revision_translations", "revision_id", &revision_ids).await?;
+    for revision in &mut result {
+        revision.translations = translations_by_revision_id.remove(&revision.id).unwrap_or_default();
     }
+
+    Ok(
+        result
+    )
+}
+
+/**
+ * Translations of each lyrics or revision id, in the order of LyricsLanguage::all().
+ */
+async fn get_translations_by_parent_id(
+    table: &str,
+    parent_column: &str,
+    parent_ids: &Vec<i32>,
+) -> Result<HashMap<i32, Vec<LyricsTranslation>>, Box<dyn Error>> {
+    let mut translations_by_parent_id: HashMap<i32, Vec<LyricsTranslation>> = HashMap::new();
+    if parent_ids.len() == 0 {
+        return Ok(translations_by_parent_id);
+    }
+
+    let ids_group = parent_ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    let rows = sqlx::query(format!(r#"
+        SELECT {}, language_code, content FROM {}
+        WHERE {} IN ({})
+    "#, parent_column, table, parent_column, ids_group).as_str())
+        .fetch_all(get_pool())
+        .await?;
+
+    for row in rows {
+        translations_by_parent_id.entry(row.get::<i32, usize>(0)).or_default().push(LyricsTranslation {
+            language_code: row.get::<String, usize>(1),
+            content: row.get::<String, usize>(2),
+        });
+    }
+    let languages = LyricsLanguage::all();
+    for translations in translations_by_parent_id.values_mut() {
+        translations.sort_by_key(|translation| {
+            languages.iter().position(|language| language.to_string() == translation.language_code)
+                .unwrap_or(languages.len())
+        });
+    }
+
+    Ok(translations_by_parent_id)
+}
+
+fn find_translation<'a>(translations: &'a [LyricsTranslation], language: &LyricsLanguage) -> &'a str {
+    let language_code = language.to_string();
+    translations.iter()
+        .find(|translation| translation.language_code == language_code)
+        .map(|translation| translation.content.as_str())
+        .unwrap_or("")
 }
 
 pub async fn mark_lyrics_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Lyrics>(r#"
         UPDATE lyrics
@@ -152,7 +562,7 @@ pub async fn mark_lyrics_for_deletion(
     
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::Lyrics, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
@@ -0,0 +1,210 @@
+/**
+ * Moves album track lists out of the forty "song0".."song39" columns on albums into an
+ * ordered album_tracks relation, which allows any number of tracks and multiple discs.
+ *
+ * Existing track lists are copied to disc 1, keeping their order and skipping empty slots.
+ * Reverting copies the first forty tracks of each album back into the old columns.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 2,
+    name: "album_tracks",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS album_tracks (
+                album_id INT(11) NOT NULL,
+                song_id INT(11) NOT NULL,
+                disc_number INT(11) NOT NULL DEFAULT 1,
+                track_number INT(11) NOT NULL,
+                PRIMARY KEY (album_id, disc_number, track_number),
+                INDEX album_tracks_song_id (song_id)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO album_tracks (album_id, song_id, disc_number, track_number)
+            SELECT
+                legacy_tracks.album_id,
+                legacy_tracks.song_id,
+                1,
+                ROW_NUMBER() OVER (PARTITION BY legacy_tracks.album_id ORDER BY legacy_tracks.slot)
+            FROM (
+                    SELECT id AS album_id, song0 AS song_id, 0 AS slot FROM albums WHERE song0 <> 0
+                    UNION ALL SELECT id AS album_id, song1 AS song_id, 1 AS slot FROM albums WHERE song1 <> 0
+                    UNION ALL SELECT id AS album_id, song2 AS song_id, 2 AS slot FROM albums WHERE song2 <> 0
+                    UNION ALL SELECT id AS album_id, song3 AS song_id, 3 AS slot FROM albums WHERE song3 <> 0
+                    UNION ALL SELECT id AS album_id, song4 AS song_id, 4 AS slot FROM albums WHERE song4 <> 0
+                    UNION ALL SELECT id AS album_id, song5 AS song_id, 5 AS slot FROM albums WHERE song5 <> 0
+                    UNION ALL SELECT id AS album_id, song6 AS song_id, 6 AS slot FROM albums WHERE song6 <> 0
+                    UNION ALL SELECT id AS album_id, song7 AS song_id, 7 AS slot FROM albums WHERE song7 <> 0
+                    UNION ALL SELECT id AS album_id, song8 AS song_id, 8 AS slot FROM albums WHERE song8 <> 0
+                    UNION ALL SELECT id AS album_id, song9 AS song_id, 9 AS slot FROM albums WHERE song9 <> 0
+                    UNION ALL SELECT id AS album_id, song10 AS song_id, 10 AS slot FROM albums WHERE song10 <> 0
+                    UNION ALL SELECT id AS album_id, song11 AS song_id, 11 AS slot FROM albums WHERE song11 <> 0
+                    UNION ALL SELECT id AS album_id, song12 AS song_id, 12 AS slot FROM albums WHERE song12 <> 0
+                    UNION ALL SELECT id AS album_id, song13 AS song_id, 13 AS slot FROM albums WHERE song13 <> 0
+                    UNION ALL SELECT id AS album_id, song14 AS song_id, 14 AS slot FROM albums WHERE song14 <> 0
+                    UNION ALL SELECT id AS album_id, song15 AS song_id, 15 AS slot FROM albums WHERE song15 <> 0
+                    UNION ALL SELECT id AS album_id, song16 AS song_id, 16 AS slot FROM albums WHERE song16 <> 0
+                    UNION ALL SELECT id AS album_id, song17 AS song_id, 17 AS slot FROM albums WHERE song17 <> 0
+                    UNION ALL SELECT id AS album_id, song18 AS song_id, 18 AS slot FROM albums WHERE song18 <> 0
+                    UNION ALL SELECT id AS album_id, song19 AS song_id, 19 AS slot FROM albums WHERE song19 <> 0
+                    UNION ALL SELECT id AS album_id, song20 AS song_id, 20 AS slot FROM albums WHERE song20 <> 0
+                    UNION ALL SELECT id AS album_id, song21 AS song_id, 21 AS slot FROM albums WHERE song21 <> 0
+                    UNION ALL SELECT id AS album_id, song22 AS song_id, 22 AS slot FROM albums WHERE song22 <> 0
+                    UNION ALL SELECT id AS album_id, song23 AS song_id, 23 AS slot FROM albums WHERE song23 <> 0
+                    UNION ALL SELECT id AS album_id, song24 AS song_id, 24 AS slot FROM albums WHERE song24 <> 0
+                    UNION ALL SELECT id AS album_id, song25 AS song_id, 25 AS slot FROM albums WHERE song25 <> 0
+                    UNION ALL SELECT id AS album_id, song26 AS song_id, 26 AS slot FROM albums WHERE song26 <> 0
+                    UNION ALL SELECT id AS album_id, song27 AS song_id, 27 AS slot FROM albums WHERE song27 <> 0
+                    UNION ALL SELECT id AS album_id, song28 AS song_id, 28 AS slot FROM albums WHERE song28 <> 0
+                    UNION ALL SELECT id AS album_id, song29 AS song_id, 29 AS slot FROM albums WHERE song29 <> 0
+                    UNION ALL SELECT id AS album_id, song30 AS song_id, 30 AS slot FROM albums WHERE song30 <> 0
+                    UNION ALL SELECT id AS album_id, song31 AS song_id, 31 AS slot FROM albums WHERE song31 <> 0
+                    UNION ALL SELECT id AS album_id, song32 AS song_id, 32 AS slot FROM albums WHERE song32 <> 0
+                    UNION ALL SELECT id AS album_id, song33 AS song_id, 33 AS slot FROM albums WHERE song33 <> 0
+                    UNION ALL SELECT id AS album_id, song34 AS song_id, 34 AS slot FROM albums WHERE song34 <> 0
+                    UNION ALL SELECT id AS album_id, song35 AS song_id, 35 AS slot FROM albums WHERE song35 <> 0
+                    UNION ALL SELECT id AS album_id, song36 AS song_id, 36 AS slot FROM albums WHERE song36 <> 0
+                    UNION ALL SELECT id AS album_id, song37 AS song_id, 37 AS slot FROM albums WHERE song37 <> 0
+                    UNION ALL SELECT id AS album_id, song38 AS song_id, 38 AS slot FROM albums WHERE song38 <> 0
+                    UNION ALL SELECT id AS album_id, song39 AS song_id, 39 AS slot FROM albums WHERE song39 <> 0
+            ) AS legacy_tracks;
+        "#),
+        MigrationStep::Sql(r#"
+            ALTER TABLE albums
+                DROP COLUMN song0,
+                DROP COLUMN song1,
+                DROP COLUMN song2,
+                DROP COLUMN song3,
+                DROP COLUMN song4,
+                DROP COLUMN song5,
+                DROP COLUMN song6,
+                DROP COLUMN song7,
+                DROP COLUMN song8,
+                DROP COLUMN song9,
+                DROP COLUMN song10,
+                DROP COLUMN song11,
+                DROP COLUMN song12,
+                DROP COLUMN song13,
+                DROP COLUMN song14,
+                DROP COLUMN song15,
+                DROP COLUMN song16,
+                DROP COLUMN song17,
+                DROP COLUMN song18,
+                DROP COLUMN song19,
+                DROP COLUMN song20,
+                DROP COLUMN song21,
+                DROP COLUMN song22,
+                DROP COLUMN song23,
+                DROP COLUMN song24,
+                DROP COLUMN song25,
+                DROP COLUMN song26,
+                DROP COLUMN song27,
+                DROP COLUMN song28,
+                DROP COLUMN song29,
+                DROP COLUMN song30,
+                DROP COLUMN song31,
+                DROP COLUMN song32,
+                DROP COLUMN song33,
+                DROP COLUMN song34,
+                DROP COLUMN song35,
+                DROP COLUMN song36,
+                DROP COLUMN song37,
+                DROP COLUMN song38,
+                DROP COLUMN song39;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql(r#"
+            ALTER TABLE albums
+                ADD COLUMN song0 INT(11),
+                ADD COLUMN song1 INT(11),
+                ADD COLUMN song2 INT(11),
+                ADD COLUMN song3 INT(11),
+                ADD COLUMN song4 INT(11),
+                ADD COLUMN song5 INT(11),
+                ADD COLUMN song6 INT(11),
+                ADD COLUMN song7 INT(11),
+                ADD COLUMN song8 INT(11),
+                ADD COLUMN song9 INT(11),
+                ADD COLUMN song10 INT(11),
+                ADD COLUMN song11 INT(11),
+                ADD COLUMN song12 INT(11),
+                ADD COLUMN song13 INT(11),
+                ADD COLUMN song14 INT(11),
+                ADD COLUMN song15 INT(11),
+                ADD COLUMN song16 INT(11),
+                ADD COLUMN song17 INT(11),
+                ADD COLUMN song18 INT(11),
+                ADD COLUMN song19 INT(11),
+                ADD COLUMN song20 INT(11),
+                ADD COLUMN song21 INT(11),
+                ADD COLUMN song22 INT(11),
+                ADD COLUMN song23 INT(11),
+                ADD COLUMN song24 INT(11),
+                ADD COLUMN song25 INT(11),
+                ADD COLUMN song26 INT(11),
+                ADD COLUMN song27 INT(11),
+                ADD COLUMN song28 INT(11),
+                ADD COLUMN song29 INT(11),
+                ADD COLUMN song30 INT(11),
+                ADD COLUMN song31 INT(11),
+                ADD COLUMN song32 INT(11),
+                ADD COLUMN song33 INT(11),
+                ADD COLUMN song34 INT(11),
+                ADD COLUMN song35 INT(11),
+                ADD COLUMN song36 INT(11),
+                ADD COLUMN song37 INT(11),
+                ADD COLUMN song38 INT(11),
+                ADD COLUMN song39 INT(11);
+        "#),
+        MigrationStep::Sql(r#"
+            UPDATE albums SET
+                song0=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 0),
+                song1=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 1),
+                song2=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 2),
+                song3=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 3),
+                song4=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 4),
+                song5=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 5),
+                song6=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 6),
+                song7=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 7),
+                song8=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 8),
+                song9=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 9),
+                song10=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 10),
+                song11=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 11),
+                song12=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 12),
+                song13=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 13),
+                song14=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 14),
+                song15=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 15),
+                song16=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 16),
+                song17=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 17),
+                song18=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 18),
+                song19=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 19),
+                song20=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 20),
+                song21=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 21),
+                song22=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 22),
+                song23=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 23),
+                song24=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 24),
+                song25=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 25),
+                song26=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 26),
+                song27=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 27),
+                song28=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 28),
+                song29=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 29),
+                song30=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 30),
+                song31=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 31),
+                song32=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 32),
+                song33=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 33),
+                song34=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 34),
+                song35=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 35),
+                song36=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 36),
+                song37=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 37),
+                song38=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 38),
+                song39=(SELECT song_id FROM album_tracks WHERE album_id=albums.id ORDER BY disc_number, track_number LIMIT 1 OFFSET 39);
+        "#),
+        MigrationStep::Sql(r#"
+            DROP TABLE album_tracks;
+        "#),
+    ],
+};
@@ -0,0 +1,47 @@
+/**
+ * Two-factor authentication. The TOTP secret is stored encrypted (see util::totp) and is
+ * only used once totp_enabled is set, after the user has verified a first code.
+ * Recovery codes are stored as sha256 hashes and are single use.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 3,
+    name: "two_factor_auth",
+    up: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "users",
+            column: "totp_secret",
+            definition: "VARCHAR(255) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "users",
+            column: "totp_enabled",
+            definition: "BOOLEAN DEFAULT 0",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "users",
+            column: "totp_last_used_step",
+            definition: "BIGINT DEFAULT 0",
+        },
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS user_recovery_codes (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                user_id INT(11) NOT NULL,
+                code_hash CHAR(64) NOT NULL,
+                create_time DATETIME DEFAULT NOW(),
+                used_time DATETIME DEFAULT NULL,
+                INDEX user_recovery_codes_user_id (user_id)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql(r#"
+            DROP TABLE IF EXISTS user_recovery_codes;
+        "#),
+        MigrationStep::DropColumnIfExists { table: "users", column: "totp_last_used_step" },
+        MigrationStep::DropColumnIfExists { table: "users", column: "totp_enabled" },
+        MigrationStep::DropColumnIfExists { table: "users", column: "totp_secret" },
+    ],
+};
@@ -0,0 +1,43 @@
+/**
+ * Moves login sessions out of process memory. "sessions" backs the tower session store,
+ * one row per browser session. "user_sessions" keeps the per-user parts of authn::UserSession
+ * that are not on the users table (the IP address and location the user signed in from).
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 4,
+    name: "sessions",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id VARCHAR(128) NOT NULL PRIMARY KEY,
+                user_id INT(11) DEFAULT NULL,
+                record MEDIUMTEXT NOT NULL,
+                expiry_date BIGINT NOT NULL,
+                create_time DATETIME DEFAULT NOW(),
+                last_seen_time DATETIME DEFAULT NOW(),
+                INDEX sessions_user_id (user_id),
+                INDEX sessions_expiry_date (expiry_date)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS user_sessions (
+                user_id INT(11) NOT NULL PRIMARY KEY,
+                ip_address VARCHAR(50) DEFAULT '',
+                latitude DOUBLE DEFAULT 0,
+                longitude DOUBLE DEFAULT 0,
+                update_time DATETIME DEFAULT NOW()
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql(r#"
+            DROP TABLE IF EXISTS user_sessions;
+        "#),
+        MigrationStep::Sql(r#"
+            DROP TABLE IF EXISTS sessions;
+        "#),
+    ],
+};
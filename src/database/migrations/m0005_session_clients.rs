@@ -0,0 +1,32 @@
+/**
+ * Stores where each session was last used from, for the active sessions page.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 5,
+    name: "session_clients",
+    up: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "sessions",
+            column: "ip_address",
+            definition: "VARCHAR(50) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "sessions",
+            column: "user_agent",
+            definition: "VARCHAR(512) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "sessions",
+            column: "location",
+            definition: "VARCHAR(200) DEFAULT ''",
+        },
+    ],
+    down: &[
+        MigrationStep::DropColumnIfExists { table: "sessions", column: "location" },
+        MigrationStep::DropColumnIfExists { table: "sessions", column: "user_agent" },
+        MigrationStep::DropColumnIfExists { table: "sessions", column: "ip_address" },
+    ],
+};
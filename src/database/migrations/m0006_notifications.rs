@@ -0,0 +1,20 @@
+/**
+ * Notifications are always read per user, newest first, and counted by read state.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 6,
+    name: "notifications",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE INDEX notifications_username_is_read ON notifications (username, is_read, post_time)
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql(r#"
+            DROP INDEX notifications_username_is_read ON notifications
+        "#),
+    ],
+};
@@ -0,0 +1,41 @@
+/**
+ * Direct messages between two members. Each pair of members has one conversation,
+ * stored with the lower user id first so that it can be found from either side.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 7,
+    name: "direct_messages",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS conversations (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                user_a_id INT(11) NOT NULL,
+                user_b_id INT(11) NOT NULL,
+                create_time DATETIME DEFAULT NOW(),
+                last_message_time DATETIME DEFAULT NOW(),
+                UNIQUE KEY conversations_users (user_a_id, user_b_id),
+                INDEX conversations_user_b (user_b_id, last_message_time)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS direct_messages (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                conversation_id INT(11) NOT NULL,
+                sender_id INT(11) NOT NULL,
+                recipient_id INT(11) NOT NULL,
+                post_time DATETIME DEFAULT NOW(),
+                message VARCHAR(4000) DEFAULT '',
+                is_read BOOLEAN DEFAULT 0,
+                INDEX direct_messages_conversation (conversation_id, id),
+                INDEX direct_messages_recipient_unread (recipient_id, is_read)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS direct_messages"),
+        MigrationStep::Sql("DROP TABLE IF EXISTS conversations"),
+    ],
+};
@@ -0,0 +1,28 @@
+/**
+ * One row per member that liked a comment, so that a member can only like a comment once.
+ * The total is still kept in "comments.likes" so that listing comments doesn't need to count rows.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 8,
+    name: "comment_likes",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS comment_likes (
+                comment_id INT(11) NOT NULL,
+                user_id INT(11) NOT NULL,
+                like_time DATETIME DEFAULT NOW(),
+                PRIMARY KEY (comment_id, user_id),
+                INDEX comment_likes_user (user_id)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            UPDATE comments SET likes = 0 WHERE likes IS NULL
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS comment_likes"),
+    ],
+};
@@ -0,0 +1,44 @@
+/**
+ * Comment editing and soft deletion. Every edit keeps the previous text in
+ * "comment_edits", and "comments.edit_time" marks the comment as edited.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 9,
+    name: "comment_edits",
+    up: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "comments",
+            column: "edit_time",
+            definition: "DATETIME NULL DEFAULT NULL",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "comments",
+            column: "delete_time",
+            definition: "DATETIME NULL DEFAULT NULL",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "comments",
+            column: "deleted_by",
+            definition: "VARCHAR(30) NULL DEFAULT NULL",
+        },
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS comment_edits (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                comment_id INT(11) NOT NULL,
+                username VARCHAR(30) DEFAULT '',
+                edit_time DATETIME DEFAULT NOW(),
+                previous_comment VARCHAR(5000) DEFAULT '',
+                INDEX comment_edits_comment (comment_id, id)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS comment_edits"),
+        MigrationStep::DropColumnIfExists { table: "comments", column: "deleted_by" },
+        MigrationStep::DropColumnIfExists { table: "comments", column: "delete_time" },
+        MigrationStep::DropColumnIfExists { table: "comments", column: "edit_time" },
+    ],
+};
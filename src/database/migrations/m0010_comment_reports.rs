@@ -0,0 +1,35 @@
+/**
+ * Reports on comments and chat box messages, reviewed by moderators. Each member can
+ * report a comment once. Resolving a comment resolves every open report on it.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 10,
+    name: "comment_reports",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS comment_reports (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                comment_id INT(11) NOT NULL,
+                username VARCHAR(30) DEFAULT '',
+                ip_address VARCHAR(50) DEFAULT '',
+                report_time DATETIME DEFAULT NOW(),
+                reason ENUM('spam','harassment','hate_speech','explicit','misinformation','off_topic','other') DEFAULT 'other',
+                details VARCHAR(500) DEFAULT '',
+                status ENUM('open','dismissed','hidden','deleted') DEFAULT 'open',
+                resolved_by VARCHAR(30) NULL DEFAULT NULL,
+                resolve_time DATETIME NULL DEFAULT NULL,
+                UNIQUE KEY comment_reports_comment_username (comment_id, username),
+                INDEX comment_reports_status (status, report_time)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            UPDATE comments SET visibility = 0 WHERE visibility IS NULL
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS comment_reports"),
+    ],
+};
@@ -0,0 +1,31 @@
+/**
+ * Deletions by contributors only hide content (is_deleted=1) until a moderator reviews them.
+ * Each deletion is queued here with the member who requested it, then approved (the rows
+ * and uploaded files are removed for good) or undone (the content is restored).
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 11,
+    name: "deletion_queue",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS deletion_queue (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                content_type ENUM('band','album','lyrics','tabs','photo','photo_album','video','video_category') NOT NULL,
+                content_id INT(11) NOT NULL,
+                username VARCHAR(30) DEFAULT '',
+                request_time DATETIME DEFAULT NOW(),
+                status ENUM('pending','approved','undone') DEFAULT 'pending',
+                reviewed_by VARCHAR(30) NULL DEFAULT NULL,
+                review_time DATETIME NULL DEFAULT NULL,
+                INDEX deletion_queue_status (status, request_time),
+                INDEX deletion_queue_content (content_type, content_id)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS deletion_queue"),
+    ],
+};
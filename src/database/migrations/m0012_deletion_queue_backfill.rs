@@ -0,0 +1,88 @@
+/**
+ * Content that was soft-deleted before the deletion queue existed is queued with an unknown
+ * deleter, so the trash purge retention period starts counting from this migration.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 12,
+    name: "deletion_queue_backfill",
+    up: &[
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'band', bands.id, '', NOW(), 'pending'
+            FROM bands
+            WHERE bands.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'band' AND deletion_queue.content_id = bands.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'album', albums.id, '', NOW(), 'pending'
+            FROM albums
+            WHERE albums.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'album' AND deletion_queue.content_id = albums.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'lyrics', lyrics.id, '', NOW(), 'pending'
+            FROM lyrics
+            WHERE lyrics.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'lyrics' AND deletion_queue.content_id = lyrics.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'tabs', tabs.id, '', NOW(), 'pending'
+            FROM tabs
+            WHERE tabs.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'tabs' AND deletion_queue.content_id = tabs.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'photo', photos.id, '', NOW(), 'pending'
+            FROM photos
+            WHERE photos.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'photo' AND deletion_queue.content_id = photos.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'photo_album', photo_albums.id, '', NOW(), 'pending'
+            FROM photo_albums
+            WHERE photo_albums.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'photo_album' AND deletion_queue.content_id = photo_albums.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'video', videos.id, '', NOW(), 'pending'
+            FROM videos
+            WHERE videos.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'video' AND deletion_queue.content_id = videos.id
+            )
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO deletion_queue (content_type, content_id, username, request_time, status)
+            SELECT 'video_category', video_categories.id, '', NOW(), 'pending'
+            FROM video_categories
+            WHERE video_categories.is_deleted = 1 AND NOT EXISTS (
+                SELECT 1 FROM deletion_queue
+                WHERE deletion_queue.content_type = 'video_category' AND deletion_queue.content_id = video_categories.id
+            )
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DELETE FROM deletion_queue WHERE username = '' AND status = 'pending'"),
+    ],
+};
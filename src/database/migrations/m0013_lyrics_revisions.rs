@@ -0,0 +1,41 @@
+/**
+ * Every time lyrics are created or updated, the full translation is saved as a revision,
+ * so a bad edit can be rolled back. Existing lyrics start with their current content as
+ * the first revision.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 13,
+    name: "lyrics_revisions",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS lyrics_revisions (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                lyrics_id INT(11) NOT NULL,
+                username VARCHAR(30) DEFAULT '',
+                revision_time DATETIME DEFAULT NOW(),
+                kanji_content MEDIUMTEXT DEFAULT '',
+                romaji_content VARCHAR(4000) DEFAULT '',
+                english_content VARCHAR(4000) DEFAULT '',
+                comment VARCHAR(2000) DEFAULT '',
+                INDEX lyrics_revisions_lyrics_id (lyrics_id, revision_time)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO lyrics_revisions (
+                lyrics_id, username, revision_time, kanji_content, romaji_content, english_content, comment
+            )
+            SELECT lyrics.id, lyrics.username, lyrics.post_time, lyrics.kanji_content,
+                lyrics.romaji_content, lyrics.english_content, lyrics.comment
+            FROM lyrics
+            WHERE NOT EXISTS (
+                SELECT 1 FROM lyrics_revisions WHERE lyrics_revisions.lyrics_id = lyrics.id
+            )
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS lyrics_revisions"),
+    ],
+};
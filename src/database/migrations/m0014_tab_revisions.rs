@@ -0,0 +1,35 @@
+/**
+ * Every time tabs are created or updated, the tab content is saved as a revision, so
+ * contributors can compare edits and restore an earlier version. Existing tabs start
+ * with their current content as the first revision.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 14,
+    name: "tab_revisions",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS tab_revisions (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                tab_id INT(11) NOT NULL,
+                username VARCHAR(30) DEFAULT '',
+                revision_time DATETIME DEFAULT NOW(),
+                tab_content MEDIUMTEXT DEFAULT '',
+                INDEX tab_revisions_tab_id (tab_id, revision_time)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT INTO tab_revisions (tab_id, username, revision_time, tab_content)
+            SELECT tabs.id, tabs.username, tabs.post_time, tabs.tab_content
+            FROM tabs
+            WHERE NOT EXISTS (
+                SELECT 1 FROM tab_revisions WHERE tab_revisions.tab_id = tabs.id
+            )
+        "#),
+    ],
+    down: &[
+        MigrationStep::Sql("DROP TABLE IF EXISTS tab_revisions"),
+    ],
+};
@@ -0,0 +1,77 @@
+/**
+ * Moves translations out of the english_content columns on lyrics and lyrics_revisions
+ * into tables keyed by language code, so lyrics can be translated into languages other
+ * than English.
+ *
+ * Existing English translations are copied with the language code "en", empty ones are skipped.
+ * Reverting copies the English translations back and drops every other language.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 15,
+    name: "lyrics_translations",
+    up: &[
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS lyrics_translations (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                lyrics_id INT(11) NOT NULL,
+                language_code VARCHAR(10) NOT NULL,
+                post_time DATETIME DEFAULT NOW(),
+                content VARCHAR(4000) DEFAULT '',
+                UNIQUE INDEX lyrics_translations_language (lyrics_id, language_code),
+                INDEX lyrics_translations_recent (language_code, post_time)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS lyrics_revision_translations (
+                id INT(11) AUTO_INCREMENT PRIMARY KEY,
+                revision_id INT(11) NOT NULL,
+                language_code VARCHAR(10) NOT NULL,
+                content VARCHAR(4000) DEFAULT '',
+                UNIQUE INDEX lyrics_revision_translations_language (revision_id, language_code)
+            ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT IGNORE INTO lyrics_translations (lyrics_id, language_code, post_time, content)
+            SELECT id, 'en', post_time, english_content
+            FROM lyrics
+            WHERE TRIM(english_content) <> ''
+        "#),
+        MigrationStep::Sql(r#"
+            INSERT IGNORE INTO lyrics_revision_translations (revision_id, language_code, content)
+            SELECT id, 'en', english_content
+            FROM lyrics_revisions
+            WHERE TRIM(english_content) <> ''
+        "#),
+        MigrationStep::DropColumnIfExists { table: "lyrics", column: "english_content" },
+        MigrationStep::DropColumnIfExists { table: "lyrics_revisions", column: "english_content" },
+    ],
+    down: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics",
+            column: "english_content",
+            definition: "VARCHAR(4000) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics_revisions",
+            column: "english_content",
+            definition: "VARCHAR(4000) DEFAULT ''",
+        },
+        MigrationStep::Sql(r#"
+            UPDATE lyrics
+            JOIN lyrics_translations ON lyrics_translations.lyrics_id = lyrics.id
+            SET lyrics.english_content = lyrics_translations.content
+            WHERE lyrics_translations.language_code = 'en'
+        "#),
+        MigrationStep::Sql(r#"
+            UPDATE lyrics_revisions
+            JOIN lyrics_revision_translations ON lyrics_revision_translations.revision_id = lyrics_revisions.id
+            SET lyrics_revisions.english_content = lyrics_revision_translations.content
+            WHERE lyrics_revision_translations.language_code = 'en'
+        "#),
+        MigrationStep::Sql("DROP TABLE IF EXISTS lyrics_revision_translations"),
+        MigrationStep::Sql("DROP TABLE IF EXISTS lyrics_translations"),
+    ],
+};
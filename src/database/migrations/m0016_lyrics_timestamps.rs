@@ -0,0 +1,39 @@
+/**
+ * Lyrics can be synchronized with a song, with a timestamp for each kanji line and
+ * a video to follow along with. Both are optional and kept in revisions as well.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 16,
+    name: "lyrics_timestamps",
+    up: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics",
+            column: "line_timestamps",
+            definition: "VARCHAR(8000) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics",
+            column: "video_url",
+            definition: "VARCHAR(1000) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics_revisions",
+            column: "line_timestamps",
+            definition: "VARCHAR(8000) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "lyrics_revisions",
+            column: "video_url",
+            definition: "VARCHAR(1000) DEFAULT ''",
+        },
+    ],
+    down: &[
+        MigrationStep::DropColumnIfExists { table: "lyrics_revisions", column: "video_url" },
+        MigrationStep::DropColumnIfExists { table: "lyrics_revisions", column: "line_timestamps" },
+        MigrationStep::DropColumnIfExists { table: "lyrics", column: "video_url" },
+        MigrationStep::DropColumnIfExists { table: "lyrics", column: "line_timestamps" },
+    ],
+};
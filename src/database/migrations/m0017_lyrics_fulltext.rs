@@ -0,0 +1,34 @@
+/**
+ * Full-text indexes over the kanji, rōmaji and translated lyrics, so songs can be found
+ * from a line of their lyrics. The ngram parser splits text into runs of characters
+ * (two by default, see ngram_token_size), which works for Japanese written without spaces.
+ *
+ * Stopwords are turned off while the indexes are built. With the ngram parser every token
+ * containing a stopword like "a" or "i" would be left out, which drops most of the rōmaji.
+ * All steps run on the same connection, so the session setting covers the index steps.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 17,
+    name: "lyrics_fulltext",
+    up: &[
+        MigrationStep::Sql("SET SESSION innodb_ft_enable_stopword = OFF"),
+        MigrationStep::AddIndexIfMissing {
+            table: "lyrics",
+            index: "lyrics_content_fulltext",
+            definition: "FULLTEXT INDEX lyrics_content_fulltext (kanji_content, romaji_content) WITH PARSER ngram",
+        },
+        MigrationStep::AddIndexIfMissing {
+            table: "lyrics_translations",
+            index: "lyrics_translations_content_fulltext",
+            definition: "FULLTEXT INDEX lyrics_translations_content_fulltext (content) WITH PARSER ngram",
+        },
+        MigrationStep::Sql("SET SESSION innodb_ft_enable_stopword = ON"),
+    ],
+    down: &[
+        MigrationStep::DropIndexIfExists { table: "lyrics_translations", index: "lyrics_translations_content_fulltext" },
+        MigrationStep::DropIndexIfExists { table: "lyrics", index: "lyrics_content_fulltext" },
+    ],
+};
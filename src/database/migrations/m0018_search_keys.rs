@@ -0,0 +1,36 @@
+/**
+ * Search keys for the names of bands, albums and songs, so they can be found with
+ * kana, rōmaji or English spellings of their names. See util/search_key.rs.
+ *
+ * The keys are made in Rust, so they're left empty here and filled in when the
+ * application starts. A later migration can empty them again to remake them.
+ */
+
+use super::{ Migration, MigrationStep };
+
+pub static MIGRATION: Migration = Migration {
+    version: 18,
+    name: "search_keys",
+    up: &[
+        MigrationStep::AddColumnIfMissing {
+            table: "bands",
+            column: "search_key",
+            definition: "VARCHAR(600) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "albums",
+            column: "search_key",
+            definition: "VARCHAR(600) DEFAULT ''",
+        },
+        MigrationStep::AddColumnIfMissing {
+            table: "songs",
+            column: "search_key",
+            definition: "VARCHAR(600) DEFAULT ''",
+        },
+    ],
+    down: &[
+        MigrationStep::DropColumnIfExists { table: "songs", column: "search_key" },
+        MigrationStep::DropColumnIfExists { table: "albums", column: "search_key" },
+        MigrationStep::DropColumnIfExists { table: "bands", column: "search_key" },
+    ],
+};
@@ -0,0 +1,468 @@
+/**
+ * Versioned schema migrations.
+ *
+ * Every change to the database schema is written as a numbered migration in this
+ * folder and added to the MIGRATIONS list below. Applied migrations are recorded in
+ * the "schema_migrations" table along with a checksum of their steps, so that an
+ * environment can never silently drift from what the queries in database/*.rs expect.
+ *
+ * Never edit a migration that has already been deployed, add a new one instead.
+ * The checksum check at startup will refuse to run if an applied migration changed.
+ */
+
+use std::error::Error;
+use std::io;
+use chrono::NaiveDateTime;
+use sha2::{ Digest, Sha256 };
+use sqlx::{
+    FromRow,
+    MySql,
+    MySqlConnection,
+    Row,
+};
+
+use super::get_pool;
+
+mod m0001_baseline_schema;
+mod m0002_album_tracks;
+mod m0003_two_factor_auth;
+mod m0004_sessions;
+mod m0005_session_clients;
+mod m0006_notifications;
+mod m0007_direct_messages;
+mod m0008_comment_likes;
+mod m0009_comment_edits;
+mod m0010_comment_reports;
+mod m0011_deletion_queue;
+mod m0012_deletion_queue_backfill;
+mod m0013_lyrics_revisions;
+mod m0014_tab_revisions;
+mod m0015_lyrics_translations;
+mod m0016_lyrics_timestamps;
+mod m0017_lyrics_fulltext;
+mod m0018_search_keys;
+
+/**
+ * A single operation inside of a migration.
+ * MySQL does not support "IF NOT EXISTS" on columns or indexes, so the conditional
+ * variants check information_schema first. This keeps migrations safe to run against
+ * environments where a column was already added by hand.
+ */
+pub enum MigrationStep {
+    Sql(&'static str),
+    AddColumnIfMissing {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+    DropColumnIfExists {
+        table: &'static str,
+        column: &'static str,
+    },
+    AddIndexIfMissing {
+        table: &'static str,
+        index: &'static str,
+        definition: &'static str,
+    },
+    DropIndexIfExists {
+        table: &'static str,
+        index: &'static str,
+    },
+}
+impl MigrationStep {
+    fn checksum_source(&self) -> String {
+        match self {
+            MigrationStep::Sql(sql) => format!("sql:{}", sql.trim()),
+            MigrationStep::AddColumnIfMissing { table, column, definition } => {
+                format!("add_column:{}.{}:{}", table, column, definition.trim())
+            },
+            MigrationStep::DropColumnIfExists { table, column } => {
+                format!("drop_column:{}.{}", table, column)
+            },
+            MigrationStep::AddIndexIfMissing { table, index, definition } => {
+                format!("add_index:{}.{}:{}", table, index, definition.trim())
+            },
+            MigrationStep::DropIndexIfExists { table, index } => {
+                format!("drop_index:{}.{}", table, index)
+            },
+        }
+    }
+}
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static [MigrationStep],
+    pub down: &'static [MigrationStep],
+}
+impl Migration {
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for step in self.up {
+            hasher.update(step.checksum_source().as_bytes());
+            hasher.update(b"\n");
+        }
+        hex::encode(hasher.finalize())
+    }
+    pub fn is_reversible(&self) -> bool {
+        self.down.len() > 0
+    }
+}
+
+/**
+ * All migrations, in the order they must be applied. Versions must be increasing.
+ */
+pub static MIGRATIONS: &[&Migration] = &[
+    &m0001_baseline_schema::MIGRATION,
+    &m0002_album_tracks::MIGRATION,
+    &m0003_two_factor_auth::MIGRATION,
+    &m0004_sessions::MIGRATION,
+    &m0005_session_clients::MIGRATION,
+    &m0006_notifications::MIGRATION,
+    &m0007_direct_messages::MIGRATION,
+    &m0008_comment_likes::MIGRATION,
+    &m0009_comment_edits::MIGRATION,
+    &m0010_comment_reports::MIGRATION,
+    &m0011_deletion_queue::MIGRATION,
+    &m0012_deletion_queue_backfill::MIGRATION,
+    &m0013_lyrics_revisions::MIGRATION,
+    &m0014_tab_revisions::MIGRATION,
+    &m0015_lyrics_translations::MIGRATION,
+    &m0016_lyrics_timestamps::MIGRATION,
+    &m0017_lyrics_fulltext::MIGRATION,
+    &m0018_search_keys::MIGRATION,
+];
+
+#[allow(unused)]
+#[derive(Debug, Clone, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: NaiveDateTime,
+}
+
+#[allow(unused)]
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: Option<NaiveDateTime>,
+    pub checksum_matches: bool,
+}
+
+const MIGRATION_LOCK_NAME: &str = "supercar_band_schema_migrations";
+const MIGRATION_LOCK_TIMEOUT_SECONDS: i32 = 60;
+
+async fn create_schema_migrations_table(connection: &mut MySqlConnection) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name VARCHAR(200) DEFAULT '',
+            checksum CHAR(64) DEFAULT '',
+            applied_at DATETIME DEFAULT NOW()
+        ) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+    "#)
+        .execute(&mut *connection)
+        .await?;
+    Ok(())
+}
+
+async fn get_applied_migrations(connection: &mut MySqlConnection) -> Result<Vec<AppliedMigration>, Box<dyn Error + Send + Sync>> {
+    Ok(
+        sqlx::query_as::<MySql, AppliedMigration>(r#"
+            SELECT version, name, checksum, applied_at FROM schema_migrations
+            ORDER BY version ASC;
+        "#)
+            .fetch_all(&mut *connection)
+            .await?
+    )
+}
+
+/**
+ * MySQL named locks are held per connection, which is why the whole migration run
+ * happens on a single connection taken out of the pool. This stops two instances
+ * behind the load balancer from migrating at the same time.
+ */
+async fn acquire_migration_lock(connection: &mut MySqlConnection) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let acquired: Option<i64> = sqlx::query("SELECT GET_LOCK(?, ?)")
+        .bind(MIGRATION_LOCK_NAME)
+        .bind(MIGRATION_LOCK_TIMEOUT_SECONDS)
+        .fetch_one(&mut *connection)
+        .await?
+        .get::<Option<i64>, usize>(0);
+    if acquired != Some(1) {
+        return Err(Box::new(
+            io::Error::new(io::ErrorKind::Other, "Timed out waiting for the schema migration lock.")
+        ));
+    }
+    Ok(())
+}
+
+async fn release_migration_lock(connection: &mut MySqlConnection) {
+    let _ = sqlx::query("SELECT RELEASE_LOCK(?)")
+        .bind(MIGRATION_LOCK_NAME)
+        .fetch_optional(&mut *connection)
+        .await;
+}
+
+async fn column_exists(connection: &mut MySqlConnection, table: &str, column: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let count = sqlx::query(r#"
+        SELECT COUNT(*) FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?
+    "#)
+        .bind(table)
+        .bind(column)
+        .fetch_one(&mut *connection)
+        .await?
+        .get::<i64, usize>(0);
+    Ok(count > 0)
+}
+
+async fn index_exists(connection: &mut MySqlConnection, table: &str, index: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let count = sqlx::query(r#"
+        SELECT COUNT(*) FROM information_schema.STATISTICS
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND INDEX_NAME = ?
+    "#)
+        .bind(table)
+        .bind(index)
+        .fetch_one(&mut *connection)
+        .await?
+        .get::<i64, usize>(0);
+    Ok(count > 0)
+}
+
+async fn run_step(connection: &mut MySqlConnection, step: &MigrationStep) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match step {
+        MigrationStep::Sql(sql) => {
+            sqlx::query(sql)
+                .execute(&mut *connection)
+                .await?;
+        },
+        MigrationStep::AddColumnIfMissing { table, column, definition } => {
+            if !column_exists(connection, table, column).await? {
+                sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                    .execute(&mut *connection)
+                    .await?;
+            }
+        },
+        MigrationStep::DropColumnIfExists { table, column } => {
+            if column_exists(connection, table, column).await? {
+                sqlx::query(&format!("ALTER TABLE {} DROP COLUMN {}", table, column))
+                    .execute(&mut *connection)
+                    .await?;
+            }
+        },
+        MigrationStep::AddIndexIfMissing { table, index, definition } => {
+            if !index_exists(connection, table, index).await? {
+                sqlx::query(&format!("ALTER TABLE {} ADD {}", table, definition))
+                    .execute(&mut *connection)
+                    .await?;
+            }
+        },
+        MigrationStep::DropIndexIfExists { table, index } => {
+            if index_exists(connection, table, index).await? {
+                sqlx::query(&format!("ALTER TABLE {} DROP INDEX {}", table, index))
+                    .execute(&mut *connection)
+                    .await?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/**
+ * Fails if a migration that was already applied has been modified since, or if
+ * the list of migrations is not sorted by version.
+ */
+fn verify_applied_migrations(applied: &Vec<AppliedMigration>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut previous_version = 0;
+    for migration in MIGRATIONS {
+        if migration.version <= previous_version {
+            return Err(Box::new(
+                io::Error::new(io::ErrorKind::Other, format!("Migration {} is out of order.", migration.version))
+            ));
+        }
+        previous_version = migration.version;
+    }
+    for applied_migration in applied {
+        match MIGRATIONS.iter().find(|migration| migration.version == applied_migration.version) {
+            Some(migration) => {
+                if migration.checksum() != applied_migration.checksum {
+                    return Err(Box::new(
+                        io::Error::new(io::ErrorKind::Other, format!(
+                            "Checksum mismatch for applied migration {} ({}). Applied migrations must not be edited.",
+                            migration.version, migration.name,
+                        ))
+                    ));
+                }
+            },
+            None => {
+                tracing::warn!(
+                    "Database has migration {} ({}) applied, which is unknown to this build.",
+                    applied_migration.version, applied_migration.name,
+                );
+            },
+        }
+    }
+    Ok(())
+}
+
+async fn apply_pending_migrations(connection: &mut MySqlConnection) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+    create_schema_migrations_table(connection).await?;
+    let applied = get_applied_migrations(connection).await?;
+    verify_applied_migrations(&applied)?;
+
+    let mut applied_versions: Vec<i64> = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.iter().any(|applied_migration| applied_migration.version == migration.version) {
+            continue;
+        }
+        tracing::info!("Applying migration {} ({}).", migration.version, migration.name);
+        for step in migration.up {
+            if let Err(error) = run_step(connection, step).await {
+                tracing::error!("Migration {} ({}) failed. {:?}", migration.version, migration.name, error);
+                return Err(error);
+            }
+        }
+        sqlx::query(r#"
+            INSERT INTO schema_migrations (version, name, checksum, applied_at)
+            VALUES (?, ?, ?, NOW())
+        "#)
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .execute(&mut *connection)
+            .await?;
+        applied_versions.push(migration.version);
+    }
+    Ok(applied_versions)
+}
+
+async fn revert_migrations(connection: &mut MySqlConnection, count: usize) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+    create_schema_migrations_table(connection).await?;
+    let applied = get_applied_migrations(connection).await?;
+    verify_applied_migrations(&applied)?;
+
+    let mut reverted_versions: Vec<i64> = Vec::new();
+    for applied_migration in applied.iter().rev().take(count) {
+        let migration = match MIGRATIONS.iter().find(|migration| migration.version == applied_migration.version) {
+            Some(migration) => migration,
+            None => {
+                return Err(Box::new(
+                    io::Error::new(io::ErrorKind::Other, format!("Migration {} is unknown to this build.", applied_migration.version))
+                ));
+            },
+        };
+        if !migration.is_reversible() {
+            return Err(Box::new(
+                io::Error::new(io::ErrorKind::Other, format!("Migration {} ({}) cannot be reverted.", migration.version, migration.name))
+            ));
+        }
+        tracing::info!("Reverting migration {} ({}).", migration.version, migration.name);
+        for step in migration.down {
+            run_step(connection, step).await?;
+        }
+        sqlx::query("DELETE FROM schema_migrations WHERE version=? LIMIT 1")
+            .bind(migration.version)
+            .execute(&mut *connection)
+            .await?;
+        reverted_versions.push(migration.version);
+    }
+    Ok(reverted_versions)
+}
+
+/**
+ * Applies every migration that has not been applied yet, in order.
+ * Returns the versions that were applied.
+ */
+pub async fn run_pending_migrations() -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+    let mut connection = get_pool().acquire().await?;
+    acquire_migration_lock(&mut connection).await?;
+    let result = apply_pending_migrations(&mut connection).await;
+    release_migration_lock(&mut connection).await;
+    result
+}
+
+/**
+ * Reverts the most recently applied migrations using their "down" steps.
+ * Returns the versions that were reverted.
+ */
+pub async fn rollback_migrations(count: usize) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+    let mut connection = get_pool().acquire().await?;
+    acquire_migration_lock(&mut connection).await?;
+    let result = revert_migrations(&mut connection, count).await;
+    release_migration_lock(&mut connection).await;
+    result
+}
+
+pub async fn get_migration_status() -> Result<Vec<MigrationStatus>, Box<dyn Error + Send + Sync>> {
+    let mut connection = get_pool().acquire().await?;
+    create_schema_migrations_table(&mut connection).await?;
+    let applied = get_applied_migrations(&mut connection).await?;
+
+    Ok(
+        MIGRATIONS
+            .iter()
+            .map(|migration| {
+                let applied_migration = applied.iter().find(|applied_migration| applied_migration.version == migration.version);
+                MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    applied_at: applied_migration.map(|applied_migration| applied_migration.applied_at),
+                    checksum_matches: applied_migration
+                        .map(|applied_migration| applied_migration.checksum == migration.checksum())
+                        .unwrap_or(true),
+                }
+            })
+            .collect()
+    )
+}
+
+/**
+ * Entry point for "cargo run -- migrate <up|down [count]|status>".
+ */
+pub async fn run_command(args: &[String]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let command = args.get(0).map(|arg| arg.as_str()).unwrap_or("up");
+    match command {
+        "up" => {
+            let applied = run_pending_migrations().await?;
+            if applied.is_empty() {
+                println!("Database schema is up to date.");
+            }
+            for version in applied {
+                println!("Applied migration {}.", version);
+            }
+        },
+        "down" => {
+            let count = args.get(1)
+                .and_then(|arg| arg.parse::<usize>().ok())
+                .unwrap_or(1);
+            for version in rollback_migrations(count).await? {
+                println!("Reverted migration {}.", version);
+            }
+        },
+        "status" => {
+            for status in get_migration_status().await? {
+                let state = match (&status.applied_at, status.checksum_matches) {
+                    (Some(_), false) => "CHANGED",
+                    (Some(_), true) => "applied",
+                    (None, _) => "pending",
+                };
+                println!(
+                    "{:>6}  {:<8}  {:<40}  {}",
+                    status.version,
+                    state,
+                    status.name,
+                    status.applied_at.map(|applied_at| applied_at.to_string()).unwrap_or_default(),
+                );
+            }
+        },
+        _ => {
+            return Err(Box::new(
+                io::Error::new(io::ErrorKind::Other, format!("Unknown migrate command \"{}\". Expected up, down or status.", command))
+            ));
+        },
+    }
+    Ok(())
+}
@@ -23,6 +23,7 @@ struct SecretsConfigDatabase {
 pub enum QueryOrder {
     Asc,
     Desc,
+    Top,
 }
 
 pub static POOL: OnceCell<MySqlPool> = OnceCell::const_new();
@@ -60,11 +61,15 @@ pub mod albums;
 pub use albums::Album;
 pub use albums::AlbumSummary;
 pub use albums::AlbumSearchResult;
+pub use albums::AlbumTrack;
+pub use albums::AlbumTrackListing;
 pub use albums::AlbumType;
 pub use albums::get_album_by_slug_and_band_id;
 pub use albums::get_album_by_song_slug;
 pub use albums::get_album_summaries_by_band_id;
 pub use albums::get_albums_by_band_id;
+pub use albums::get_album_tracks;
+pub use albums::get_album_track_listing;
 pub use albums::find_albums_by_name;
 pub use albums::get_album_3d_assets;
 pub use albums::get_lyrics_booklet_images;
@@ -77,13 +82,26 @@ pub use bands::Band;
 pub use bands::get_all_bands;
 pub use bands::get_band_by_id;
 pub use bands::get_band_by_slug;
+pub use bands::band_by_id_is_empty;
 pub use bands::find_bands_by_name;
 pub use bands::create_band;
 pub use bands::update_band;
 pub use bands::mark_band_for_deletion;
 
+pub mod comment_reports;
+pub use comment_reports::CommentReport;
+pub use comment_reports::CommentReportReason;
+pub use comment_reports::CommentReportStatus;
+pub use comment_reports::ReportedComment;
+pub use comment_reports::get_open_comment_report_count;
+pub use comment_reports::get_reported_comments_in_range;
+pub use comment_reports::get_hidden_comments;
+pub use comment_reports::get_open_comment_reports_by_comment_id;
+pub use comment_reports::create_comment_report;
+pub use comment_reports::resolve_comment_reports;
 pub mod comments;
 pub use comments::Comment;
+pub use comments::CommentEdit;
 pub use comments::CommentWithReplies;
 pub use comments::CommentSectionName;
 pub use comments::get_comments_count;
@@ -91,19 +109,75 @@ pub use comments::get_comment_by_id;
 pub use comments::get_comments_in_range;
 pub use comments::get_comments_in_range_with_replies;
 pub use comments::create_comment;
+pub use comments::like_comment;
+pub use comments::unlike_comment;
+pub use comments::has_user_liked_comment;
+pub use comments::get_comment_edits;
+pub use comments::update_comment;
+pub use comments::delete_comment;
+pub use comments::set_comment_visibility;
+pub use comments::COMMENT_VISIBILITY_HIDDEN;
+pub use comments::COMMENT_VISIBILITY_VISIBLE;
 
-pub mod initialize;
+pub mod deletion_queue;
+pub use deletion_queue::DeletionContentType;
+pub use deletion_queue::QueuedDeletion;
+pub use deletion_queue::QueuedDeletionStatus;
+pub use deletion_queue::TrashedContent;
+pub use deletion_queue::get_pending_deletions_count;
+pub use deletion_queue::get_pending_deletions_in_range;
+pub use deletion_queue::get_queued_deletion_by_id;
+pub use deletion_queue::queue_deletion;
+pub use deletion_queue::approve_queued_deletion;
+pub use deletion_queue::undo_queued_deletion;
+pub use deletion_queue::purge_deleted_content;
+pub use deletion_queue::restore_deleted_content;
+pub use deletion_queue::get_expired_deleted_content;
+pub use deletion_queue::get_trashed_content_count;
+pub use deletion_queue::get_trashed_content_in_range;
+pub mod direct_messages;
+pub use direct_messages::DirectMessage;
+pub use direct_messages::ConversationSummary;
+pub use direct_messages::get_conversations_count;
+pub use direct_messages::get_conversations_in_range;
+pub use direct_messages::get_conversation_id;
+pub use direct_messages::get_direct_messages_count;
+pub use direct_messages::get_direct_messages_in_range;
+pub use direct_messages::get_unread_direct_message_count;
+pub use direct_messages::mark_conversation_read;
+pub use direct_messages::create_direct_message;
 
 pub mod lyrics;
 pub use lyrics::Lyrics;
+pub use lyrics::LyricsRevision;
+pub use lyrics::LyricsContentSearchResult;
+pub use lyrics::LyricsLanguage;
+pub use lyrics::LyricsTranslation;
 pub use lyrics::RecentLyricTranslation;
 pub use lyrics::get_lyrics_by_song_id;
 pub use lyrics::get_lyrics_by_username_and_song_id;
 pub use lyrics::get_recent_lyric_translations_by_band_id;
+pub use lyrics::get_lyrics_content_search_count;
+pub use lyrics::find_lyrics_by_content;
 pub use lyrics::create_lyrics;
 pub use lyrics::update_lyrics;
+pub use lyrics::get_lyrics_revisions;
 pub use lyrics::mark_lyrics_for_deletion;
 
+pub mod migrations;
+
+pub mod notifications;
+pub use notifications::Notification;
+pub use notifications::NotificationType;
+pub use notifications::get_unread_notification_count;
+pub use notifications::get_notifications_count;
+pub use notifications::get_notifications_in_range;
+pub use notifications::get_notification_by_id;
+pub use notifications::create_notification;
+pub use notifications::mark_notification_read;
+pub use notifications::mark_all_notifications_read;
+pub use notifications::notify_comment_created;
+
 pub mod photos;
 pub use photos::Photo;
 pub use photos::PhotoAlbum;
@@ -121,6 +195,32 @@ pub use photos::create_photo;
 pub use photos::update_photo;
 pub use photos::mark_photo_for_deletion;
 
+pub mod search;
+pub use search::SEARCH_RESULTS_PER_PAGE;
+pub use search::SearchResult;
+pub use search::SearchResultGroup;
+pub use search::SearchResultType;
+pub use search::fill_missing_search_keys;
+pub use search::get_search_key_entries;
+pub use search::find_search_result_groups;
+
+pub mod sessions;
+pub use sessions::SessionClient;
+pub use sessions::SessionRecord;
+pub use sessions::UserSessionRecord;
+pub use sessions::get_session_record;
+pub use sessions::session_record_exists;
+pub use sessions::save_session_record;
+pub use sessions::delete_session_record;
+pub use sessions::get_session_records_by_user_id;
+pub use sessions::delete_session_record_by_user_id_and_hash;
+pub use sessions::delete_session_records_by_user_id;
+pub use sessions::delete_expired_session_records;
+pub use sessions::get_user_session_record;
+pub use sessions::save_user_session_record;
+pub use sessions::update_user_session_record_ip;
+pub use sessions::delete_user_session_record;
+
 pub mod site_events;
 pub use site_events::SiteEvent;
 pub use site_events::get_recent_site_events;
@@ -150,11 +250,13 @@ pub mod tabs;
 pub use tabs::SongTab;
 pub use tabs::SongTabType;
 pub use tabs::JoinedSongTab;
+pub use tabs::TabRevision;
 pub use tabs::get_song_tab_by_id;
 pub use tabs::get_song_tab_by_username_type_and_song_id;
 pub use tabs::get_song_tabs_by_song_id;
 pub use tabs::create_song_tab;
 pub use tabs::update_song_tab;
+pub use tabs::get_tab_revisions;
 pub use tabs::mark_tab_for_deletion;
 
 pub mod users;
@@ -165,6 +267,7 @@ pub use users::UserPermission;
 pub use users::UserPermissionSet;
 pub use users::UserPreference;
 pub use users::UserPreferenceSet;
+pub use users::get_user_by_id;
 pub use users::get_user_by_username;
 pub use users::get_users_count;
 pub use users::get_users_in_range;
@@ -173,6 +276,17 @@ pub use users::update_user_at_login;
 pub use users::update_user_profile_info;
 pub use users::update_user_password;
 pub use users::update_user_profile_picture;
+pub use users::enable_user_totp;
+pub use users::disable_user_totp;
+pub use users::replace_user_recovery_codes;
+pub use users::get_unused_recovery_code_count;
+pub use users::consume_user_totp_step;
+pub use users::consume_user_recovery_code;
+pub use users::get_user_blocklist;
+pub use users::is_either_user_blocked;
+pub use users::get_blocked_users;
+pub use users::block_user;
+pub use users::unblock_user;
 
 pub mod videos;
 pub use videos::Video;
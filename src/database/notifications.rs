@@ -0,0 +1,208 @@
+use std::error::Error;
+use chrono::NaiveDateTime;
+use sqlx::{
+    FromRow,
+    MySql,
+    Row,
+    Type,
+};
+use strum_macros::{ Display, EnumString };
+
+use super::get_pool;
+use super::comments::{ Comment, CommentSectionName, get_comment_by_id };
+use super::users::{ UserPreference, get_user_by_username };
+use crate::util::user::is_guest_user;
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq, Type)]
+#[sqlx(type_name = "notification_type")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum NotificationType {
+    #[default]
+    DirectMessage,
+    CommentReply,
+    LyricPostComment,
+    TabsComment,
+    PhotoComment,
+    VideoComment,
+    ProfileComment,
+}
+
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct Notification {
+    pub id: i32,
+    pub username: String,
+    pub post_time: NaiveDateTime,
+    pub notification_type: NotificationType,
+    pub notifier_username: String,
+    pub link: String,
+    pub is_read: bool,
+}
+
+pub async fn get_unread_notification_count(username: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM notifications WHERE username=? AND is_read=0")
+            .bind(username)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn get_notifications_count(username: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM notifications WHERE username=?")
+            .bind(username)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn get_notifications_in_range(
+    username: &str,
+    start: u32,
+    length: u32,
+) -> Result<Vec<Notification>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, Notification>(r#"
+            SELECT * FROM notifications
+            WHERE username=?
+            ORDER BY post_time DESC, id DESC
+            LIMIT ? OFFSET ?
+        "#)
+            .bind(username)
+            .bind(length)
+            .bind(start)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn get_notification_by_id(username: &str, id: i32) -> Result<Notification, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, Notification>("SELECT * FROM notifications WHERE username=? AND id=? LIMIT 1")
+            .bind(username)
+            .bind(id)
+            .fetch_one(get_pool())
+            .await?
+    )
+}
+
+pub async fn create_notification(
+    notification: &Notification,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        INSERT INTO notifications (
+            username, post_time, notification_type, notifier_username, link, is_read
+        )
+        VALUES (?, NOW(), ?, ?, ?, 0)
+    "#)
+        .bind(&notification.username)
+        .bind(&notification.notification_type)
+        .bind(&notification.notifier_username)
+        .bind(&notification.link)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn mark_notification_read(username: &str, id: i32) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("UPDATE notifications SET is_read=1 WHERE username=? AND id=?")
+        .bind(username)
+        .bind(id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn mark_all_notifications_read(username: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("UPDATE notifications SET is_read=1 WHERE username=? AND is_read=0")
+        .bind(username)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+/**
+ * The member who contributed the thing a comment section belongs to.
+ */
+async fn get_comment_section_owner(
+    section: &CommentSectionName,
+    section_tag_id: i32,
+) -> Result<Option<(NotificationType, String)>, Box<dyn Error>> {
+    let (notification_type, query) = match section {
+        CommentSectionName::Lyrics => (
+            NotificationType::LyricPostComment,
+            "SELECT username FROM lyrics WHERE song=? AND is_deleted=0 ORDER BY post_time ASC LIMIT 1",
+        ),
+        CommentSectionName::Tabs => (
+            NotificationType::TabsComment,
+            "SELECT username FROM tabs WHERE id=? AND is_deleted=0 LIMIT 1",
+        ),
+        CommentSectionName::Photos => (
+            NotificationType::PhotoComment,
+            "SELECT username FROM photos WHERE id=? AND is_deleted=0 LIMIT 1",
+        ),
+        CommentSectionName::Videos => (
+            NotificationType::VideoComment,
+            "SELECT username FROM videos WHERE id=? AND is_deleted=0 LIMIT 1",
+        ),
+        CommentSectionName::Members => (
+            NotificationType::ProfileComment,
+            "SELECT username FROM users WHERE id=? AND is_deleted=0 LIMIT 1",
+        ),
+        _ => return Ok(None),
+    };
+    let row = sqlx::query(query)
+        .bind(section_tag_id)
+        .fetch_optional(get_pool())
+        .await?;
+    Ok(row.map(|row| (notification_type, row.get::<String, &str>("username"))))
+}
+
+/**
+ * Notifies the author of the comment being replied to (if they want comment reply
+ * notifications), and the member who contributed the lyrics/tabs/photo/video or owns
+ * the profile being commented on (if they want profile comment notifications).
+ * Nobody is notified of their own comments, and guests are never notified.
+ */
+pub async fn notify_comment_created(comment: &Comment, link: &str) -> Result<(), Box<dyn Error>> {
+    let mut notified_usernames: Vec<String> = vec![comment.username.to_lowercase()];
+
+    let mut recipients: Vec<(NotificationType, String, UserPreference)> = Vec::new();
+    if comment.reply_id > -1 {
+        let reply_to_comment = get_comment_by_id(comment.reply_id).await?;
+        recipients.push((NotificationType::CommentReply, reply_to_comment.username, UserPreference::NotifyCommentReplies));
+    }
+    if let Some((notification_type, owner_username)) = get_comment_section_owner(
+        &comment.section,
+        comment.section_tag_id.unwrap_or(-1),
+    ).await? {
+        recipients.push((notification_type, owner_username, UserPreference::NotifyProfileComments));
+    }
+
+    for (notification_type, username, preference) in recipients {
+        if is_guest_user(&username) || notified_usernames.contains(&username.to_lowercase()) {
+            continue;
+        }
+        notified_usernames.push(username.to_lowercase());
+
+        let user = get_user_by_username(&username).await?;
+        if !user.preferences.contains(&preference) {
+            continue;
+        }
+        create_notification(&Notification {
+            username: user.username,
+            notification_type,
+            notifier_username: comment.username.clone(),
+            link: link.to_string(),
+            ..Notification::default()
+        }).await.map_err(|error| error.to_string())?;
+    }
+
+    Ok(())
+}
@@ -6,6 +6,7 @@ use sqlx::{
     MySql,
 };
 use super::get_pool;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
 
 #[allow(unused)]
 #[derive(Debug, Default, FromRow)]
@@ -225,6 +226,7 @@ pub async fn update_photo_album(
 
 pub async fn mark_photo_album_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, PhotoAlbum>(r#"
         UPDATE photo_albums
@@ -238,7 +240,7 @@ pub async fn mark_photo_album_for_deletion(
 
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::PhotoAlbum, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
@@ -304,6 +306,7 @@ pub async fn update_photo(
 
 pub async fn mark_photo_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Photo>(r#"
         UPDATE photos
@@ -317,7 +320,7 @@ pub async fn mark_photo_for_deletion(
 
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::Photo, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
@@ -0,0 +1,458 @@
+use std::error::Error;
+use std::io;
+use std::str::FromStr;
+use sqlx::{
+    FromRow,
+    MySql,
+    Row,
+};
+use strum_macros::{ Display, EnumString };
+use urlencoding::encode;
+
+use super::get_pool;
+use crate::database::lyrics::{ find_lyrics_by_content, get_lyrics_content_search_count, LyricsLanguage };
+use crate::database::tabs::SongTabType;
+use crate::util::format::to_kebab_case;
+use crate::util::lyrics::find_matching_line;
+use crate::util::search_key::create_search_key;
+use crate::util::search_index::find_search_index_entries;
+use crate::util::sql::sanitize_like_clause_value;
+use crate::util::user::create_user_profile_href;
+
+pub const SEARCH_RESULTS_PER_PAGE: u32 = 20;
+const SEARCH_RESULTS_PER_GROUP: u32 = 5;
+const SEARCH_DESCRIPTION_MAX_LENGTH: usize = 200;
+
+/**
+ * The tables with search keys, and the column each key is made from.
+ */
+static SEARCH_KEY_COLUMNS: [(&str, &str); 3] = [
+    ("bands", "band_name"),
+    ("albums", "album_name"),
+    ("songs", "song_name"),
+];
+
+#[derive(Clone, Debug, Default, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum SearchResultType {
+    #[default]
+    Bands,
+    Albums,
+    Songs,
+    Lyrics,
+    Tabs,
+    Photos,
+    Videos,
+    Members,
+}
+impl SearchResultType {
+    pub fn all() -> Vec<SearchResultType> {
+        vec![
+            SearchResultType::Bands,
+            SearchResultType::Albums,
+            SearchResultType::Songs,
+            SearchResultType::Lyrics,
+            SearchResultType::Tabs,
+            SearchResultType::Photos,
+            SearchResultType::Videos,
+            SearchResultType::Members,
+        ]
+    }
+
+    pub fn as_display(&self) -> &'static str {
+        match self {
+            SearchResultType::Bands => "Bands",
+            SearchResultType::Albums => "Albums",
+            SearchResultType::Songs => "Songs",
+            SearchResultType::Lyrics => "Lyrics",
+            SearchResultType::Tabs => "Tabs",
+            SearchResultType::Photos => "Photos",
+            SearchResultType::Videos => "Videos",
+            SearchResultType::Members => "Members",
+        }
+    }
+
+    pub fn as_singular_display(&self) -> &'static str {
+        match self {
+            SearchResultType::Bands => "Band",
+            SearchResultType::Albums => "Album",
+            SearchResultType::Songs => "Song",
+            SearchResultType::Lyrics => "Lyrics",
+            SearchResultType::Tabs => "Tab",
+            SearchResultType::Photos => "Photo",
+            SearchResultType::Videos => "Video",
+            SearchResultType::Members => "Member",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub description: String,
+    pub href: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SearchResultGroup {
+    pub result_type: SearchResultType,
+    pub results: Vec<SearchResult>,
+    pub result_count: u32,
+}
+
+/**
+ * Tabs, photos, videos and members select the same columns, so they share one row type.
+ * What "parent_slug" and "slug" hold depends on the type, see create_search_result.
+ */
+#[derive(Debug, Default, Clone, FromRow)]
+struct SearchRow {
+    title: String,
+    description: String,
+    band_slug: String,
+    parent_slug: String,
+    slug: String,
+    username: String,
+}
+
+#[derive(FromRow)]
+struct SearchKeyRow {
+    id: i32,
+    name: String,
+}
+
+/**
+ * A band, album or song with its stored search key, for the search index.
+ * Slugs that don't apply to the type are empty, and "description" is the band name.
+ */
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct SearchKeyEntry {
+    pub id: i32,
+    pub title: String,
+    pub description: String,
+    pub band_slug: String,
+    pub album_slug: String,
+    pub song_slug: String,
+    pub search_key: String,
+}
+
+/**
+ * The query for a type of result, and how many times the search is bound in it.
+ * Bands, albums and songs are found without a query, see is_ranked_by_search_key.
+ */
+fn get_search_sql(result_type: &SearchResultType) -> (&'static str, usize) {
+    match result_type {
+        SearchResultType::Tabs => (r#"
+            SELECT songs.song_name AS title, bands.band_name AS description, bands.band_slug,
+                CAST(tabs.tab_type AS CHAR) AS parent_slug, songs.song_slug AS slug, tabs.username
+            FROM tabs
+            JOIN songs ON tabs.song = songs.id
+            JOIN bands ON songs.band = bands.id
+            WHERE songs.song_name LIKE ? AND tabs.is_deleted=0 AND songs.is_deleted=0
+        "#, 1),
+        SearchResultType::Photos => (r#"
+            SELECT photos.title, photos.description, '' AS band_slug,
+                photo_albums.slug AS parent_slug, CAST(photos.id AS CHAR) AS slug, photos.username
+            FROM photos
+            JOIN photo_albums ON photos.album = photo_albums.id
+            WHERE (photos.title LIKE ? OR photos.description LIKE ?)
+                AND photos.is_deleted=0 AND photo_albums.is_deleted=0
+        "#, 2),
+        SearchResultType::Videos => (r#"
+            SELECT videos.title, videos.description, '' AS band_slug,
+                video_categories.slug AS parent_slug, videos.slug, videos.username
+            FROM videos
+            JOIN video_categories ON videos.category = video_categories.id
+            WHERE videos.title LIKE ? AND videos.is_deleted=0 AND video_categories.is_deleted=0
+        "#, 1),
+        SearchResultType::Members => (r#"
+            SELECT username AS title, '' AS description, '' AS band_slug, '' AS parent_slug, '' AS slug, username
+            FROM users
+            WHERE username LIKE ? AND is_deleted=0
+        "#, 1),
+        SearchResultType::Bands | SearchResultType::Albums | SearchResultType::Songs | SearchResultType::Lyrics => ("", 0),
+    }
+}
+
+fn is_ranked_by_search_key(result_type: &SearchResultType) -> bool {
+    matches!(result_type, SearchResultType::Bands | SearchResultType::Albums | SearchResultType::Songs)
+}
+
+/**
+ * Bands, albums and songs are ranked by their search keys in the search index.
+ * Returns how many matched, and the results from "start".
+ */
+async fn find_search_results_by_search_key(
+    result_type: &SearchResultType,
+    search: &str,
+    start: u32,
+    length: u32,
+) -> Result<(u32, Vec<SearchResult>), Box<dyn Error>> {
+    let entries = find_search_index_entries(Some(result_type), search, usize::MAX).await;
+    let result_count = u32::try_from(entries.len())?;
+
+    Ok((
+        result_count,
+        entries.into_iter()
+            .skip(start as usize)
+            .take(length as usize)
+            .map(|entry| SearchResult {
+                title: entry.title,
+                description: entry.description,
+                href: entry.href,
+            })
+            .collect(),
+    ))
+}
+
+fn create_search_result(result_type: &SearchResultType, row: SearchRow) -> SearchResult {
+    let SearchRow { title, description, band_slug, parent_slug, slug, username } = row;
+    match result_type {
+        SearchResultType::Tabs => {
+            let tab_type = SongTabType::from_str(&parent_slug).unwrap_or_default();
+            SearchResult {
+                href: format!("/tabs/{}/{}/{}/{}/", band_slug, slug, to_kebab_case(&parent_slug), encode(&username)),
+                title,
+                description: format!("{} by {}, {} tab", description, username, tab_type.as_display()),
+            }
+        },
+        SearchResultType::Photos => SearchResult {
+            href: format!("/photos/{}/{}/", parent_slug, slug),
+            title,
+            description: truncate_description(&description),
+        },
+        SearchResultType::Videos => SearchResult {
+            href: format!("/videos/{}/{}/", parent_slug, slug),
+            title,
+            description: truncate_description(&description),
+        },
+        SearchResultType::Members => SearchResult {
+            href: create_user_profile_href(&username),
+            title,
+            description,
+        },
+        SearchResultType::Bands | SearchResultType::Albums | SearchResultType::Songs | SearchResultType::Lyrics => {
+            SearchResult::default()
+        },
+    }
+}
+
+fn truncate_description(description: &str) -> String {
+    if description.chars().count() <= SEARCH_DESCRIPTION_MAX_LENGTH {
+        return description.to_string();
+    }
+    format!("{}…", description.chars().take(SEARCH_DESCRIPTION_MAX_LENGTH).collect::<String>().trim_end())
+}
+
+pub async fn get_search_results_count(result_type: &SearchResultType, search: &str) -> Result<u32, Box<dyn Error>> {
+    if result_type == &SearchResultType::Lyrics {
+        return get_lyrics_content_search_count(search).await;
+    }
+    if search.len() > 200 {
+        return Err(
+            Box::new(
+                io::Error::new(io::ErrorKind::Other, "Search term was too long.")
+            )
+        );
+    }
+    if is_ranked_by_search_key(result_type) {
+        return Ok(find_search_results_by_search_key(result_type, search, 0, 0).await?.0);
+    }
+
+    let (sql, bind_count) = get_search_sql(result_type);
+    let pattern = format!("%{}%", sanitize_like_clause_value(search));
+    let sql = format!("SELECT COUNT(*) FROM ({}) AS search_results", sql);
+    let mut query = sqlx::query::<MySql>(&sql);
+    for _ in 0..bind_count {
+        query = query.bind(pattern.clone());
+    }
+    Ok(
+        u32::try_from(query
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+pub async fn find_search_results(
+    result_type: &SearchResultType,
+    search: &str,
+    start: u32,
+    length: u32,
+) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    if result_type == &SearchResultType::Lyrics {
+        return find_lyrics_search_results(search, start, length).await;
+    }
+    if search.len() > 200 {
+        return Err(
+            Box::new(
+                io::Error::new(io::ErrorKind::Other, "Search term was too long.")
+            )
+        );
+    }
+    if is_ranked_by_search_key(result_type) {
+        return Ok(find_search_results_by_search_key(result_type, search, start, length).await?.1);
+    }
+
+    let (sql, bind_count) = get_search_sql(result_type);
+    let pattern = format!("%{}%", sanitize_like_clause_value(search));
+    let sql = format!("{} ORDER BY title ASC LIMIT ? OFFSET ?", sql);
+    let mut query = sqlx::query_as::<MySql, SearchRow>(&sql);
+    for _ in 0..bind_count {
+        query = query.bind(pattern.clone());
+    }
+    let rows = query
+        .bind(length)
+        .bind(start)
+        .fetch_all(get_pool())
+        .await?;
+
+    Ok(
+        rows.into_iter().map(|row| create_search_result(result_type, row)).collect()
+    )
+}
+
+/**
+ * Lyrics are found by their content with the full-text index, and described by the
+ * line that matched.
+ */
+async fn find_lyrics_search_results(search: &str, start: u32, length: u32) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let results = find_lyrics_by_content(search, start, length).await?;
+
+    Ok(
+        results.into_iter().map(|result| {
+            let translation_name = LyricsLanguage::from_str(&result.translation_language_code)
+                .map(|language| language.as_display())
+                .unwrap_or_default();
+            let description = match find_matching_line(
+                &result.kanji_content, &result.romaji_content, &result.translation_content, translation_name, search,
+            ) {
+                Some(matching_line) => format!("{}{}{}", matching_line.before, matching_line.matched, matching_line.after),
+                None => format!("{} by {}", result.band_name, result.username),
+            };
+            let mut href = format!("/lyrics/{}/{}/{}/?contributor={}",
+                result.band_slug, result.album_slug, result.song_slug, encode(&result.username),
+            );
+            if !result.translation_language_code.is_empty() {
+                href.push_str(&format!("&language={}", encode(&result.translation_language_code)));
+            }
+            SearchResult {
+                title: result.song_name,
+                description,
+                href,
+            }
+        }).collect()
+    )
+}
+
+/**
+ * Searches every type for its first few results, or a page of "result_type" when given.
+ * Types without any results are left out.
+ */
+pub async fn find_search_result_groups(
+    result_type: Option<&SearchResultType>,
+    search: &str,
+    page_number: u32,
+) -> Result<Vec<SearchResultGroup>, Box<dyn Error>> {
+    let search = search.trim();
+    if search.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (result_types, start, length) = match result_type {
+        Some(result_type) => (
+            vec![result_type.clone()],
+            (page_number.max(1) - 1) * SEARCH_RESULTS_PER_PAGE,
+            SEARCH_RESULTS_PER_PAGE,
+        ),
+        None => (SearchResultType::all(), 0, SEARCH_RESULTS_PER_GROUP),
+    };
+
+    let mut groups: Vec<SearchResultGroup> = Vec::new();
+    for result_type in result_types {
+        let (result_count, results) = if is_ranked_by_search_key(&result_type) {
+            find_search_results_by_search_key(&result_type, search, start, length).await?
+        } else {
+            let result_count = get_search_results_count(&result_type, search).await?;
+            if result_count == 0 {
+                continue;
+            }
+            (result_count, find_search_results(&result_type, search, start, length).await?)
+        };
+        if result_count == 0 {
+            continue;
+        }
+        groups.push(SearchResultGroup { result_type, results, result_count });
+    }
+
+    Ok(groups)
+}
+
+/**
+ * Every band, album and song that isn't deleted, with the search keys stored for their names.
+ */
+pub async fn get_search_key_entries() -> Result<Vec<(SearchResultType, SearchKeyEntry)>, Box<dyn Error>> {
+    let queries = [
+        (SearchResultType::Bands, r#"
+            SELECT id, band_name AS title, '' AS description, band_slug, '' AS album_slug, '' AS song_slug, search_key
+            FROM bands
+            WHERE is_deleted=0
+            LIMIT 1000
+        "#),
+        (SearchResultType::Albums, r#"
+            SELECT albums.id, albums.album_name AS title, bands.band_name AS description, bands.band_slug,
+                albums.album_slug, '' AS song_slug, albums.search_key
+            FROM albums
+            JOIN bands ON albums.band = bands.id
+            WHERE albums.is_deleted=0 AND bands.is_deleted=0
+            LIMIT 10000
+        "#),
+        (SearchResultType::Songs, r#"
+            SELECT songs.id, songs.song_name AS title, bands.band_name AS description, bands.band_slug,
+                albums.album_slug, songs.song_slug, songs.search_key
+            FROM songs
+            JOIN albums ON songs.album = albums.id
+            JOIN bands ON songs.band = bands.id
+            WHERE songs.is_deleted=0 AND albums.is_deleted=0 AND bands.is_deleted=0
+            LIMIT 10000
+        "#),
+    ];
+
+    let mut entries: Vec<(SearchResultType, SearchKeyEntry)> = Vec::new();
+    for (result_type, sql) in queries {
+        let rows = sqlx::query_as::<MySql, SearchKeyEntry>(sql)
+            .fetch_all(get_pool())
+            .await?;
+        entries.extend(rows.into_iter().map(|row| (result_type.clone(), row)));
+    }
+
+    Ok(entries)
+}
+
+/**
+ * Makes the search keys that are missing, for names added before search keys existed
+ * or after a migration cleared them. Called when the application starts.
+ */
+pub async fn fill_missing_search_keys() -> Result<(), Box<dyn Error + Send + Sync>> {
+    for (table, column) in SEARCH_KEY_COLUMNS {
+        let rows = sqlx::query_as::<MySql, SearchKeyRow>(&format!(
+            "SELECT id, {} AS name FROM {} WHERE search_key='' AND {}<>''", column, table, column,
+        ))
+            .fetch_all(get_pool())
+            .await?;
+        if rows.is_empty() {
+            continue;
+        }
+
+        tracing::info!("Filling in {} missing search keys for {}.", rows.len(), table);
+        let sql = format!("UPDATE {} SET search_key=? WHERE id=? LIMIT 1", table);
+        for row in rows {
+            sqlx::query(&sql)
+                .bind(create_search_key(&row.name))
+                .bind(row.id)
+                .execute(get_pool())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
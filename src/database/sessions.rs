@@ -0,0 +1,247 @@
+use std::error::Error;
+use chrono::NaiveDateTime;
+use sqlx::{
+    FromRow,
+    MySql,
+};
+
+use super::get_pool;
+
+/**
+ * A tower session record. The record itself is stored as JSON, user_id and expiry_date
+ * are copied out of it so that sessions can be queried per user and swept when expired.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct SessionRecord {
+    pub id: String,
+    pub user_id: Option<i32>,
+    pub record: String,
+    pub expiry_date: i64,
+    pub create_time: NaiveDateTime,
+    pub last_seen_time: NaiveDateTime,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub location: String,
+}
+
+/**
+ * Where a session was last used from. Copied out of the session record on save.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, Clone)]
+pub struct SessionClient {
+    pub ip_address: String,
+    pub user_agent: String,
+    pub location: String,
+}
+
+/**
+ * Data about a signed in user that isn't stored on the users table.
+ */
+#[allow(unused)]
+#[derive(Debug, Default, Clone, FromRow)]
+pub struct UserSessionRecord {
+    pub user_id: i32,
+    pub ip_address: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+pub async fn get_session_record(
+    id: &str,
+    now: i64,
+) -> Result<Option<SessionRecord>, Box<dyn Error + Send + Sync>> {
+    Ok(
+        sqlx::query_as::<MySql, SessionRecord>(r#"
+            SELECT * FROM sessions
+            WHERE id=? AND expiry_date > ?
+            LIMIT 1
+        "#)
+            .bind(id)
+            .bind(now)
+            .fetch_optional(get_pool())
+            .await?
+    )
+}
+
+pub async fn session_record_exists(
+    id: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    Ok(
+        sqlx::query("SELECT 1 FROM sessions WHERE id=? LIMIT 1")
+            .bind(id)
+            .fetch_optional(get_pool())
+            .await?
+            .is_some()
+    )
+}
+
+pub async fn save_session_record(
+    id: &str,
+    user_id: Option<i32>,
+    record: &str,
+    expiry_date: i64,
+    client: &SessionClient,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        INSERT INTO sessions (
+            id, user_id, record, expiry_date, create_time, last_seen_time, ip_address, user_agent, location
+        )
+        VALUES (?, ?, ?, ?, NOW(), NOW(), ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            user_id=VALUES(user_id), record=VALUES(record), expiry_date=VALUES(expiry_date), last_seen_time=NOW(),
+            ip_address=VALUES(ip_address), user_agent=VALUES(user_agent), location=VALUES(location)
+    "#)
+        .bind(id)
+        .bind(user_id)
+        .bind(record)
+        .bind(expiry_date)
+        .bind(&client.ip_address)
+        .bind(&client.user_agent)
+        .bind(&client.location)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_session_record(
+    id: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("DELETE FROM sessions WHERE id=? LIMIT 1")
+        .bind(id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn get_session_records_by_user_id(
+    user_id: i32,
+    now: i64,
+) -> Result<Vec<SessionRecord>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, SessionRecord>(r#"
+            SELECT * FROM sessions
+            WHERE user_id=? AND expiry_date > ?
+            ORDER BY last_seen_time DESC
+            LIMIT 100
+        "#)
+            .bind(user_id)
+            .bind(now)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+/**
+ * Sessions are revoked by a sha256 hash of their id, since the id itself is the
+ * value of the session cookie and should never be shown on a page.
+ */
+pub async fn delete_session_record_by_user_id_and_hash(
+    user_id: i32,
+    id_hash: &str,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query("DELETE FROM sessions WHERE user_id=? AND SHA2(id, 256)=? LIMIT 1")
+        .bind(user_id)
+        .bind(id_hash)
+        .execute(get_pool())
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/**
+ * Deletes every session of a user, optionally keeping the one the request came from.
+ */
+pub async fn delete_session_records_by_user_id(
+    user_id: i32,
+    except_id: Option<&str>,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query("DELETE FROM sessions WHERE user_id=? AND id<>?")
+        .bind(user_id)
+        .bind(except_id.unwrap_or(""))
+        .execute(get_pool())
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/**
+ * Removes expired sessions, and the user session data of users who no longer
+ * have any session left.
+ */
+pub async fn delete_expired_session_records(
+    now: i64,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query("DELETE FROM sessions WHERE expiry_date <= ?")
+        .bind(now)
+        .execute(get_pool())
+        .await?;
+    sqlx::query(r#"
+        DELETE FROM user_sessions
+        WHERE NOT EXISTS (
+            SELECT 1 FROM sessions WHERE sessions.user_id = user_sessions.user_id
+        )
+    "#)
+        .execute(get_pool())
+        .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn get_user_session_record(
+    user_id: i32,
+) -> Result<Option<UserSessionRecord>, Box<dyn Error + Send + Sync>> {
+    Ok(
+        sqlx::query_as::<MySql, UserSessionRecord>(r#"
+            SELECT user_id, ip_address, latitude, longitude FROM user_sessions
+            WHERE user_id=?
+            LIMIT 1
+        "#)
+            .bind(user_id)
+            .fetch_optional(get_pool())
+            .await?
+    )
+}
+
+pub async fn save_user_session_record(
+    user_session: &UserSessionRecord,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        INSERT INTO user_sessions (user_id, ip_address, latitude, longitude, update_time)
+        VALUES (?, ?, ?, ?, NOW())
+        ON DUPLICATE KEY UPDATE
+            ip_address=VALUES(ip_address), latitude=VALUES(latitude), longitude=VALUES(longitude), update_time=NOW()
+    "#)
+        .bind(user_session.user_id)
+        .bind(&user_session.ip_address)
+        .bind(user_session.latitude)
+        .bind(user_session.longitude)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn update_user_session_record_ip(
+    user_id: i32,
+    ip_address: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        UPDATE user_sessions
+        SET ip_address=?, update_time=NOW()
+        WHERE user_id=?
+        LIMIT 1
+    "#)
+        .bind(ip_address)
+        .bind(user_id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_user_session_record(
+    user_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("DELETE FROM user_sessions WHERE user_id=? LIMIT 1")
+        .bind(user_id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
@@ -5,8 +5,10 @@ use sqlx::{
     MySql,
 };
 use super::get_pool;
+use crate::database::search::SearchResultType;
 use crate::util::format;
-use crate::util::sql::sanitize_like_clause_value;
+use crate::util::search_key::create_search_key;
+use crate::util::search_index::{ find_search_index_entries, mark_search_index_stale };
 
 #[allow(unused)]
 #[derive(Debug, Default, Clone, FromRow)]
@@ -134,7 +136,7 @@ pub async fn get_song_slugs_by_ids(ids: &Vec<i32>) -> Result<Vec<JoinedSongSlugs
         JOIN bands ON songs.band = bands.id
         WHERE songs.id IN ({}) AND songs.is_deleted=0
         ORDER BY FIELD(songs.id, {})
-        LIMIT 100;
+        LIMIT 1000;
     "#, ids_group, ids_group);
 
     let result = sqlx::query_as::<MySql, JoinedSongSlugs>(&query)
@@ -171,16 +173,18 @@ pub async fn create_songs_by_names(names: &Vec<String>, album_id: i32, band_id:
             None => {
                 let _ = sqlx::query_as::<MySql, Song>(r#"
                     INSERT INTO songs (
-                        band, album, song_slug, song_name, tab_count
+                        band, album, song_slug, song_name, search_key, tab_count
                     )
-                    VALUES (?, ?, ?, ?, 0)
+                    VALUES (?, ?, ?, ?, ?, 0)
                 "#)
                     .bind(band_id)
                     .bind(album_id)
                     .bind(&song_slug)
                     .bind(name)
+                    .bind(create_search_key(name))
                     .fetch_optional(get_pool())
                     .await?;
+                mark_search_index_stale();
                 let insert_result = sqlx::query_as::<MySql, Song>(r#"
                     SELECT * from songs
                     WHERE song_slug=? AND band=? AND is_deleted=0
@@ -207,14 +211,20 @@ pub async fn create_songs_by_names(names: &Vec<String>, album_id: i32, band_id:
 #[allow(unused)]
 #[derive(Debug, Default, Clone, FromRow)]
 pub struct SongSearchResult {
+    pub id: i32,
     pub song_name: String,
     pub song_slug: String,
     pub album_slug: String,
     pub band_slug: String,
     pub band_name: String,
     pub has_translation: bool,
+    pub translation_languages: String,
 }
 
+/**
+ * Songs are ranked by their search keys in the search index first, so the
+ * translations are only looked up for the songs that are shown.
+ */
 pub async fn find_songs_with_translations_by_name(search: &str) -> Result<Vec<SongSearchResult>, Box<dyn Error>> {
     if search.len() > 200 {
         return Err(
@@ -224,8 +234,18 @@ pub async fn find_songs_with_translations_by_name(search: &str) -> Result<Vec<So
         );
     }
 
-    let result = sqlx::query_as::<MySql, SongSearchResult>(r#"
+    let song_ids: Vec<i32> = find_search_index_entries(Some(&SearchResultType::Songs), search, 15).await
+        .into_iter()
+        .map(|song| song.id)
+        .collect();
+    if song_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids_group = song_ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(", ");
+
+    let sql = format!(r#"
         SELECT
+            songs.id,
             songs.song_slug,
             songs.song_name,
             albums.album_slug,
@@ -235,14 +255,20 @@ pub async fn find_songs_with_translations_by_name(search: &str) -> Result<Vec<So
                 SELECT 1 
                 FROM lyrics 
                 WHERE lyrics.song = songs.id AND lyrics.is_deleted=0
-            )) AS has_translation
+            )) AS has_translation,
+            COALESCE((
+                SELECT GROUP_CONCAT(DISTINCT lyrics_translations.language_code)
+                FROM lyrics_translations
+                JOIN lyrics ON lyrics_translations.lyrics_id = lyrics.id
+                WHERE lyrics.song = songs.id AND lyrics.is_deleted=0
+            ), '') AS translation_languages
         FROM songs
         JOIN albums ON songs.album = albums.id
         JOIN bands ON songs.band = bands.id
-        WHERE songs.song_name LIKE ? AND songs.is_deleted=0
-        LIMIT 15;
-    "#)
-        .bind(format!("%{}%", sanitize_like_clause_value(search)))
+        WHERE songs.id IN ({}) AND songs.is_deleted=0
+        ORDER BY FIELD(songs.id, {})
+    "#, ids_group, ids_group);
+    let result = sqlx::query_as::<MySql, SongSearchResult>(&sql)
         .fetch_all(get_pool())
         .await?;
 
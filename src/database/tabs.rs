@@ -3,11 +3,13 @@ use chrono::NaiveDateTime;
 use sqlx::{
     FromRow,
     MySql,
+    MySqlConnection,
     Type,
 };
 use strum_macros::{ Display, EnumString };
 
 use super::get_pool;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
 use crate::database::songs::Song;
 
 #[derive(Clone, Debug, Default, Display, EnumString, Type)]
@@ -56,6 +58,16 @@ pub struct SongTab {
     pub tab_content: String,
 }
 
+#[allow(unused)]
+#[derive(Clone, Debug, Default, FromRow)]
+pub struct TabRevision {
+    pub id: i32,
+    pub tab_id: i32,
+    pub username: String,
+    pub revision_time: NaiveDateTime,
+    pub tab_content: String,
+}
+
 pub async fn get_song_tab_by_id(id: i32) -> Result<SongTab, Box<dyn Error>> {
     let result = sqlx::query_as::<MySql, SongTab>(r#"
         SELECT * FROM tabs
@@ -117,18 +129,25 @@ pub async fn get_song_tabs_by_song_id(id: i32) -> Result<Vec<JoinedSongTab>, Box
 pub async fn create_song_tab(
     tab: SongTab,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let result = sqlx::query_as::<MySql, SongTab>(r#"
+    let mut transaction = get_pool().begin().await?;
+
+    let tab_id = sqlx::query(r#"
         INSERT INTO tabs (
             username, post_time, song, tab_type, tab_content
         )
         VALUES (?, NOW(), ?, ?, ?)
     "#)
-        .bind(tab.username)
+        .bind(&tab.username)
         .bind(tab.song)
-        .bind(tab.tab_type)
-        .bind(tab.tab_content)
-        .fetch_optional(get_pool())
-        .await;
+        .bind(&tab.tab_type)
+        .bind(&tab.tab_content)
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_id();
+
+    insert_tab_revision(&mut *transaction, i32::try_from(tab_id)?, &tab.username, &tab.tab_content).await?;
+
+    transaction.commit().await?;
 
     let _ = sqlx::query_as::<MySql, Song>(r#"
         UPDATE songs
@@ -140,43 +159,75 @@ pub async fn create_song_tab(
         .fetch_optional(get_pool())
         .await;
 
-    match result {
-        Ok(_) => {
-            Ok(())
-        }
-        Err(e) => {
-            Err(Box::new(e))
-        }
-    }
+    Ok(())
 }
 
+/**
+ * "edited_by" may be a moderator with the EditTabs permission, not the contributor.
+ */
 pub async fn update_song_tab(
     tab: SongTab,
+    edited_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let result = sqlx::query_as::<MySql, SongTab>(r#"
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
         UPDATE tabs
         SET tab_type=?, tab_content=?
         WHERE id=?
         LIMIT 1
     "#)
-        .bind(tab.tab_type)
-        .bind(tab.tab_content)
+        .bind(&tab.tab_type)
+        .bind(&tab.tab_content)
         .bind(tab.id)
-        .fetch_optional(get_pool())
-        .await;
+        .execute(&mut *transaction)
+        .await?;
 
-    match result {
-        Ok(_) => {
-            Ok(())
-        }
-        Err(e) => {
-            Err(Box::new(e))
-        }
-    }
+    insert_tab_revision(&mut *transaction, tab.id, edited_by, &tab.tab_content).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+async fn insert_tab_revision(
+    connection: &mut MySqlConnection,
+    tab_id: i32,
+    username: &str,
+    tab_content: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        INSERT INTO tab_revisions (tab_id, username, revision_time, tab_content)
+        VALUES (?, ?, NOW(), ?)
+    "#)
+        .bind(tab_id)
+        .bind(username)
+        .bind(tab_content)
+        .execute(connection)
+        .await?;
+
+    Ok(())
+}
+
+/**
+ * Newest first, the first revision has the current content of the tab.
+ */
+pub async fn get_tab_revisions(tab_id: i32) -> Result<Vec<TabRevision>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, TabRevision>(r#"
+            SELECT * FROM tab_revisions
+            WHERE tab_id=?
+            ORDER BY revision_time DESC, id DESC
+        "#)
+            .bind(tab_id)
+            .fetch_all(get_pool())
+            .await?
+    )
 }
 
 pub async fn mark_tab_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let song_id = match get_song_tab_by_id(id).await {
         Ok(tab) => tab.song,
@@ -205,7 +256,7 @@ pub async fn mark_tab_for_deletion(
     
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::Tabs, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
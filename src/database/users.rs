@@ -259,6 +259,9 @@ pub struct User {
     pub ip_address: String,
     pub permissions: UserPermissionSet,
     pub preferences: UserPreferenceSet,
+    pub totp_secret: String,
+    pub totp_enabled: bool,
+    pub totp_last_used_step: i64,
 }
 
 #[allow(unused)]
@@ -477,3 +480,236 @@ pub async fn update_user_profile_picture(
         }
     }
 }
+
+/**
+ * Two-factor authentication
+ */
+
+pub async fn enable_user_totp(
+    user_id: i32,
+    encrypted_secret: &str,
+    recovery_code_hashes: &Vec<String>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
+        UPDATE users
+        SET totp_secret=?, totp_enabled=1, totp_last_used_step=0
+        WHERE id=?
+        LIMIT 1
+    "#)
+        .bind(encrypted_secret)
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id=?")
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for code_hash in recovery_code_hashes {
+        sqlx::query("INSERT INTO user_recovery_codes (user_id, code_hash, create_time) VALUES (?, ?, NOW())")
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn disable_user_totp(
+    user_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query(r#"
+        UPDATE users
+        SET totp_secret='', totp_enabled=0, totp_last_used_step=0
+        WHERE id=?
+        LIMIT 1
+    "#)
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id=?")
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn replace_user_recovery_codes(
+    user_id: i32,
+    recovery_code_hashes: &Vec<String>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    sqlx::query("DELETE FROM user_recovery_codes WHERE user_id=?")
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    for code_hash in recovery_code_hashes {
+        sqlx::query("INSERT INTO user_recovery_codes (user_id, code_hash, create_time) VALUES (?, ?, NOW())")
+            .bind(user_id)
+            .bind(code_hash)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_unused_recovery_code_count(
+    user_id: i32,
+) -> Result<u32, Box<dyn Error>> {
+    Ok(
+        u32::try_from(sqlx::query("SELECT COUNT(*) FROM user_recovery_codes WHERE user_id=? AND used_time IS NULL")
+            .bind(user_id)
+            .fetch_one(get_pool())
+            .await?
+            .get::<i64, usize>(0)
+        )?
+    )
+}
+
+/**
+ * Records the time step of an accepted code. Returns false if that step (or a later one)
+ * was already used, which stops a code from being replayed within its window.
+ */
+pub async fn consume_user_totp_step(
+    user_id: i32,
+    step: u64,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query(r#"
+        UPDATE users
+        SET totp_last_used_step=?
+        WHERE id=? AND totp_enabled=1 AND totp_last_used_step < ?
+        LIMIT 1
+    "#)
+        .bind(step as i64)
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(get_pool())
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn consume_user_recovery_code(
+    user_id: i32,
+    code_hash: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let result = sqlx::query(r#"
+        UPDATE user_recovery_codes
+        SET used_time=NOW()
+        WHERE user_id=? AND code_hash=? AND used_time IS NULL
+        LIMIT 1
+    "#)
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(get_pool())
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/**
+ * The blocklist column holds a JSON array with the ids of the users that were blocked.
+ */
+pub async fn get_user_blocklist(
+    user_id: i32,
+) -> Result<Vec<i32>, Box<dyn Error>> {
+    let blocklist = sqlx::query("SELECT CAST(COALESCE(blocklist, JSON_ARRAY()) AS CHAR) FROM users WHERE id=? LIMIT 1")
+        .bind(user_id)
+        .fetch_optional(get_pool())
+        .await?
+        .map(|row| row.get::<String, usize>(0))
+        .unwrap_or_else(|| String::from("[]"));
+    Ok(serde_json::from_str::<Vec<i32>>(&blocklist).unwrap_or_default())
+}
+
+/**
+ * True if either user has blocked the other.
+ */
+pub async fn is_either_user_blocked(
+    user_id: i32,
+    other_user_id: i32,
+) -> Result<bool, Box<dyn Error>> {
+    Ok(
+        get_user_blocklist(user_id).await?.contains(&other_user_id)
+            || get_user_blocklist(other_user_id).await?.contains(&user_id)
+    )
+}
+
+pub async fn get_blocked_users(
+    user_id: i32,
+) -> Result<Vec<UserSummary>, Box<dyn Error>> {
+    Ok(
+        sqlx::query_as::<MySql, UserSummary>(r#"
+            SELECT users.username, users.profile_picture_filename
+            FROM users
+            WHERE JSON_CONTAINS(
+                (SELECT COALESCE(blockers.blocklist, JSON_ARRAY()) FROM users AS blockers WHERE blockers.id = ?),
+                CAST(users.id AS JSON)
+            )
+            ORDER BY users.username ASC
+        "#)
+            .bind(user_id)
+            .fetch_all(get_pool())
+            .await?
+    )
+}
+
+pub async fn block_user(
+    user_id: i32,
+    blocked_user_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(r#"
+        UPDATE users
+        SET blocklist = JSON_ARRAY_APPEND(COALESCE(blocklist, JSON_ARRAY()), '$', ?)
+        WHERE id = ? AND NOT JSON_CONTAINS(COALESCE(blocklist, JSON_ARRAY()), CAST(? AS JSON))
+    "#)
+        .bind(blocked_user_id)
+        .bind(user_id)
+        .bind(blocked_user_id)
+        .execute(get_pool())
+        .await?;
+    Ok(())
+}
+
+pub async fn unblock_user(
+    user_id: i32,
+    blocked_user_id: i32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction = get_pool().begin().await?;
+
+    let blocklist = sqlx::query("SELECT CAST(COALESCE(blocklist, JSON_ARRAY()) AS CHAR) FROM users WHERE id=? LIMIT 1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_one(&mut *transaction)
+        .await?
+        .get::<String, usize>(0);
+    let blocklist: Vec<i32> = serde_json::from_str::<Vec<i32>>(&blocklist)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|id| *id != blocked_user_id)
+        .collect();
+
+    sqlx::query("UPDATE users SET blocklist=CAST(? AS JSON) WHERE id=?")
+        .bind(serde_json::to_string(&blocklist)?)
+        .bind(user_id)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
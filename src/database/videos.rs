@@ -6,6 +6,7 @@ use sqlx::{
     MySql,
 };
 use super::get_pool;
+use crate::database::deletion_queue::{ queue_deletion, DeletionContentType };
 
 #[allow(unused)]
 #[derive(Debug, Default, FromRow)]
@@ -222,6 +223,7 @@ pub async fn update_video_category(
 
 pub async fn mark_video_category_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, VideoCategory>(r#"
         UPDATE video_categories
@@ -235,7 +237,7 @@ pub async fn mark_video_category_for_deletion(
 
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::VideoCategory, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
@@ -303,6 +305,7 @@ pub async fn update_video(
 
 pub async fn mark_video_for_deletion(
     id: i32,
+    requested_by: &str,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let result = sqlx::query_as::<MySql, Video>(r#"
         UPDATE videos
@@ -316,7 +319,7 @@ pub async fn mark_video_for_deletion(
 
     match result {
         Ok(_) => {
-            Ok(())
+            queue_deletion(DeletionContentType::Video, id, requested_by).await
         }
         Err(e) => {
             Err(Box::new(e))
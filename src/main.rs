@@ -14,12 +14,36 @@ async fn main() {
 
     let _ = util::tracing::init_tracing();
     let _ = database::init_pool().await;
+
+    // "cargo run -- migrate <up|down [count]|status>" manages the schema and exits.
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|arg| arg.as_str()) == Some("migrate") {
+        if let Err(error) = database::migrations::run_command(&args[2..]).await {
+            tracing::error!("Migration command failed. {:?}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+    database::migrations::run_pending_migrations()
+        .await
+        .expect("Failed to apply database migrations.");
+    if let Err(error) = database::fill_missing_search_keys().await {
+        tracing::warn!("Failed to fill in missing search keys. {:?}", error);
+    }
+
     let _ = router::authn::init_user_sessions().await;
     let _ = util::captcha::init_captchas();
+    let _ = util::geolocation::init_geolocation();
     let _ = util::password_reset_session::init_password_reset_sessions();
     let _ = util::rate_limit::init_rate_limits();
+    let _ = util::romaji::init_romaji();
+    util::search_index::init_search_index().await;
     let _ = util::smtp::init_mailer();
+    let _ = util::totp::init_totp();
+    let _ = util::trash::init_trash();
     tokio::spawn(util::image_upload::init_temporary_image_upload_cleanup());
+    tokio::spawn(router::session_store::init_session_expiry_sweep());
+    tokio::spawn(util::trash::init_trash_purge());
 
     let app = router::initialize();
 
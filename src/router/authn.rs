@@ -1,12 +1,12 @@
 /**
  * This file handles authenticating a user against the database,
- * and builds a user session in memory of data that needs to be readily
- * accessible on every request.
+ * and builds a user session of data that needs to be readily
+ * accessible on every request. User sessions are stored in the database
+ * so they are shared between instances, with a short-lived cache in memory.
  */
 
-use std::collections::HashMap;
 use std::error::Error;
-use std::sync::RwLock;
+use std::fmt;
 use argon2::{
     password_hash::{
         rand_core::{ OsRng },
@@ -16,11 +16,16 @@ use argon2::{
 };
 use async_trait::async_trait;
 use axum_login::{ AuthUser, AuthnBackend, UserId };
+use chrono::prelude::{ DateTime, Utc };
+use dashmap::DashMap;
 use serde::Deserialize;
 use tokio::sync::OnceCell;
 
-use crate::database::{ self, User, UserPermissionSet, UserPreferenceSet };
+use crate::database::{ self, User, UserPermissionSet, UserPreferenceSet, UserSessionRecord };
+use crate::router::session_store::SESSION_USER_ID_KEY;
 use crate::util::geolocation::{ self, Geolocation };
+use crate::util::rate_limit::rate_limit_exceeded;
+use crate::util::totp;
 
 #[allow(unused)]
 #[derive(Debug, Default, Clone)]
@@ -48,21 +53,113 @@ impl AuthUser for UserSession {
     }
 }
 
-pub static USER_SESSIONS: OnceCell<RwLock<HashMap<i32, UserSession>>> = OnceCell::const_new();
+impl UserSession {
+    fn new(user: &User, user_session_record: &UserSessionRecord) -> UserSession {
+        UserSession {
+            id: user.id,
+            username: user.username.clone(),
+            password: user.password.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            ip_address: user_session_record.ip_address.clone(),
+            latitude: user_session_record.latitude,
+            longitude: user_session_record.longitude,
+            permissions: user.permissions.clone(),
+            preferences: user.preferences.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedUserSession {
+    pub user_session: UserSession,
+    pub timestamp: DateTime<Utc>,
+}
+
+/**
+ * Other instances can change a user (permissions, password) at any time, so cached
+ * sessions are only trusted for a short while before being reloaded from the database.
+ */
+pub static USER_SESSIONS: OnceCell<DashMap<i32, CachedUserSession>> = OnceCell::const_new();
+pub static USER_SESSION_CACHE_SECONDS: i64 = 30;
 
 pub async fn init_user_sessions() {
     USER_SESSIONS
-        .set(RwLock::new(HashMap::new()))
+        .set(DashMap::new())
         .expect("User sessions already initialized.");
 }
 
+fn cache_user_session(user_session: &UserSession) {
+    let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
+    user_sessions.insert(
+        user_session.id,
+        CachedUserSession {
+            user_session: user_session.clone(),
+            timestamp: Utc::now(),
+        }
+    );
+}
+
+fn forget_cached_user_session(id: i32) {
+    let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
+    user_sessions.remove(&id);
+}
+
+async fn load_user_session(id: i32) -> Option<UserSession> {
+    let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
+    let now = Utc::now();
+    if let Some(cached) = user_sessions.get(&id) {
+        if now.signed_duration_since(cached.timestamp).num_seconds() < USER_SESSION_CACHE_SECONDS {
+            return Some(cached.user_session.clone());
+        }
+    }
+
+    let user_session_record = match database::get_user_session_record(id).await {
+        Ok(Some(user_session_record)) => user_session_record,
+        _ => {
+            user_sessions.remove(&id);
+            return None;
+        },
+    };
+    let user = match database::get_user_by_id(&id).await {
+        Ok(user) => user,
+        Err(_) => {
+            user_sessions.remove(&id);
+            return None;
+        },
+    };
+    let user_session = UserSession::new(&user, &user_session_record);
+    cache_user_session(&user_session);
+    Some(user_session)
+}
+
+/**
+ * For accounts with two-factor authentication, the first sign in step sends the
+ * username and password and receives a challenge token. The second step sends the
+ * token and a TOTP or recovery code, with an empty password.
+ */
 #[derive(Clone, Deserialize)]
 pub struct Credentials {
     pub username: String,
     pub password: String,
     pub ip_address: String,
+    pub two_factor_token: String,
+    pub two_factor_code: String,
 }
 
+#[derive(Debug)]
+pub enum AuthnError {
+    TwoFactorRequired(String),
+}
+impl fmt::Display for AuthnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthnError::TwoFactorRequired(_) => write!(f, "A two-factor authentication code is required."),
+        }
+    }
+}
+impl Error for AuthnError {}
+
 #[derive(Clone, Default)]
 pub struct Backend {
 }
@@ -71,60 +168,76 @@ pub struct Backend {
 impl AuthnBackend for Backend {
     type User = UserSession;
     type Credentials = Credentials;
-    type Error = std::convert::Infallible;
+    type Error = AuthnError;
 
     async fn authenticate(
         &self,
-        Credentials { username, password, ip_address }: Self::Credentials,
+        Credentials { username, password, ip_address, two_factor_token, two_factor_code }: Self::Credentials,
     ) -> Result<Option<Self::User>, Self::Error> {
+        let mut two_factor_challenge: Option<String> = None;
         let user = match async {
-            let user = match database::get_user_by_username(&username).await {
-                Ok(user) => user,
-                Err(_) => User::default(),
+            let user = if two_factor_token.is_empty() {
+                let user = match database::get_user_by_username(&username).await {
+                    Ok(user) => user,
+                    Err(_) => User::default(),
+                };
+
+                let parsed_hash = PasswordHash::new(&user.password)?;
+                Argon2::default().verify_password(password.as_bytes(), &parsed_hash)?;
+
+                if user.totp_enabled {
+                    if two_factor_rate_limit_exceeded(user.id, &ip_address) {
+                        return Ok(None);
+                    }
+                    two_factor_challenge = Some(totp::create_sign_in_challenge(user.id));
+                    return Ok(None);
+                }
+                user
+            } else {
+                // The password was already checked when this challenge was created.
+                let user_id = totp::attempt_sign_in_challenge(&two_factor_token).ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::Other, "Sign in challenge expired.")
+                })?;
+                let user = database::get_user_by_id(&user_id).await?;
+                if two_factor_rate_limit_exceeded(user.id, &ip_address) {
+                    return Ok(None);
+                }
+                if !verify_two_factor_code(&user, &two_factor_code).await {
+                    return Ok(None);
+                }
+                totp::discard_sign_in_challenge(&two_factor_token);
+                user
             };
 
-            let crate::database::User {
-                id,
-                username: stored_username,
-                password: stored_password_hash,
-                first_name,
-                last_name,
-                permissions,
-                preferences,
-                ..
-            } = &user;
-
-            let parsed_hash = PasswordHash::new(&stored_password_hash)?;
-            Argon2::default().verify_password(password.as_bytes(), &parsed_hash)?;
-
-            let location = match geolocation::find("99.174.217.40").await {
+            let location = match geolocation::find(&ip_address).await {
                 Ok(location) => location,
                 Err(_) => Geolocation::default(),
             };
 
-            let user_session = Self::User {
-                id: id.clone(),
-                username: stored_username.clone(),
-                password: stored_password_hash.clone(),
-                first_name: first_name.clone(),
-                last_name: last_name.clone(),
+            let user_session_record = UserSessionRecord {
+                user_id: user.id,
                 ip_address,
                 latitude: location.latitude,
                 longitude: location.longitude,
-                permissions: permissions.clone(),
-                preferences: preferences.clone(),
             };
+            if let Err(error) = database::save_user_session_record(&user_session_record).await {
+                tracing::warn!("Failed to store user session for user {}. {:?}", user.username, error);
+                return Ok(None);
+            }
 
-            let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
-            let mut user_sessions_write = user_sessions.write().unwrap_or_else(|poisoned| poisoned.into_inner());
-            user_sessions_write.insert(id.clone(), user_session.clone());
+            let user_session = Self::User::new(&user, &user_session_record);
+            cache_user_session(&user_session);
 
-            Ok::<Self::User, Box<dyn Error>>(user_session)
+            Ok::<Option<Self::User>, Box<dyn Error>>(Some(user_session))
         }.await {
-            Ok(user) => Some(user),
+            Ok(user) => user,
             Err(_) => None,
         };
 
+        if let Some(token) = two_factor_challenge {
+            return Err(AuthnError::TwoFactorRequired(token));
+        }
+
         Ok(user)
     }
 
@@ -132,10 +245,8 @@ impl AuthnBackend for Backend {
         &self,
         id: &UserId<Self>,
     ) -> Result<Option<Self::User>, Self::Error> {
-        let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
-        let user_sessions_read = user_sessions.read().unwrap_or_else(|poisoned| poisoned.into_inner());
         Ok(
-            user_sessions_read.get(&id).cloned()
+            load_user_session(*id).await
         )
     }
 }
@@ -160,10 +271,87 @@ pub fn verify_password(stored_password_hash: &str, password_entry: &str,) -> Res
     }
 }
 
-pub fn update_user_session_ip(id: &i32, ip_address: &str) {
+pub async fn update_user_session_ip(id: &i32, ip_address: &str) {
+    if let Err(error) = database::update_user_session_record_ip(*id, ip_address).await {
+        tracing::warn!("Failed to update user session IP for user {}. {:?}", id, error);
+    }
     let user_sessions = USER_SESSIONS.get().expect("User sessions not initialized.");
-    let mut user_sessions_write = user_sessions.write().unwrap_or_else(|poisoned| poisoned.into_inner());
-    if let Some(user_session) = user_sessions_write.get_mut(id) {
-        user_session.ip_address = String::from(ip_address);
+    if let Some(mut cached) = user_sessions.get_mut(id) {
+        cached.user_session.ip_address = String::from(ip_address);
+    }
+}
+
+/**
+ * Logs in and tags the session with the user id, so it can be found again
+ * when all of that user's sessions need to be invalidated.
+ */
+pub async fn login_user_session(auth_session: &mut AuthSession, user: &UserSession) -> Result<(), Box<dyn Error + Send + Sync>> {
+    auth_session.login(user).await?;
+    auth_session.session.insert(SESSION_USER_ID_KEY, user.id).await?;
+    Ok(())
+}
+
+/**
+ * Signs the user out everywhere, for example after a password reset.
+ */
+pub async fn invalidate_user_sessions(user_id: i32) -> Result<(), Box<dyn Error + Send + Sync>> {
+    database::delete_session_records_by_user_id(user_id, None).await?;
+    database::delete_user_session_record(user_id).await?;
+    forget_cached_user_session(user_id);
+    Ok(())
+}
+
+/**
+ * Signs the user out of every session except the current one. The current session is
+ * logged in again so it picks up the new password hash used by session_auth_hash.
+ */
+pub async fn invalidate_other_user_sessions(auth_session: &mut AuthSession) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let user_id = match &auth_session.user {
+        Some(user) => user.id,
+        None => return Ok(()),
+    };
+    let session_id = auth_session.session.id().map(|id| id.to_string());
+    database::delete_session_records_by_user_id(user_id, session_id.as_deref()).await?;
+    forget_cached_user_session(user_id);
+    if let Some(user_session) = load_user_session(user_id).await {
+        login_user_session(auth_session, &user_session).await?;
+    }
+    Ok(())
+}
+
+/**
+ * Limits how many two-factor codes can be tried for one user, or from one IP address.
+ * Each new sign in challenge counts, so re-entering the password doesn't reset the limit.
+ */
+pub fn two_factor_rate_limit_exceeded(user_id: i32, ip_address: &str) -> bool {
+    let user_rate_limit_key = format!("two_factor_{}", user_id);
+    let ip_address_rate_limit_key = format!("two_factor_{}", ip_address);
+    rate_limit_exceeded(user_rate_limit_key.as_str(), 10, 900)
+        || rate_limit_exceeded(ip_address_rate_limit_key.as_str(), 10, 900)
+}
+
+/**
+ * Accepts either a current TOTP code or one of the user's unused recovery codes.
+ * Both are consumed, so neither can be used a second time.
+ */
+pub async fn verify_two_factor_code(user: &User, code: &str) -> bool {
+    if !user.totp_enabled {
+        return false;
+    }
+    let code = code.trim();
+    if code.len() <= 7 {
+        let secret = match totp::decrypt_secret(&user.totp_secret) {
+            Ok(secret) => secret,
+            Err(error) => {
+                tracing::warn!("Failed to decrypt TOTP secret for user {}. {:?}", user.username, error);
+                return false;
+            },
+        };
+        match totp::verify_code(&secret, code) {
+            Some(step) => database::consume_user_totp_step(user.id, step).await.unwrap_or(false),
+            None => false,
+        }
+    } else {
+        database::consume_user_recovery_code(user.id, &totp::hash_recovery_code(code)).await.unwrap_or(false)
     }
 }
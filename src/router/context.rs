@@ -24,6 +24,7 @@ use serde_urlencoded;
 use urlencoding::encode;
 
 use crate::router::authn::{ update_user_session_ip, AuthSession, UserSession };
+use crate::router::session_store::touch_session_client;
 use crate::util::geolocation;
 use crate::util::image_upload;
 
@@ -301,7 +302,7 @@ where
                         user.latitude,
                         user.longitude,
                     ) {
-                        update_user_session_ip(&user.id, &ip_address);
+                        update_user_session_ip(&user.id, &ip_address).await;
                     } else {
                         let _ = auth_session.logout().await;
                     }
@@ -310,6 +311,12 @@ where
                 }
             }
         }
+        if auth_session.user.is_some() {
+            let user_agent = parts.headers.get("User-Agent")
+                .and_then(|user_agent| user_agent.to_str().ok())
+                .unwrap_or("");
+            touch_session_client(&auth_session.session, &ip_address, user_agent).await;
+        }
 
         let params: P = P::populate_from_context_extractor(&route_path_params, &route_query, &route_body);
 
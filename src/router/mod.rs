@@ -11,6 +11,7 @@ pub mod authn;
 pub mod context;
 pub mod routes;
 pub use routes::initialize;
+pub mod session_store;
 pub mod validation;
 
 use context::{ RouteContext, UserContext };
@@ -0,0 +1,142 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, CommentReportStatus, UserPermission, COMMENT_VISIBILITY_HIDDEN, COMMENT_VISIBILITY_VISIBLE };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::comment_reports::{ CommentReportsPageTemplate, CommentReportsPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct CommentReportsPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub success_action: String,
+
+    #[route_param_source(source = "query", name = "reports-page", default = "1")]
+    pub reports_page: u32,
+}
+pub type CommentReportsPageContext = BaseContext<CommentReportsPageParams>;
+
+pub async fn get_comment_reports(
+    Context { mut context }: Context<CommentReportsPageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::DeleteComment),
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(CommentReportsPageContentTemplate, &context),
+                _ => render_template!(CommentReportsPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateCommentReportsPageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(dismiss|hide|unhide|delete)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "comment", default = "-1")]
+    #[garde(skip)]
+    pub comment_id: i32,
+
+    #[route_param_source(source = "query", name = "reports-page", default = "1")]
+    #[garde(skip)]
+    pub reports_page: u32,
+}
+
+pub async fn post_comment_reports(
+    Context { context }: Context<UpdateCommentReportsPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(CommentReportsPageParams {
+        validation_report: None,
+        success_action: String::from(""),
+        reports_page: context.params.reports_page,
+    });
+
+    let user = match &context.user {
+        Some(user) if user.permissions.contains(&UserPermission::DeleteComment) => user,
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+            );
+            return send_comment_reports_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_comment_reports_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let comment_id = context.params.comment_id;
+    let (result, status) = match context.params.action.as_str() {
+        "hide" => (
+            database::set_comment_visibility(comment_id, COMMENT_VISIBILITY_HIDDEN).await,
+            CommentReportStatus::Hidden,
+        ),
+        "unhide" => (
+            database::set_comment_visibility(comment_id, COMMENT_VISIBILITY_VISIBLE).await,
+            CommentReportStatus::Dismissed,
+        ),
+        "delete" => (
+            database::delete_comment(comment_id, &user.username).await,
+            CommentReportStatus::Deleted,
+        ),
+        _ => (Ok(()), CommentReportStatus::Dismissed),
+    };
+
+    let result = match result {
+        Ok(_) => database::resolve_comment_reports(comment_id, &status, &user.username).await,
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to resolve comment reports. {:?}", &user.username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_comment_reports_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.success_action = context.params.action.clone();
+    send_comment_reports_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_comment_reports_page_response(status: StatusCode, context: CommentReportsPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(CommentReportsPageContentTemplate, &context),
+                    _ => render_template!(CommentReportsPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
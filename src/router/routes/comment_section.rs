@@ -7,13 +7,18 @@ use garde::{ Validate, Report };
 use macros::{ RouteParamsContext, render_template };
 use urlencoding::decode;
 
-use crate::database::{ self, Comment, CommentSectionName };
-use crate::router::{ html_to_response };
+use crate::database::{ self, Comment, CommentReport, CommentReportReason, CommentSectionName, UserPermission };
+use crate::router::{ get_hx_target, html_to_response };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
 use crate::router::validation::create_simple_report;
+use crate::ui_pages::comment_edit::{ CommentEditPageTemplate };
+use crate::ui_pages::comment_report::{ CommentReportPageTemplate };
 use crate::ui_pages::comment_section::{ CommentSectionPageTemplate };
+use crate::ui_primitives::comment_likes::CommentLikesTemplate;
 use crate::util::captcha::validate_captcha;
+use crate::util::error::RenderingError;
 use crate::util::rate_limit::rate_limit_exceeded;
+use crate::util::user::is_guest_user;
 
 #[derive(Default, Debug, RouteParamsContext)]
 pub struct CommentSectionPageParams {
@@ -109,7 +114,8 @@ pub async fn post_comment_section(
         return send_comment_section_page_response(StatusCode::BAD_REQUEST, page_context).await;
     }
 
-    let validation_result = validate_comment_section_form(&context.params).await;
+    let commenter_id = context.user.as_ref().map(|user| user.id);
+    let validation_result = validate_comment_section_form(&context.params, &section, commenter_id).await;
     if let Err(report) = validation_result {
         page_context.params.validation_report = Some(report);
         return send_comment_section_page_response(StatusCode::BAD_REQUEST, page_context).await;
@@ -135,7 +141,7 @@ pub async fn post_comment_section(
         ..Comment::default()
     };
 
-    if let Err(error) = database::create_comment(comment).await {
+    if let Err(error) = database::create_comment(comment.clone()).await {
         tracing::warn!("Database call failed when user {} tried to post a comment. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -147,15 +153,441 @@ pub async fn post_comment_section(
     if let Some(redirect_to_attr) = context.route_query.get("redirect-to") {
         redirect_to = decode(redirect_to_attr.as_str()).expect("UTF-8").to_string();
     }
+
+    if let Err(error) = database::notify_comment_created(&comment, &create_site_relative_link(&redirect_to)).await {
+        tracing::warn!("Failed to create notifications for a comment by user {}. {:?}", username, error);
+    }
+
     return Redirect::to(&redirect_to).into_response()
 }
 
-async fn validate_comment_section_form(form: &SubmitCommentSectionPageParams) -> Result<(), Report> {
-    if form.reply_id > -1 {
-        if let Err(_) = database::get_comment_by_id(form.reply_id).await {
-            return Err(
-                create_simple_report(String::from("reply_comment_missing"), String::from("Missing comment."))
+/**
+ * The redirect is the page the comment was posted on. It comes from the query string,
+ * so only site-relative links are stored in notifications or redirected to.
+ */
+fn create_site_relative_link(redirect_to: &str) -> String {
+    if !redirect_to.starts_with('/') || redirect_to.starts_with("//") || redirect_to.contains('\\') || redirect_to.len() > 200 {
+        return String::from("/");
+    }
+    redirect_to.to_string()
+}
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct CommentLikeParams {
+    #[route_param_source(source = "path", name = "section", default = "")]
+    pub section: String,
+
+    #[route_param_source(source = "path", name = "section_tag_id", default = "-1")]
+    pub section_tag_id: i32,
+
+    #[route_param_source(source = "path", name = "reply_id", default = "-1")]
+    pub comment_id: i32,
+
+    #[route_param_source(source = "form", name = "action", default = "")]
+    pub action: String,
+}
+
+pub async fn post_comment_like(
+    Context { context }: Context<CommentLikeParams>,
+) -> Response {
+    let user = match &context.user {
+        Some(user) if user.permissions.contains(&UserPermission::CreateOwnComment) => user,
+        _ => return StatusCode::FORBIDDEN.into_response(),
+    };
+
+    let section = match context.params.section.parse::<CommentSectionName>() {
+        Ok(section) => section,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let comment = match database::get_comment_by_id(context.params.comment_id).await {
+        Ok(comment) if !comment.is_hidden() => comment,
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    if comment.username == user.username {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let rate_limit_key = format!("comment_like_{}", user.id);
+    if rate_limit_exceeded(rate_limit_key.as_str(), 30, 60) {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let result = match context.params.action.as_str() {
+        "like" => database::like_comment(comment.id, user.id).await,
+        "unlike" => database::unlike_comment(comment.id, user.id).await,
+        _ => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to like a comment. {:?}", &user.username, error);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    let redirect_url_encoded = context.route_query.get("redirect-to").cloned().unwrap_or_default();
+
+    if !get_hx_target(&context.route_headers).starts_with("comment-likes-") {
+        let redirect_to = decode(&redirect_url_encoded).map(|decoded| decoded.to_string()).unwrap_or_default();
+        return Redirect::to(&create_site_relative_link(&redirect_to)).into_response();
+    }
+
+    let likes = database::get_comment_by_id(comment.id).await
+        .map(|updated_comment| updated_comment.likes)
+        .unwrap_or(comment.likes)
+        .unwrap_or(0);
+    let viewer_has_liked = database::has_user_liked_comment(comment.id, user.id).await.unwrap_or(false);
+
+    let html = CommentLikesTemplate {
+        section: &section,
+        section_tag_id: context.params.section_tag_id,
+        comment_id: comment.id,
+        likes,
+        viewer_has_liked,
+        can_like: true,
+        redirect_url_encoded: &redirect_url_encoded,
+    }.render().map_err(RenderingError::from);
+
+    html_to_response(
+        &context,
+        |_, _| async move { html }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct CommentEditPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "path", name = "section", default = "")]
+    pub section: String,
+
+    #[route_param_source(source = "path", name = "section_tag_id", default = "-1")]
+    pub section_tag_id: i32,
+
+    #[route_param_source(source = "path", name = "reply_id", default = "-1")]
+    pub comment_id: i32,
+
+    #[route_param_source(default = "")]
+    pub comment: String,
+}
+pub type CommentEditPageContext = BaseContext<CommentEditPageParams>;
+
+pub async fn get_comment_edit(
+    Context { context }: Context<CommentEditPageParams>,
+) -> Response {
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                _ => render_template!(CommentEditPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct SubmitCommentEditPageParams {
+    #[route_param_source(source = "path", name = "section", default = "")]
+    #[garde(skip)]
+    pub section: String,
+
+    #[route_param_source(source = "path", name = "section_tag_id", default = "-1")]
+    #[garde(skip)]
+    pub section_tag_id: i32,
+
+    #[route_param_source(source = "path", name = "reply_id", default = "-1")]
+    #[garde(skip)]
+    pub comment_id: i32,
+
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(skip)]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "comment", default = "")]
+    #[garde(
+        length(min = 1, max = 5000)
+    )]
+    pub comment: String,
+}
+
+pub async fn post_comment_edit(
+    Context { context }: Context<SubmitCommentEditPageParams>,
+) -> Response {
+    let mut page_context = context.clone_with_params(CommentEditPageParams {
+        validation_report: None,
+        section: context.params.section.clone(),
+        section_tag_id: context.params.section_tag_id,
+        comment_id: context.params.comment_id,
+        comment: context.params.comment.clone(),
+    });
+
+    let user = match &context.user {
+        Some(user) => user,
+        None => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+            );
+            return send_comment_edit_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    let comment = match database::get_comment_by_id(context.params.comment_id).await {
+        Ok(comment) => comment,
+        Err(_) => {
+            return send_comment_edit_page_response(StatusCode::NOT_FOUND, page_context).await;
+        },
+    };
+
+    let is_own_comment = comment.username == user.username;
+    let can_edit = is_own_comment && user.permissions.contains(&UserPermission::DeleteOwnComment);
+    let can_delete = can_edit || user.permissions.contains(&UserPermission::DeleteComment);
+
+    let has_permissions = match context.params.action.as_str() {
+        "edit" => can_edit,
+        "delete" => can_delete,
+        _ => false,
+    };
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_comment_edit_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    let ip_address_rate_limit_key = format!("comment_section_{}", &context.ip_address);
+    if rate_limit_exceeded(ip_address_rate_limit_key.as_str(), 10, 60) {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("rate_limit"), String::from("Rate limit exceeded."))
+        );
+        return send_comment_edit_page_response(StatusCode::TOO_MANY_REQUESTS, page_context).await;
+    }
+
+    let result = if context.params.action == "edit" {
+        if let Err(report) = context.params.validate() {
+            page_context.params.validation_report = Some(report);
+            return send_comment_edit_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        }
+        database::update_comment(comment.id, &user.username, &context.params.comment).await
+    } else {
+        database::delete_comment(comment.id, &user.username).await
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to {} a comment. {:?}", &user.username, &context.params.action, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_comment_edit_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    let mut redirect_to: String = String::from("/");
+    if let Some(redirect_to_attr) = context.route_query.get("redirect-to") {
+        redirect_to = decode(redirect_to_attr.as_str()).map(|decoded| decoded.to_string()).unwrap_or_default();
+    }
+    return Redirect::to(&create_site_relative_link(&redirect_to)).into_response()
+}
+
+pub async fn send_comment_edit_page_response(status: StatusCode, context: CommentEditPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    _ => render_template!(CommentEditPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct CommentReportPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "path", name = "section", default = "")]
+    pub section: String,
+
+    #[route_param_source(source = "path", name = "section_tag_id", default = "-1")]
+    pub section_tag_id: i32,
+
+    #[route_param_source(source = "path", name = "reply_id", default = "-1")]
+    pub comment_id: i32,
+
+    #[route_param_source(default = "")]
+    pub reason: String,
+
+    #[route_param_source(default = "")]
+    pub details: String,
+
+    #[route_param_source(source = "none")]
+    pub is_submitted: bool,
+}
+pub type CommentReportPageContext = BaseContext<CommentReportPageParams>;
+
+pub async fn get_comment_report(
+    Context { context }: Context<CommentReportPageParams>,
+) -> Response {
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                _ => render_template!(CommentReportPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct SubmitCommentReportPageParams {
+    #[route_param_source(source = "path", name = "section", default = "")]
+    #[garde(skip)]
+    pub section: String,
+
+    #[route_param_source(source = "path", name = "section_tag_id", default = "-1")]
+    #[garde(skip)]
+    pub section_tag_id: i32,
+
+    #[route_param_source(source = "path", name = "reply_id", default = "-1")]
+    #[garde(skip)]
+    pub comment_id: i32,
+
+    #[route_param_source(source = "form", name = "reason", default = "")]
+    #[garde(
+        custom(is_valid_report_reason)
+    )]
+    pub reason: String,
+
+    #[route_param_source(source = "form", name = "details", default = "")]
+    #[garde(
+        length(max = 500)
+    )]
+    pub details: String,
+}
+
+pub async fn post_comment_report(
+    Context { context }: Context<SubmitCommentReportPageParams>,
+) -> Response {
+    let mut page_context = context.clone_with_params(CommentReportPageParams {
+        validation_report: None,
+        section: context.params.section.clone(),
+        section_tag_id: context.params.section_tag_id,
+        comment_id: context.params.comment_id,
+        reason: context.params.reason.clone(),
+        details: context.params.details.clone(),
+        is_submitted: false,
+    });
+
+    let user = match &context.user {
+        Some(user) if user.permissions.contains(&UserPermission::CreateOwnComment) => user,
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
             );
+            return send_comment_report_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_comment_report_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let comment = match database::get_comment_by_id(context.params.comment_id).await {
+        Ok(comment) if !comment.is_hidden() => comment,
+        _ => {
+            return send_comment_report_page_response(StatusCode::NOT_FOUND, page_context).await;
+        },
+    };
+
+    let rate_limit_key = format!("comment_report_{}", user.id);
+    if rate_limit_exceeded(rate_limit_key.as_str(), 10, 600) {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("rate_limit"), String::from("Rate limit exceeded."))
+        );
+        return send_comment_report_page_response(StatusCode::TOO_MANY_REQUESTS, page_context).await;
+    }
+
+    let report = CommentReport {
+        comment_id: comment.id,
+        username: user.username.clone(),
+        ip_address: context.ip_address.clone(),
+        reason: context.params.reason.parse::<CommentReportReason>().unwrap_or_default(),
+        details: context.params.details.clone(),
+        ..CommentReport::default()
+    };
+
+    if let Err(error) = database::create_comment_report(&report).await {
+        tracing::warn!("Database call failed when user {} tried to report a comment. {:?}", &user.username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_comment_report_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.is_submitted = true;
+    send_comment_report_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_comment_report_page_response(status: StatusCode, context: CommentReportPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    _ => render_template!(CommentReportPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
+
+fn is_valid_report_reason(value: &str, _: &()) -> garde::Result {
+    match value.parse::<CommentReportReason>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(garde::Error::new("Unknown reason.")),
+    }
+}
+
+async fn validate_comment_section_form(
+    form: &SubmitCommentSectionPageParams,
+    section: &CommentSectionName,
+    commenter_id: Option<i32>,
+) -> Result<(), Report> {
+    // Members who blocked the commenter, either by owning the profile or writing the comment being replied to.
+    let mut blocker_ids: Vec<i32> = Vec::new();
+    if form.reply_id > -1 {
+        match database::get_comment_by_id(form.reply_id).await {
+            Ok(reply_to_comment) if !reply_to_comment.is_hidden() => {
+                if !is_guest_user(&reply_to_comment.username) {
+                    if let Ok(reply_to_user) = database::get_user_by_username(&reply_to_comment.username).await {
+                        blocker_ids.push(reply_to_user.id);
+                    }
+                }
+            },
+            _ => {
+                return Err(
+                    create_simple_report(String::from("reply_comment_missing"), String::from("Missing comment."))
+                );
+            },
+        }
+    }
+    if let CommentSectionName::Members = section {
+        blocker_ids.push(form.section_tag_id);
+    }
+    if let Some(commenter_id) = commenter_id {
+        for blocker_id in blocker_ids {
+            let is_blocked = database::get_user_blocklist(blocker_id).await
+                .map(|blocklist| blocklist.contains(&commenter_id))
+                .unwrap_or(false);
+            if is_blocked {
+                return Err(
+                    create_simple_report(String::from("blocked"), String::from("Blocked by member."))
+                );
+            }
         }
     }
     if let Err(report) = form.validate() {
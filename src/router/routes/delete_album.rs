@@ -92,7 +92,7 @@ pub async fn delete_album(
     let album_id = validation_result.unwrap();
     let username = context.user.unwrap().username;
 
-    if let Err(error) = database::mark_album_for_deletion(album_id).await {
+    if let Err(error) = database::mark_album_for_deletion(album_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete album. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
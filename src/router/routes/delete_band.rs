@@ -84,7 +84,7 @@ pub async fn delete_band(
     let band_id = validation_result.unwrap();
     let username = context.user.unwrap().username;
 
-    if let Err(error) = database::mark_band_for_deletion(band_id).await {
+    if let Err(error) = database::mark_band_for_deletion(band_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete band. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -104,10 +104,16 @@ async fn validate_band_delete_form(form: &DeleteConfirmBandPageParams) -> Result
             create_simple_report(String::from("band_missing"), String::from("The specified band does not exist."))
         );
     }
+    let band_id = band_id.unwrap();
+    if let Err(_) = database::band_by_id_is_empty(band_id).await {
+        return Err(
+            create_simple_report(String::from("albums_exist"), String::from("Deletion cannot be carried out when albums exist."))
+        );
+    }
     if let Err(report) = form.validate() {
         return Err(report);
     }
-    Ok(band_id.unwrap())
+    Ok(band_id)
 }
 
 async fn validate_band_exists(band_slug: &str) -> Result<i32, Box<dyn Error>> {
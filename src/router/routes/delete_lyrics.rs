@@ -114,7 +114,7 @@ pub async fn delete_lyrics(
     let lyrics_id = validation_result.unwrap();
     let username = context.user.unwrap().username;
 
-    if let Err(error) = database::mark_lyrics_for_deletion(lyrics_id).await {
+    if let Err(error) = database::mark_lyrics_for_deletion(lyrics_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete lyrics. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -107,7 +107,7 @@ pub async fn delete_photo(
         return send_delete_photo_page_response(StatusCode::FORBIDDEN, page_context).await;
     }
 
-    if let Err(error) = database::mark_photo_for_deletion(photo_id).await {
+    if let Err(error) = database::mark_photo_for_deletion(photo_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete a photo. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
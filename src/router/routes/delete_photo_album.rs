@@ -99,7 +99,7 @@ pub async fn delete_photo_album(
         return send_delete_photo_album_page_response(StatusCode::FORBIDDEN, page_context).await;
     }
 
-    if let Err(error) = database::mark_photo_album_for_deletion(photo_album_id).await {
+    if let Err(error) = database::mark_photo_album_for_deletion(photo_album_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete a photo album. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -115,7 +115,7 @@ pub async fn delete_tabs(
     let tab_id = validation_result.unwrap();
     let username = context.user.unwrap().username;
 
-    if let Err(error) = database::mark_tab_for_deletion(tab_id).await {
+    if let Err(error) = database::mark_tab_for_deletion(tab_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete tabs. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
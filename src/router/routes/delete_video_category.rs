@@ -99,7 +99,7 @@ pub async fn delete_video_category(
         return send_delete_video_category_page_response(StatusCode::FORBIDDEN, page_context).await;
     }
 
-    if let Err(error) = database::mark_video_category_for_deletion(video_category_id).await {
+    if let Err(error) = database::mark_video_category_for_deletion(video_category_id, &username).await {
         tracing::warn!("Database call failed when user {} tried to delete a video category. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -0,0 +1,142 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, QueuedDeletionStatus, UserPermission };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::deletion_queue::{ DeletionQueuePageTemplate, DeletionQueuePageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct DeletionQueuePageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub success_action: String,
+
+    #[route_param_source(source = "query", name = "deletions-page", default = "1")]
+    pub deletions_page: u32,
+}
+pub type DeletionQueuePageContext = BaseContext<DeletionQueuePageParams>;
+
+pub async fn get_deletion_queue(
+    Context { mut context }: Context<DeletionQueuePageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => {
+            user.permissions.contains(&UserPermission::ApproveQueuedDeletion)
+            || user.permissions.contains(&UserPermission::UndoQueuedDeletion)
+        },
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(DeletionQueuePageContentTemplate, &context),
+                _ => render_template!(DeletionQueuePageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateDeletionQueuePageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(approve|undo)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "deletion", default = "-1")]
+    #[garde(skip)]
+    pub deletion_id: i32,
+
+    #[route_param_source(source = "query", name = "deletions-page", default = "1")]
+    #[garde(skip)]
+    pub deletions_page: u32,
+}
+
+pub async fn post_deletion_queue(
+    Context { context }: Context<UpdateDeletionQueuePageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(DeletionQueuePageParams {
+        validation_report: None,
+        success_action: String::from(""),
+        deletions_page: context.params.deletions_page,
+    });
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_deletion_queue_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let required_permission = match context.params.action.as_str() {
+        "approve" => UserPermission::ApproveQueuedDeletion,
+        _ => UserPermission::UndoQueuedDeletion,
+    };
+    let user = match &context.user {
+        Some(user) if user.permissions.contains(&required_permission) => user,
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+            );
+            return send_deletion_queue_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    let queued_deletion = match database::get_queued_deletion_by_id(context.params.deletion_id).await {
+        Ok(queued_deletion) if queued_deletion.status == QueuedDeletionStatus::Pending => queued_deletion,
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("deletion"), String::from("The deletion is no longer queued."))
+            );
+            return send_deletion_queue_page_response(StatusCode::NOT_FOUND, page_context).await;
+        },
+    };
+
+    let result = match context.params.action.as_str() {
+        "approve" => database::approve_queued_deletion(&queued_deletion, &user.username).await,
+        _ => database::undo_queued_deletion(&queued_deletion, &user.username).await,
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to review a queued deletion. {:?}", &user.username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_deletion_queue_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.success_action = context.params.action.clone();
+    send_deletion_queue_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_deletion_queue_page_response(status: StatusCode, context: DeletionQueuePageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(DeletionQueuePageContentTemplate, &context),
+                    _ => render_template!(DeletionQueuePageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
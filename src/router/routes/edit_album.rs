@@ -9,7 +9,7 @@ use chrono::NaiveDate;
 use garde::{ Validate, Report };
 use macros::{ RouteParamsContext, render_template };
 
-use crate::database::{ self, Album, AlbumType, UserPermission, UserPreference };
+use crate::database::{ self, Album, AlbumTrack, AlbumType, UserPermission, UserPreference };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
 use crate::ui_pages::edit_album::{ EditAlbumPageTemplate, EditAlbumPageContentTemplate };
 use crate::util::format;
@@ -17,6 +17,9 @@ use crate::util::image_upload;
 use crate::router::{ html_to_response };
 use crate::router::validation::create_simple_report;
 
+pub const MAX_ALBUM_TRACKS: usize = 300;
+pub const MAX_ALBUM_DISCS: i32 = 20;
+
 #[derive(Default, Debug, RouteParamsContext)]
 pub struct EditAlbumPageParams {
     #[route_param_source(source = "none")]
@@ -49,6 +52,9 @@ pub struct EditAlbumPageParams {
     #[route_param_source(default = "")]
     pub songs: String,
 
+    #[route_param_source(default = "")]
+    pub discs: String,
+
     #[route_param_source(default = "")]
     pub temporary_cover_picture_filename: String,
 }
@@ -159,6 +165,7 @@ pub async fn post_create_album(
         release_month: context.params.release_month.clone(),
         release_day_of_month: context.params.release_day_of_month.clone(),
         songs: String::from(""),
+        discs: String::from(""),
         temporary_cover_picture_filename: temporary_cover_picture_filename.clone(),
     });
 
@@ -322,6 +329,10 @@ pub struct UpdateAlbumPageParams {
     #[garde(skip)]
     pub songs: String,
 
+    #[route_param_source(source = "form", name = "discs", default = "")]
+    #[garde(skip)]
+    pub discs: String,
+
     #[route_param_source(source = "form", name = "cover-image", default = "")]
     #[garde(skip)]
     pub cover_picture_upload: String,
@@ -352,6 +363,7 @@ pub async fn put_update_album(
         release_month: context.params.release_month.clone(),
         release_day_of_month: context.params.release_day_of_month.clone(),
         songs: context.params.songs.clone(),
+        discs: context.params.discs.clone(),
         temporary_cover_picture_filename: temporary_cover_picture_filename.clone(),
     });
 
@@ -387,11 +399,14 @@ pub async fn put_update_album(
     let user = &context.user.as_ref().unwrap();
     let username = &user.username;
 
-    let mut songs: Vec<String> = context.params.songs
-        .split(',')
-        .map(|s| s.replace("%2C", ","))
-        .collect::<Vec<_>>();
-    songs.resize(40, String::from(""));
+    let track_names = parse_track_names(&context.params.songs, &context.params.discs);
+    if track_names.len() > MAX_ALBUM_TRACKS {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("songs"), String::from("Too many tracks."))
+        );
+        return send_edit_album_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+    let songs: Vec<String> = track_names.iter().map(|(_, name)| name.clone()).collect();
     let song_ids: Vec<Option<i32>>;
     match database::create_songs_by_names(&songs, existing_album.id, existing_album.band).await {
         Ok(ids) => {
@@ -405,7 +420,7 @@ pub async fn put_update_album(
             return send_edit_album_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
         }
     }
-    existing_album = existing_album.populate_song_ids(song_ids);
+    existing_album.tracks = number_album_tracks(existing_album.id, &track_names, &song_ids);
 
     existing_album.album_slug = album_slug.clone();
     existing_album.album_name = context.params.album_name.clone();
@@ -450,6 +465,48 @@ pub async fn put_update_album(
     ).into_response()
 }
 
+/**
+ * Pairs each submitted song name with its disc number, dropping empty rows.
+ * Rows are stable sorted by disc so tracks can be listed in any order in the form.
+ */
+fn parse_track_names(songs: &str, discs: &str) -> Vec<(i32, String)> {
+    let discs = discs.split(',').collect::<Vec<&str>>();
+    let mut track_names = songs
+        .split(',')
+        .map(|s| s.replace("%2C", ","))
+        .enumerate()
+        .filter(|(_, name)| !name.trim().is_empty())
+        .map(|(index, name)| {
+            let disc_number = discs.get(index)
+                .and_then(|disc| disc.trim().parse::<i32>().ok())
+                .unwrap_or(1)
+                .clamp(1, MAX_ALBUM_DISCS);
+            (disc_number, name.trim().to_string())
+        })
+        .collect::<Vec<_>>();
+    track_names.sort_by_key(|(disc_number, _)| *disc_number);
+    track_names
+}
+
+fn number_album_tracks(album_id: i32, track_names: &Vec<(i32, String)>, song_ids: &Vec<Option<i32>>) -> Vec<AlbumTrack> {
+    let mut tracks: Vec<AlbumTrack> = Vec::with_capacity(track_names.len());
+    for ((disc_number, _), song_id) in track_names.iter().zip(song_ids.iter()) {
+        if let Some(song_id) = song_id {
+            let track_number = tracks
+                .iter()
+                .filter(|track| track.disc_number == *disc_number)
+                .count() as i32 + 1;
+            tracks.push(AlbumTrack {
+                album_id,
+                song_id: *song_id,
+                disc_number: *disc_number,
+                track_number,
+            });
+        }
+    }
+    tracks
+}
+
 async fn validate_album_update_form(form: &UpdateAlbumPageParams) -> Result<Album, Report> {
     let validation_result = validate_album_exists(&form.band, &form.album).await;
     if let Err(_) = validation_result {
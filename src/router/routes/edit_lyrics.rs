@@ -1,5 +1,6 @@
 use std::error::Error;
 use std::io;
+use std::str::FromStr;
 
 use axum::{
     http::{ StatusCode },
@@ -9,11 +10,20 @@ use askama::Template;
 use garde::{ Validate, Report };
 use macros::{ RouteParamsContext, render_template };
 
-use crate::database::{ self, Lyrics, UserPermission, UserPreference };
+use crate::database::{ self, Lyrics, LyricsLanguage, UserPermission, UserPreference };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
-use crate::ui_pages::edit_lyrics::{ EditLyricsPageTemplate, EditLyricsPageContentTemplate, EditLyricsSelectBandAlbumSongTemplate };
+use crate::ui_modules::lyrics_preview::{ LyricsPreviewTemplate, LyricsPreviewParams };
+use crate::ui_pages::edit_lyrics::{
+    EditLyricsPageTemplate, EditLyricsPageContentTemplate, EditLyricsRomajiFieldTemplate, EditLyricsSelectBandAlbumSongTemplate,
+    EditLyricsTimestampsFieldTemplate, EditLyricsTranslationFieldTemplate,
+};
 use crate::router::{ html_to_response };
 use crate::router::validation::create_simple_report;
+use crate::util::error::RenderingError;
+use crate::util::furigana::find_furigana_error;
+use crate::util::lrc::{ find_line_timestamps_error, format_line_timestamps, parse_line_timestamps };
+use crate::util::lyrics::find_lyrics_misalignment;
+use crate::util::video::is_synchronizable_video_url;
 
 #[derive(Default, Debug, RouteParamsContext)]
 pub struct EditLyricsPageParams {
@@ -35,8 +45,17 @@ pub struct EditLyricsPageParams {
     #[route_param_source(default = "")]
     pub romaji: String,
 
+    #[route_param_source(default = "en")]
+    pub language: String,
+
+    #[route_param_source(default = "")]
+    pub translation: String,
+
     #[route_param_source(default = "")]
-    pub english: String,
+    pub timestamps: String,
+
+    #[route_param_source(name = "video-url", default = "")]
+    pub video_url: String,
 
     #[route_param_source(default = "")]
     pub comment: String,
@@ -65,6 +84,7 @@ pub async fn get_edit_lyrics(
             match hx_target.as_str() {
                 "main-article" => render_template!(EditLyricsPageContentTemplate, &context),
                 "edit-lyrics-select-song-album-song-section" => render_template!(EditLyricsSelectBandAlbumSongTemplate, &context),
+                "edit-lyrics-translation-field" => render_template!(EditLyricsTranslationFieldTemplate, &context),
                 _ => render_template!(EditLyricsPageTemplate, &context),
             }
         }
@@ -97,17 +117,33 @@ pub struct CreateLyricsPageParams {
     )]
     pub romaji: String,
 
-    #[route_param_source(source = "form", name = "english", default = "")]
+    #[route_param_source(source = "form", name = "language", default = "en")]
+    #[garde(skip)]
+    pub language: String,
+
+    #[route_param_source(source = "form", name = "translation", default = "")]
     #[garde(
         length(min = 1, max = 4000),
     )]
-    pub english: String,
+    pub translation: String,
 
     #[route_param_source(source = "form", name = "comment", default = "")]
     #[garde(
         length(max = 2000),
     )]
     pub comment: String,
+
+    #[route_param_source(source = "form", name = "timestamps", default = "")]
+    #[garde(
+        length(max = 8000),
+    )]
+    pub timestamps: String,
+
+    #[route_param_source(source = "form", name = "video-url", default = "")]
+    #[garde(
+        length(max = 1000),
+    )]
+    pub video_url: String,
 }
 
 #[axum::debug_handler]
@@ -121,8 +157,11 @@ pub async fn post_create_lyrics(
         song: context.params.song.clone(),
         kanji: context.params.kanji.clone(),
         romaji: context.params.romaji.clone(),
-        english: context.params.english.clone(),
+        language: context.params.language.clone(),
+        translation: context.params.translation.clone(),
         comment: context.params.comment.clone(),
+        timestamps: context.params.timestamps.clone(),
+        video_url: context.params.video_url.clone(),
     });
 
     let has_permissions = match &context.user {
@@ -153,15 +192,17 @@ pub async fn post_create_lyrics(
     let user = context.user.unwrap();
     let username = user.username;
 
-    let lyrics = Lyrics {
+    let mut lyrics = Lyrics {
         username: username.clone(),
         song: song_id,
         kanji_content: context.params.kanji,
         romaji_content: context.params.romaji,
-        english_content: context.params.english,
         comment: context.params.comment,
+        line_timestamps: normalize_line_timestamps(&context.params.timestamps),
+        video_url: context.params.video_url.trim().to_string(),
         ..Lyrics::default()
     };
+    lyrics.set_translation(&LyricsLanguage::from_code(&context.params.language), context.params.translation);
 
     if let Err(error) = database::create_lyrics(lyrics).await {
         tracing::warn!("Database call failed when user {} tried to create lyrics. {:?}", &username, error);
@@ -200,14 +241,11 @@ async fn validate_lyrics_create_form(form: &CreateLyricsPageParams) -> Result<(i
             create_simple_report(String::from("song_missing"), String::from("The specified song does not exist."))
         );
     }
-    let kanji_lines = form.kanji.trim().lines().count();
-    let romaji_lines = form.romaji.trim().lines().count();
-    let english_lines = form.english.trim().lines().count();
-    if kanji_lines != romaji_lines || kanji_lines != english_lines {
-        return Err(
-            create_simple_report(String::from("lines_mismatch"), String::from("Lyrics must have equal lines."))
-        );
-    }
+    validate_language(&form.language)?;
+    validate_lyrics_alignment(&form.kanji, &form.romaji, &form.translation, &form.language)?;
+    validate_furigana(&form.kanji)?;
+    validate_line_timestamps(&form.timestamps, &form.kanji)?;
+    validate_video_url(&form.video_url)?;
     if let Err(report) = form.validate() {
         return Err(report);
     }
@@ -245,17 +283,33 @@ pub struct UpdateLyricsPageParams {
     )]
     pub romaji: String,
 
-    #[route_param_source(source = "form", name = "english", default = "")]
+    #[route_param_source(source = "form", name = "language", default = "en")]
+    #[garde(skip)]
+    pub language: String,
+
+    #[route_param_source(source = "form", name = "translation", default = "")]
     #[garde(
         length(min = 1, max = 4000),
     )]
-    pub english: String,
+    pub translation: String,
 
     #[route_param_source(source = "form", name = "comment", default = "")]
     #[garde(
         length(max = 2000),
     )]
     pub comment: String,
+
+    #[route_param_source(source = "form", name = "timestamps", default = "")]
+    #[garde(
+        length(max = 8000),
+    )]
+    pub timestamps: String,
+
+    #[route_param_source(source = "form", name = "video-url", default = "")]
+    #[garde(
+        length(max = 1000),
+    )]
+    pub video_url: String,
 }
 
 pub async fn put_update_lyrics(
@@ -268,8 +322,11 @@ pub async fn put_update_lyrics(
         song: context.params.song.clone(),
         kanji: context.params.kanji.clone(),
         romaji: context.params.romaji.clone(),
-        english: context.params.english.clone(),
+        language: context.params.language.clone(),
+        translation: context.params.translation.clone(),
         comment: context.params.comment.clone(),
+        timestamps: context.params.timestamps.clone(),
+        video_url: context.params.video_url.clone(),
     });
 
     let has_permissions = match &context.user {
@@ -308,10 +365,12 @@ pub async fn put_update_lyrics(
     let mut existing_lyrics = database::get_lyrics_by_username_and_song_id(username, song_id).await.unwrap();
     existing_lyrics.kanji_content = context.params.kanji.clone();
     existing_lyrics.romaji_content = context.params.romaji.clone();
-    existing_lyrics.english_content = context.params.english.clone();
+    existing_lyrics.set_translation(&LyricsLanguage::from_code(&context.params.language), context.params.translation.clone());
     existing_lyrics.comment = context.params.comment.clone();
+    existing_lyrics.line_timestamps = normalize_line_timestamps(&context.params.timestamps);
+    existing_lyrics.video_url = context.params.video_url.trim().to_string();
 
-    if let Err(error) = database::update_lyrics(existing_lyrics).await {
+    if let Err(error) = database::update_lyrics(existing_lyrics, &user.username).await {
         tracing::warn!("Database call failed when user {} tried to update lyrics. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -331,14 +390,11 @@ async fn validate_lyrics_update_form(form: &UpdateLyricsPageParams) -> Result<i3
             create_simple_report(String::from("song_missing"), String::from("The specified song does not exist."))
         );
     }
-    let kanji_lines = form.kanji.trim().lines().count();
-    let romaji_lines = form.romaji.trim().lines().count();
-    let english_lines = form.english.trim().lines().count();
-    if kanji_lines != romaji_lines || kanji_lines != english_lines {
-        return Err(
-            create_simple_report(String::from("lines_mismatch"), String::from("Lyrics must have equal lines."))
-        );
-    }
+    validate_language(&form.language)?;
+    validate_lyrics_alignment(&form.kanji, &form.romaji, &form.translation, &form.language)?;
+    validate_furigana(&form.kanji)?;
+    validate_line_timestamps(&form.timestamps, &form.kanji)?;
+    validate_video_url(&form.video_url)?;
     if let Err(report) = form.validate() {
         return Err(report);
     }
@@ -346,6 +402,60 @@ async fn validate_lyrics_update_form(form: &UpdateLyricsPageParams) -> Result<i3
     Ok(song_id)
 }
 
+fn validate_language(language_code: &str) -> Result<(), Report> {
+    match LyricsLanguage::from_str(language_code) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(
+            create_simple_report(String::from("language"), String::from("The language is not supported."))
+        ),
+    }
+}
+
+fn validate_lyrics_alignment(kanji: &str, romaji: &str, translation: &str, language_code: &str) -> Result<(), Report> {
+    let language = LyricsLanguage::from_code(language_code);
+    match find_lyrics_misalignment(kanji, romaji, translation, language.as_display()) {
+        Some(misalignment) => Err(
+            create_simple_report(String::from("lines_mismatch"), misalignment.message)
+        ),
+        None => Ok(()),
+    }
+}
+
+fn validate_furigana(kanji: &str) -> Result<(), Report> {
+    match find_furigana_error(kanji) {
+        Some(furigana_error) => Err(
+            create_simple_report(String::from("furigana"), furigana_error.message)
+        ),
+        None => Ok(()),
+    }
+}
+
+fn validate_line_timestamps(timestamps: &str, kanji: &str) -> Result<(), Report> {
+    match find_line_timestamps_error(timestamps, kanji) {
+        Some(message) => Err(
+            create_simple_report(String::from("timestamps"), message)
+        ),
+        None => Ok(()),
+    }
+}
+
+fn validate_video_url(video_url: &str) -> Result<(), Report> {
+    let video_url = video_url.trim();
+    if video_url.is_empty() || is_synchronizable_video_url(video_url) {
+        return Ok(());
+    }
+    Err(
+        create_simple_report(String::from("video_url"), String::from("Only YouTube videos can be followed along with."))
+    )
+}
+
+/**
+ * Timestamps are saved as mm:ss.xx no matter how precise they were written.
+ */
+fn normalize_line_timestamps(timestamps: &str) -> String {
+    format_line_timestamps(&parse_line_timestamps(timestamps))
+}
+
 async fn validate_song_exists(band_slug: &str, album_slug: &str, song_slug: &str) -> Result<(i32, String), Box<dyn Error>> {
     let band = database::get_band_by_slug(band_slug).await?;
     let album = database::get_album_by_slug_and_band_id(album_slug, band.id).await?;
@@ -358,6 +468,137 @@ async fn validate_song_exists(band_slug: &str, album_slug: &str, song_slug: &str
     Ok((song.id, song.song_name.to_string()))
 }
 
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct PreviewLyricsPageParams {
+    #[route_param_source(source = "form", name = "kanji", default = "")]
+    #[garde(
+        length(max = 32000),
+    )]
+    pub kanji: String,
+
+    #[route_param_source(source = "form", name = "romaji", default = "")]
+    #[garde(
+        length(max = 4000),
+    )]
+    pub romaji: String,
+
+    #[route_param_source(source = "form", name = "language", default = "en")]
+    #[garde(skip)]
+    pub language: String,
+
+    #[route_param_source(source = "form", name = "translation", default = "")]
+    #[garde(
+        length(max = 4000),
+    )]
+    pub translation: String,
+}
+
+/**
+ * Renders the combined view of the lyrics in the editor before they're saved.
+ */
+pub async fn post_preview_lyrics(
+    Context { context }: Context<PreviewLyricsPageParams>,
+) -> Response {
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::CreateOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditLyrics),
+        None => false,
+    };
+    if !has_permissions {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if context.params.validate().is_err() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let html = match LyricsPreviewTemplate::new(LyricsPreviewParams {
+        kanji: context.params.kanji.clone(),
+        romaji: context.params.romaji.clone(),
+        translation: context.params.translation.clone(),
+        language: LyricsLanguage::from_code(&context.params.language),
+    }).await {
+        Ok(template) => template.render().map_err(RenderingError::from),
+        Err(error) => Err(RenderingError::from(error)),
+    };
+
+    html_to_response(
+        &context,
+        |_, _| async move { html }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct FillRomajiPageParams {
+    #[route_param_source(source = "form", name = "kanji", default = "")]
+    pub kanji: String,
+
+    #[route_param_source(source = "form", name = "romaji", default = "")]
+    pub romaji: String,
+}
+pub type FillRomajiPageContext = BaseContext<FillRomajiPageParams>;
+
+/**
+ * Fills in the blank lines of the rōmaji field from kana in the kanji field.
+ */
+pub async fn post_fill_romaji(
+    Context { context }: Context<FillRomajiPageParams>,
+) -> Response {
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::CreateOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditLyrics),
+        None => false,
+    };
+    if !has_permissions {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    html_to_response(
+        &context,
+        |_, context| async move {
+            render_template!(EditLyricsRomajiFieldTemplate, &context)
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct ImportLrcPageParams {
+    #[route_param_source(source = "form", name = "kanji", default = "")]
+    pub kanji: String,
+
+    #[route_param_source(source = "form", name = "timestamps", default = "")]
+    pub timestamps: String,
+
+    #[route_param_source(source = "form", name = "lrc", default = "")]
+    pub lrc: String,
+}
+pub type ImportLrcPageContext = BaseContext<ImportLrcPageParams>;
+
+/**
+ * Fills in the timestamps field from LRC or enhanced LRC pasted into the editor.
+ */
+pub async fn post_import_lrc(
+    Context { context }: Context<ImportLrcPageParams>,
+) -> Response {
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::CreateOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditOwnLyrics)
+            || user.permissions.contains(&UserPermission::EditLyrics),
+        None => false,
+    };
+    if !has_permissions {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    html_to_response(
+        &context,
+        |_, context| async move {
+            render_template!(EditLyricsTimestampsFieldTemplate, &context)
+        }
+    ).await
+}
+
 pub async fn send_edit_lyrics_page_response(status: StatusCode, context: EditLyricsPageContext) -> Response {
     (
         status,
@@ -0,0 +1,244 @@
+use axum::{
+    body::Bytes,
+    http::{ header, HeaderMap, StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, UserPermission, User };
+use crate::router::authn::{ two_factor_rate_limit_exceeded, verify_password, verify_two_factor_code };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::edit_profile_2fa::{ EditProfile2faPageTemplate, EditProfile2faPageContentTemplate };
+use crate::util::totp;
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct EditProfile2faPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub recovery_codes: Vec<String>,
+}
+pub type EditProfile2faPageContext = BaseContext<EditProfile2faPageParams>;
+
+pub async fn get_edit_profile_2fa(
+    Context { mut context }: Context<EditProfile2faPageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(EditProfile2faPageContentTemplate, &context),
+                _ => render_template!(EditProfile2faPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, RouteParamsContext)]
+pub struct EditProfile2faQrCodeParams {}
+
+/**
+ * QR code for the secret that is waiting to be confirmed. It is never generated
+ * for a secret that was already enabled, so it can't be used to copy an active one.
+ */
+pub async fn get_edit_profile_2fa_qr_code(
+    Context { context }: Context<EditProfile2faQrCodeParams>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CACHE_CONTROL, header::HeaderValue::from_static("no-store"));
+
+    let qr_code = match &context.user {
+        Some(user) => match totp::get_pending_enrollment(user.id) {
+            Some(secret) => totp::generate_qr_code(&secret, &user.username).ok(),
+            None => None,
+        },
+        None => None,
+    };
+
+    match qr_code {
+        Some(png) => {
+            headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("image/png"));
+            (StatusCode::OK, headers, Bytes::from(png))
+        },
+        None => (StatusCode::NOT_FOUND, headers, Bytes::from("")),
+    }
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateProfile2faPageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(enable|regenerate|disable)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "code", default = "")]
+    #[garde(
+        length(min = 1, max = 20),
+    )]
+    pub code: String,
+
+    #[route_param_source(source = "form", name = "current-password", default = "")]
+    #[garde(skip)]
+    pub current_password: String,
+}
+
+pub async fn put_update_profile_2fa(
+    Context { context }: Context<UpdateProfile2faPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(EditProfile2faPageParams {
+        validation_report: None,
+        recovery_codes: Vec::new(),
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_edit_profile_2fa_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    let username = context.user.as_ref().unwrap().username.clone();
+
+    let existing_user = match validate_profile_2fa_update_form(&context.params, &username).await {
+        Ok(existing_user) => existing_user,
+        Err(report) => {
+            page_context.params.validation_report = Some(report);
+            return send_edit_profile_2fa_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        }
+    };
+
+    let result = match context.params.action.as_str() {
+        "enable" => {
+            let secret = totp::get_pending_enrollment(existing_user.id);
+            let step = secret.as_ref().and_then(|secret| totp::verify_code(secret, &context.params.code));
+            if existing_user.totp_enabled || secret.is_none() || step.is_none() {
+                page_context.params.validation_report = Some(
+                    create_simple_report(String::from("code"), String::from("Bad code entry."))
+                );
+                return send_edit_profile_2fa_page_response(StatusCode::BAD_REQUEST, page_context).await;
+            }
+            let recovery_codes = totp::generate_recovery_codes();
+            let recovery_code_hashes = recovery_codes.iter().map(|code| totp::hash_recovery_code(code)).collect();
+            let result = match totp::encrypt_secret(&secret.unwrap()) {
+                Ok(encrypted_secret) => {
+                    database::enable_user_totp(existing_user.id, &encrypted_secret, &recovery_code_hashes).await
+                },
+                Err(error) => Err(error),
+            };
+            if result.is_ok() {
+                totp::discard_pending_enrollment(existing_user.id);
+                let _ = database::consume_user_totp_step(existing_user.id, step.unwrap()).await;
+                page_context.params.recovery_codes = recovery_codes;
+            }
+            result
+        },
+        "regenerate" => {
+            if let Err(status) = check_current_password_and_code(&existing_user, &context, &mut page_context).await {
+                return send_edit_profile_2fa_page_response(status, page_context).await;
+            }
+            let recovery_codes = totp::generate_recovery_codes();
+            let recovery_code_hashes = recovery_codes.iter().map(|code| totp::hash_recovery_code(code)).collect();
+            let result = database::replace_user_recovery_codes(existing_user.id, &recovery_code_hashes).await;
+            if result.is_ok() {
+                page_context.params.recovery_codes = recovery_codes;
+            }
+            result
+        },
+        _ => {
+            if let Err(status) = check_current_password_and_code(&existing_user, &context, &mut page_context).await {
+                return send_edit_profile_2fa_page_response(status, page_context).await;
+            }
+            database::disable_user_totp(existing_user.id).await
+        },
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to update their two-factor authentication. {:?}", &username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_edit_profile_2fa_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    send_edit_profile_2fa_page_response(StatusCode::OK, page_context).await
+}
+
+/**
+ * Regenerating recovery codes and disabling two-factor authentication both need the
+ * current password and a code. Code attempts share the rate limit used when signing in.
+ */
+async fn check_current_password_and_code(
+    existing_user: &User,
+    context: &BaseContext<UpdateProfile2faPageParams>,
+    page_context: &mut EditProfile2faPageContext,
+) -> Result<(), StatusCode> {
+    if let Err(_) = verify_password(&existing_user.password, &context.params.current_password) {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("current_password"), String::from("Bad password entry."))
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if two_factor_rate_limit_exceeded(existing_user.id, &context.ip_address) {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("rate_limit"), String::from("Rate limit exceeded."))
+        );
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+    if !verify_two_factor_code(existing_user, &context.params.code).await {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("code"), String::from("Bad code entry."))
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+async fn validate_profile_2fa_update_form(form: &UpdateProfile2faPageParams, username: &str) -> Result<User, Report> {
+    if let Err(report) = form.validate() {
+        return Err(report);
+    }
+    match database::get_user_by_username(username).await {
+        Ok(existing_user) => Ok(existing_user),
+        Err(_) => Err(
+            create_simple_report(String::from("server_error"), String::from("User not found."))
+        ),
+    }
+}
+
+pub async fn send_edit_profile_2fa_page_response(status: StatusCode, context: EditProfile2faPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(EditProfile2faPageContentTemplate, &context),
+                    _ => render_template!(EditProfile2faPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
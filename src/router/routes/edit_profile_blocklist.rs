@@ -0,0 +1,117 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, UserPermission };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::edit_profile_blocklist::{ EditProfileBlocklistPageTemplate, EditProfileBlocklistPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct EditProfileBlocklistPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+}
+pub type EditProfileBlocklistPageContext = BaseContext<EditProfileBlocklistPageParams>;
+
+pub async fn get_edit_profile_blocklist(
+    Context { mut context }: Context<EditProfileBlocklistPageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(EditProfileBlocklistPageContentTemplate, &context),
+                _ => render_template!(EditProfileBlocklistPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateProfileBlocklistPageParams {
+    #[route_param_source(source = "form", name = "username", default = "")]
+    #[garde(
+        length(min = 1, max = 30),
+    )]
+    pub username: String,
+}
+
+pub async fn put_update_profile_blocklist(
+    Context { context }: Context<UpdateProfileBlocklistPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(EditProfileBlocklistPageParams {
+        validation_report: None,
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_edit_profile_blocklist_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_edit_profile_blocklist_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let user = context.user.as_ref().unwrap();
+
+    let blocked_user_id = match database::get_user_by_username(&context.params.username).await {
+        Ok(blocked_user) => blocked_user.id,
+        Err(_) => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("username"), String::from("Member not found."))
+            );
+            return send_edit_profile_blocklist_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        },
+    };
+
+    if let Err(error) = database::unblock_user(user.id, blocked_user_id).await {
+        tracing::warn!("Database call failed when user {} tried to unblock a member. {:?}", &user.username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_edit_profile_blocklist_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    send_edit_profile_blocklist_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_edit_profile_blocklist_page_response(status: StatusCode, context: EditProfileBlocklistPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(EditProfileBlocklistPageContentTemplate, &context),
+                    _ => render_template!(EditProfileBlocklistPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
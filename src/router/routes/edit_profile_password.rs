@@ -8,7 +8,7 @@ use garde::{ Validate, Report };
 use macros::{ RouteParamsContext, render_template };
 
 use crate::database::{ self, UserPermission, User };
-use crate::router::authn::verify_password;
+use crate::router::authn::{ invalidate_other_user_sessions, verify_password, AuthSession };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
 use crate::ui_pages::edit_profile_password::{ EditProfilePasswordPageTemplate, EditProfilePasswordPageContentTemplate };
 use crate::util::user::create_user_profile_href;
@@ -68,6 +68,7 @@ pub struct UpdateProfilePasswordPageParams {
 }
 
 pub async fn put_update_profile_password(
+    mut auth_session: AuthSession,
     Context { context }: Context<UpdateProfilePasswordPageParams>,
 ) -> Response {
 
@@ -105,6 +106,10 @@ pub async fn put_update_profile_password(
         return send_edit_profile_password_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
     };
 
+    if let Err(error) = invalidate_other_user_sessions(&mut auth_session).await {
+        tracing::warn!("Failed to sign out other sessions after user {} changed their password. {:?}", &username, error);
+    }
+
     Redirect::to(
         create_user_profile_href(&username).as_str()
     ).into_response()
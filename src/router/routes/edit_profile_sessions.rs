@@ -0,0 +1,154 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, UserPermission };
+use crate::router::authn::{ AuthSession };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::router::session_store::hash_session_id;
+use crate::ui_pages::edit_profile_sessions::{ EditProfileSessionsPageTemplate, EditProfileSessionsPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct EditProfileSessionsPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub current_session: String,
+
+    #[route_param_source(source = "none")]
+    pub revoked_count: u64,
+}
+pub type EditProfileSessionsPageContext = BaseContext<EditProfileSessionsPageParams>;
+
+fn get_current_session_hash(auth_session: &AuthSession) -> String {
+    match auth_session.session.id() {
+        Some(id) => hash_session_id(&id.to_string()),
+        None => String::from(""),
+    }
+}
+
+pub async fn get_edit_profile_sessions(
+    auth_session: AuthSession,
+    Context { mut context }: Context<EditProfileSessionsPageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+    context.params.current_session = get_current_session_hash(&auth_session);
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(EditProfileSessionsPageContentTemplate, &context),
+                _ => render_template!(EditProfileSessionsPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateProfileSessionsPageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(revoke|revoke-others)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "session", default = "")]
+    #[garde(
+        pattern(r"^([0-9a-f]{64})?$"),
+    )]
+    pub session: String,
+}
+
+pub async fn put_update_profile_sessions(
+    auth_session: AuthSession,
+    Context { context }: Context<UpdateProfileSessionsPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(EditProfileSessionsPageParams {
+        validation_report: None,
+        current_session: get_current_session_hash(&auth_session),
+        revoked_count: 0,
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::EditOwnProfileInfo),
+        None => false,
+    };
+
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_edit_profile_sessions_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_edit_profile_sessions_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let user = context.user.as_ref().unwrap();
+
+    let result = match context.params.action.as_str() {
+        "revoke" => {
+            if context.params.session.is_empty() || context.params.session == page_context.params.current_session {
+                page_context.params.validation_report = Some(
+                    create_simple_report(String::from("session"), String::from("Bad session entry."))
+                );
+                return send_edit_profile_sessions_page_response(StatusCode::BAD_REQUEST, page_context).await;
+            }
+            database::delete_session_record_by_user_id_and_hash(user.id, &context.params.session).await
+        },
+        _ => {
+            let session_id = auth_session.session.id().map(|id| id.to_string());
+            database::delete_session_records_by_user_id(user.id, session_id.as_deref()).await
+        },
+    };
+
+    match result {
+        Ok(revoked_count) => {
+            page_context.params.revoked_count = revoked_count;
+        },
+        Err(error) => {
+            tracing::warn!("Database call failed when user {} tried to revoke sessions. {:?}", &user.username, error);
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+            );
+            return send_edit_profile_sessions_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+        },
+    }
+
+    send_edit_profile_sessions_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_edit_profile_sessions_page_response(status: StatusCode, context: EditProfileSessionsPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(EditProfileSessionsPageContentTemplate, &context),
+                    _ => render_template!(EditProfileSessionsPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
@@ -260,7 +260,7 @@ pub async fn put_update_tabs(
     existing_tabs.tab_type = tab_type.clone();
     existing_tabs.tab_content = context.params.tab_content.clone();
 
-    if let Err(error) = database::update_song_tab(existing_tabs).await {
+    if let Err(error) = database::update_song_tab(existing_tabs, &user.username).await {
         tracing::warn!("Database call failed when user {} tried to update tabs. {:?}", username, error);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
@@ -9,6 +9,7 @@ use macros::{ RouteParamsContext, render_template };
 
 use crate::database::{ self, User };
 use crate::router::{ html_to_response };
+use crate::router::authn::invalidate_user_sessions;
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
 use crate::router::validation::create_simple_report;
 use crate::ui_pages::forgot_password::{ ForgotPasswordPageTemplate, ForgotPasswordPageContentTemplate };
@@ -197,6 +198,10 @@ pub async fn post_reset_password(
 
     discard_password_reset_session(&context.params.session);
 
+    if let Err(error) = invalidate_user_sessions(user.id).await {
+        tracing::warn!("Failed to sign out sessions after user {} reset their password. {:?}", &user.username, error);
+    }
+
     page_context.params.validation_report = Some(
         create_simple_report(String::from("reset_success"), String::from("Reset request success."))
     );
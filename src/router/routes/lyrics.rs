@@ -27,6 +27,12 @@ pub struct LyricsPageParams {
     #[route_param_source(source = "query", name = "search", default = "")]
     pub search: String,
 
+    #[route_param_source(source = "query", name = "search-page", default = "1")]
+    pub search_page: u32,
+
+    #[route_param_source(source = "query", name = "language", default = "")]
+    pub language: String,
+
     #[route_param_source(source = "query", name = "comments-page", default = "1")]
     pub comments_page: u32,
 }
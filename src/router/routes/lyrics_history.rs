@@ -0,0 +1,179 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, Lyrics, LyricsLanguage, UserPermission };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::lyrics_history::{ LyricsHistoryPageTemplate, LyricsHistoryPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct LyricsHistoryPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub is_rolled_back: bool,
+
+    #[route_param_source(source = "path", name = "band", default = "")]
+    pub band: String,
+
+    #[route_param_source(source = "path", name = "album", default = "")]
+    pub album: String,
+
+    #[route_param_source(source = "path", name = "song", default = "")]
+    pub song: String,
+
+    #[route_param_source(source = "query", name = "contributor", default = "")]
+    pub contributor: String,
+
+    #[route_param_source(source = "query", name = "revision", default = "-1")]
+    pub revision_id: i32,
+}
+pub type LyricsHistoryPageContext = BaseContext<LyricsHistoryPageParams>;
+
+pub async fn get_lyrics_history(
+    Context { context }: Context<LyricsHistoryPageParams>,
+) -> Response {
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(LyricsHistoryPageContentTemplate, &context),
+                _ => render_template!(LyricsHistoryPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct RollbackLyricsPageParams {
+    #[route_param_source(source = "path", name = "band", default = "")]
+    #[garde(skip)]
+    pub band: String,
+
+    #[route_param_source(source = "path", name = "album", default = "")]
+    #[garde(skip)]
+    pub album: String,
+
+    #[route_param_source(source = "path", name = "song", default = "")]
+    #[garde(skip)]
+    pub song: String,
+
+    #[route_param_source(source = "form", name = "contributor", default = "")]
+    #[garde(
+        length(min = 1),
+    )]
+    pub contributor: String,
+
+    #[route_param_source(source = "form", name = "revision", default = "-1")]
+    #[garde(skip)]
+    pub revision_id: i32,
+}
+
+pub async fn post_lyrics_history(
+    Context { context }: Context<RollbackLyricsPageParams>,
+) -> Response {
+    let mut page_context = context.clone_with_params(LyricsHistoryPageParams {
+        validation_report: None,
+        is_rolled_back: false,
+        band: context.params.band.clone(),
+        album: context.params.album.clone(),
+        song: context.params.song.clone(),
+        contributor: context.params.contributor.clone(),
+        revision_id: context.params.revision_id,
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => {
+            user.permissions.contains(&UserPermission::EditLyrics) || (
+                user.permissions.contains(&UserPermission::EditOwnLyrics)
+                && user.username == context.params.contributor
+            )
+        },
+        None => false,
+    };
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_lyrics_history_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_lyrics_history_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let lyrics = match find_lyrics(&context.params).await {
+        Some(lyrics) => lyrics,
+        None => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("lyrics_missing"), String::from("The lyrics do not exist."))
+            );
+            return send_lyrics_history_page_response(StatusCode::NOT_FOUND, page_context).await;
+        },
+    };
+
+    let revisions = database::get_lyrics_revisions(lyrics.id).await.unwrap_or_default();
+    let revision = match revisions.iter().position(|revision| revision.id == context.params.revision_id) {
+        Some(index) if index > 0 => &revisions[index],
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("revision"), String::from("The revision can't be restored."))
+            );
+            return send_lyrics_history_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        },
+    };
+
+    let username = &context.user.as_ref().unwrap().username;
+    let mut rolled_back_lyrics = Lyrics {
+        kanji_content: revision.kanji_content.clone(),
+        romaji_content: revision.romaji_content.clone(),
+        comment: revision.comment.clone(),
+        line_timestamps: revision.line_timestamps.clone(),
+        video_url: revision.video_url.clone(),
+        ..lyrics
+    };
+    for language in LyricsLanguage::all() {
+        rolled_back_lyrics.set_translation(&language, revision.get_translation(&language).to_string());
+    }
+
+    if let Err(error) = database::update_lyrics(rolled_back_lyrics, username).await {
+        tracing::warn!("Database call failed when user {} tried to roll back lyrics. {:?}", username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_lyrics_history_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.is_rolled_back = true;
+    page_context.params.revision_id = -1;
+    send_lyrics_history_page_response(StatusCode::OK, page_context).await
+}
+
+async fn find_lyrics(params: &RollbackLyricsPageParams) -> Option<Lyrics> {
+    let band = database::get_band_by_slug(&params.band).await.ok()?;
+    let song = database::get_song_by_slug_and_band_id(&params.song, band.id).await.ok()?;
+    database::get_lyrics_by_username_and_song_id(&params.contributor, song.id).await.ok()
+}
+
+pub async fn send_lyrics_history_page_response(status: StatusCode, context: LyricsHistoryPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(LyricsHistoryPageContentTemplate, &context),
+                    _ => render_template!(LyricsHistoryPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
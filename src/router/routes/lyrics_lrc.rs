@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+use axum::{
+    body::Bytes,
+    http::{ header, HeaderMap, StatusCode },
+    response::IntoResponse,
+};
+use macros::RouteParamsContext;
+
+use crate::database::{ self, Lyrics, LyricsLanguage };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::util::furigana::strip_furigana;
+use crate::util::lrc::{ create_lrc, parse_line_timestamps, LrcDocument, LrcLine };
+
+#[derive(Default, RouteParamsContext)]
+pub struct LyricsLrcParams {
+    #[route_param_source(source = "path", name = "band", default = "supercar")]
+    pub band: String,
+
+    #[route_param_source(source = "path", name = "album", default = "")]
+    pub album: String,
+
+    #[route_param_source(source = "path", name = "song", default = "")]
+    pub song: String,
+
+    #[route_param_source(source = "query", name = "contributor", default = "")]
+    pub contributor: String,
+
+    #[route_param_source(source = "query", name = "text", default = "kanji")]
+    pub text: String,
+
+    #[route_param_source(source = "query", name = "format", default = "")]
+    pub format: String,
+}
+pub type LyricsLrcContext = BaseContext<LyricsLrcParams>;
+
+/**
+ * Downloads the timed lines of the lyrics as LRC. "text" picks the kanji, the rōmaji,
+ * or a translation by its language code, and "format=enhanced" adds word timestamps.
+ */
+pub async fn get_lyrics_lrc(
+    Context { context }: Context<LyricsLrcParams>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, header::HeaderValue::from_static("text/plain; charset=utf-8"));
+
+    match create_lyrics_lrc(&context).await {
+        Some(lrc) => {
+            headers.insert(header::CONTENT_DISPOSITION, header::HeaderValue::from_static("attachment"));
+            (StatusCode::OK, headers, Bytes::from(lrc))
+        },
+        None => (StatusCode::NOT_FOUND, headers, Bytes::from("")),
+    }
+}
+
+async fn create_lyrics_lrc(context: &LyricsLrcContext) -> Option<String> {
+    let band = database::get_band_by_slug(&context.params.band).await.ok()?;
+    let album = database::get_album_by_slug_and_band_id(&context.params.album, band.id).await.ok()?;
+    let song = database::get_song_by_slug_and_band_id(&context.params.song, band.id).await.ok()?;
+
+    let lyrics: Lyrics = if context.params.contributor.is_empty() {
+        database::get_lyrics_by_song_id(song.id).await.ok()?.into_iter().next()?
+    } else {
+        database::get_lyrics_by_username_and_song_id(&context.params.contributor, song.id).await.ok()?
+    };
+
+    let text = match context.params.text.as_str() {
+        "kanji" => strip_furigana(&lyrics.kanji_content),
+        "romaji" => lyrics.romaji_content.clone(),
+        language_code => lyrics.get_translation(&LyricsLanguage::from_str(language_code).ok()?).to_string(),
+    };
+    let text_lines: Vec<&str> = text.lines().collect();
+
+    let lines: Vec<LrcLine> = parse_line_timestamps(&lyrics.line_timestamps)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, timestamp)| {
+            Some(LrcLine {
+                time_ms: timestamp?,
+                text: text_lines.get(index).unwrap_or(&"").trim().to_string(),
+            })
+        })
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some(create_lrc(
+        &LrcDocument {
+            title: song.song_name,
+            artist: band.band_name,
+            album: album.album_name,
+            author: lyrics.username,
+            lines,
+        },
+        context.params.format == "enhanced",
+    ))
+}
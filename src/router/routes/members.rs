@@ -1,12 +1,14 @@
 use axum::{
-    response::{ Response, Redirect },
+    response::{ IntoResponse, Response, Redirect },
 };
 use askama::Template;
 use macros::{ RouteParamsContext, render_template };
 
+use crate::database;
 use crate::ui_pages::members::{ MembersTemplate, MembersContentTemplate, MembersCommentsTemplate };
 use crate::router::{ html_to_response };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::util::user::create_user_profile_href;
 
 #[derive(Default, RouteParamsContext)]
 pub struct MembersPageParams {
@@ -54,3 +56,41 @@ pub async fn get_members_redirect(
         &format!("/members/")
     )
 }
+
+#[derive(Default, RouteParamsContext)]
+pub struct MemberBlockParams {
+    #[route_param_source(source = "path", name = "username", default = "")]
+    pub username: String,
+
+    #[route_param_source(source = "form", name = "action", default = "")]
+    pub action: String,
+}
+
+/**
+ * Blocks or unblocks a member from their profile page, then goes back to it.
+ */
+pub async fn post_member_block(
+    Context { context }: Context<MemberBlockParams>,
+) -> Response {
+    let user = match &context.user {
+        Some(user) => user,
+        None => return Redirect::to("/sign-in/").into_response(),
+    };
+    let blocked_user = match database::get_user_by_username(&context.params.username).await {
+        Ok(blocked_user) => blocked_user,
+        Err(_) => return Redirect::to("/members/").into_response(),
+    };
+
+    if blocked_user.id != user.id {
+        let result = match context.params.action.as_str() {
+            "block" => database::block_user(user.id, blocked_user.id).await,
+            "unblock" => database::unblock_user(user.id, blocked_user.id).await,
+            _ => Ok(()),
+        };
+        if let Err(error) = result {
+            tracing::warn!("Database call failed when user {} tried to {} user {}. {:?}", &user.username, &context.params.action, &blocked_user.username, error);
+        }
+    }
+
+    Redirect::to(&create_user_profile_href(&blocked_user.username)).into_response()
+}
@@ -0,0 +1,174 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response, Redirect },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, Notification, NotificationType, User, UserPermission, UserPreference };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::messages::{ MessagesPageTemplate, MessagesPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+use crate::util::rate_limit::rate_limit_exceeded;
+use crate::util::user::create_user_messages_href;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct MessagesPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "path", name = "username", default = "")]
+    pub username: String,
+
+    #[route_param_source(source = "query", name = "messages-page", default = "1")]
+    pub messages_page: u32,
+
+    #[route_param_source(default = "")]
+    pub message: String,
+}
+pub type MessagesPageContext = BaseContext<MessagesPageParams>;
+
+pub async fn get_messages(
+    Context { mut context }: Context<MessagesPageParams>,
+) -> Response {
+
+    if context.user.is_none() {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(MessagesPageContentTemplate, &context),
+                _ => render_template!(MessagesPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct SendMessagePageParams {
+    #[route_param_source(source = "path", name = "username", default = "")]
+    #[garde(skip)]
+    pub username: String,
+
+    #[route_param_source(source = "form", name = "message", default = "")]
+    #[garde(
+        length(min = 1, max = 4000)
+    )]
+    pub message: String,
+}
+
+pub async fn post_messages(
+    Context { context }: Context<SendMessagePageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(MessagesPageParams {
+        validation_report: None,
+        username: context.params.username.clone(),
+        messages_page: 1,
+        message: context.params.message.clone(),
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => user.permissions.contains(&UserPermission::SendDms),
+        None => false,
+    };
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_messages_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    let user = context.user.as_ref().unwrap();
+
+    let recipient = match validate_send_message_form(&context.params, user.id).await {
+        Ok(recipient) => recipient,
+        Err(report) => {
+            page_context.params.validation_report = Some(report);
+            return send_messages_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        },
+    };
+
+    let user_rate_limit_key = format!("direct_message_{}", user.id);
+    if rate_limit_exceeded(user_rate_limit_key.as_str(), 10, 60) {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("rate_limit"), String::from("Rate limit exceeded."))
+        );
+        return send_messages_page_response(StatusCode::TOO_MANY_REQUESTS, page_context).await;
+    }
+
+    if let Err(error) = database::create_direct_message(user.id, recipient.id, &context.params.message).await {
+        tracing::warn!("Database call failed when user {} tried to send a message. {:?}", &user.username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_messages_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    if recipient.preferences.contains(&UserPreference::NotifyDms) {
+        let notification = Notification {
+            username: recipient.username.clone(),
+            notification_type: NotificationType::DirectMessage,
+            notifier_username: user.username.clone(),
+            link: create_user_messages_href(&user.username),
+            ..Notification::default()
+        };
+        if let Err(error) = database::create_notification(&notification).await {
+            tracing::warn!("Failed to create a message notification for user {}. {:?}", &recipient.username, error);
+        }
+    }
+
+    Redirect::to(&create_user_messages_href(&recipient.username)).into_response()
+}
+
+/**
+ * Members can't message themselves, members who turned off messages, or members
+ * when either of them has blocked the other.
+ */
+async fn validate_send_message_form(form: &SendMessagePageParams, sender_id: i32) -> Result<User, Report> {
+    if let Err(report) = form.validate() {
+        return Err(report);
+    }
+    let recipient = match database::get_user_by_username(&form.username).await {
+        Ok(recipient) => recipient,
+        Err(_) => return Err(
+            create_simple_report(String::from("recipient_missing"), String::from("Member not found."))
+        ),
+    };
+    if recipient.id == sender_id || !recipient.preferences.contains(&UserPreference::AllowDms) {
+        return Err(
+            create_simple_report(String::from("recipient_not_accepting"), String::from("Member does not accept messages."))
+        );
+    }
+    match database::is_either_user_blocked(sender_id, recipient.id).await {
+        Ok(false) => Ok(recipient),
+        Ok(true) => Err(
+            create_simple_report(String::from("recipient_not_accepting"), String::from("Member does not accept messages."))
+        ),
+        Err(_) => Err(
+            create_simple_report(String::from("server_error"), String::from("Blocklist lookup failed."))
+        ),
+    }
+}
+
+pub async fn send_messages_page_response(status: StatusCode, context: MessagesPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(MessagesPageContentTemplate, &context),
+                    _ => render_template!(MessagesPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
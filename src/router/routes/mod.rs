@@ -6,7 +6,7 @@ use axum::{
     Router,
 };
 use axum_login::{
-    tower_sessions::{ MemoryStore, SessionManagerLayer },
+    tower_sessions::{ SessionManagerLayer },
     AuthManagerLayerBuilder,
 };
 use memory_serve::{ load_assets, MemoryServe };
@@ -14,11 +14,13 @@ use tower_http::{
     services::{ ServeDir },
 };
 use super::authn::Backend;
+use super::session_store::MySqlSessionStore;
 
 pub mod album_3d;
 pub mod bio;
 pub mod captcha;
 pub mod chat_box;
+pub mod comment_reports;
 pub mod comment_section;
 pub mod community_guidelines;
 pub mod delete_album;
@@ -29,14 +31,18 @@ pub mod delete_photo_album;
 pub mod delete_tabs;
 pub mod delete_video;
 pub mod delete_video_category;
+pub mod deletion_queue;
 pub mod edit_album;
 pub mod edit_band;
 pub mod edit_lyrics;
 pub mod edit_photo;
 pub mod edit_photo_album;
+pub mod edit_profile_2fa;
+pub mod edit_profile_blocklist;
 pub mod edit_profile_info;
 pub mod edit_profile_password;
 pub mod edit_profile_picture;
+pub mod edit_profile_sessions;
 pub mod edit_tabs;
 pub mod edit_video;
 pub mod edit_video_category;
@@ -44,17 +50,23 @@ pub mod forgot_password;
 pub mod home;
 pub mod lyrics;
 pub mod lyrics_booklet;
+pub mod lyrics_history;
+pub mod lyrics_lrc;
 pub mod members;
+pub mod messages;
+pub mod notifications;
 pub mod page_not_found;
 pub mod photos;
 pub mod privacy_policy;
 pub mod robots;
+pub mod search;
 pub mod sign_in;
 pub mod sign_out;
 pub mod sign_up;
 pub mod sitemap;
 pub mod tabs;
 pub mod terms_of_service;
+pub mod trash;
 pub mod videos;
 
 pub fn initialize() -> Router {
@@ -66,7 +78,7 @@ pub fn initialize() -> Router {
     let uploaded_files_path = PathBuf::from("uploads");
 
     // Session layer.
-    let session_store = MemoryStore::default();
+    let session_store = MySqlSessionStore::default();
     let session_layer = SessionManagerLayer::new(session_store);
 
     // Auth service.
@@ -93,6 +105,21 @@ pub fn initialize() -> Router {
         .route("/chat-box", post(chat_box::post_chat_box))
         .route("/chat-box/", post(chat_box::post_chat_box))
 
+        .route("/moderation/comment-reports", get(comment_reports::get_comment_reports))
+        .route("/moderation/comment-reports/", get(comment_reports::get_comment_reports))
+        .route("/moderation/comment-reports", post(comment_reports::post_comment_reports))
+        .route("/moderation/comment-reports/", post(comment_reports::post_comment_reports))
+
+        .route("/moderation/deletion-queue", get(deletion_queue::get_deletion_queue))
+        .route("/moderation/deletion-queue/", get(deletion_queue::get_deletion_queue))
+        .route("/moderation/deletion-queue", post(deletion_queue::post_deletion_queue))
+        .route("/moderation/deletion-queue/", post(deletion_queue::post_deletion_queue))
+
+        .route("/moderation/trash", get(trash::get_trash))
+        .route("/moderation/trash/", get(trash::get_trash))
+        .route("/moderation/trash", post(trash::post_trash))
+        .route("/moderation/trash/", post(trash::post_trash))
+
         .route("/comment-section/{section}/{section_tag_id}", get(comment_section::get_comment_section))
         .route("/comment-section/{section}/{section_tag_id}/", get(comment_section::get_comment_section))
         .route("/comment-section/{section}/{section_tag_id}/{reply_id}", get(comment_section::get_comment_section))
@@ -101,6 +128,16 @@ pub fn initialize() -> Router {
         .route("/comment-section/{section}/{section_tag_id}/", post(comment_section::post_comment_section))
         .route("/comment-section/{section}/{section_tag_id}/{reply_id}", post(comment_section::post_comment_section))
         .route("/comment-section/{section}/{section_tag_id}/{reply_id}/", post(comment_section::post_comment_section))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/like", post(comment_section::post_comment_like))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/like/", post(comment_section::post_comment_like))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/report", get(comment_section::get_comment_report))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/report/", get(comment_section::get_comment_report))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/report", post(comment_section::post_comment_report))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/report/", post(comment_section::post_comment_report))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/edit", get(comment_section::get_comment_edit))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/edit/", get(comment_section::get_comment_edit))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/edit", post(comment_section::post_comment_edit))
+        .route("/comment-section/{section}/{section_tag_id}/{reply_id}/edit/", post(comment_section::post_comment_edit))
 
         .route("/community-guidelines", get(community_guidelines::get_community_guidelines))
         .route("/community-guidelines/", get(community_guidelines::get_community_guidelines))
@@ -131,6 +168,12 @@ pub fn initialize() -> Router {
         .route("/editor/create/lyrics/{band}/", get(edit_lyrics::get_edit_lyrics))
         .route("/editor/create/lyrics/{band}/{album}", get(edit_lyrics::get_edit_lyrics))
         .route("/editor/create/lyrics/{band}/{album}/", get(edit_lyrics::get_edit_lyrics))
+        .route("/editor/preview/lyrics", post(edit_lyrics::post_preview_lyrics))
+        .route("/editor/preview/lyrics/", post(edit_lyrics::post_preview_lyrics))
+        .route("/editor/romaji/lyrics", post(edit_lyrics::post_fill_romaji))
+        .route("/editor/romaji/lyrics/", post(edit_lyrics::post_fill_romaji))
+        .route("/editor/lrc/lyrics", post(edit_lyrics::post_import_lrc))
+        .route("/editor/lrc/lyrics/", post(edit_lyrics::post_import_lrc))
         .route("/editor/delete/lyrics/{band}/{album}/{song}", get(delete_lyrics::get_delete_lyrics))
         .route("/editor/delete/lyrics/{band}/{album}/{song}/", get(delete_lyrics::get_delete_lyrics))
         .route("/editor/delete/lyrics/{band}/{album}/{song}/{contributor}", get(delete_lyrics::get_delete_lyrics))
@@ -182,6 +225,21 @@ pub fn initialize() -> Router {
         .route("/editor/update/photo-album/{album}", post(edit_photo_album::put_update_photo_album))
         .route("/editor/update/photo-album/{album}/", post(edit_photo_album::put_update_photo_album))
 
+        .route("/editor/update/profile-2fa", get(edit_profile_2fa::get_edit_profile_2fa))
+        .route("/editor/update/profile-2fa/", get(edit_profile_2fa::get_edit_profile_2fa))
+        .route("/editor/update/profile-2fa", put(edit_profile_2fa::put_update_profile_2fa))
+        .route("/editor/update/profile-2fa/", put(edit_profile_2fa::put_update_profile_2fa))
+        .route("/editor/update/profile-2fa", post(edit_profile_2fa::put_update_profile_2fa))
+        .route("/editor/update/profile-2fa/", post(edit_profile_2fa::put_update_profile_2fa))
+        .route("/editor/update/profile-2fa/qr-code.png", get(edit_profile_2fa::get_edit_profile_2fa_qr_code))
+
+        .route("/editor/update/profile-blocklist", get(edit_profile_blocklist::get_edit_profile_blocklist))
+        .route("/editor/update/profile-blocklist/", get(edit_profile_blocklist::get_edit_profile_blocklist))
+        .route("/editor/update/profile-blocklist", put(edit_profile_blocklist::put_update_profile_blocklist))
+        .route("/editor/update/profile-blocklist/", put(edit_profile_blocklist::put_update_profile_blocklist))
+        .route("/editor/update/profile-blocklist", post(edit_profile_blocklist::put_update_profile_blocklist))
+        .route("/editor/update/profile-blocklist/", post(edit_profile_blocklist::put_update_profile_blocklist))
+
         .route("/editor/update/profile-info", get(edit_profile_info::get_edit_profile_info))
         .route("/editor/update/profile-info/", get(edit_profile_info::get_edit_profile_info))
         .route("/editor/update/profile-info", put(edit_profile_info::put_update_profile_info))
@@ -196,6 +254,13 @@ pub fn initialize() -> Router {
         .route("/editor/update/profile-password", post(edit_profile_password::put_update_profile_password))
         .route("/editor/update/profile-password/", post(edit_profile_password::put_update_profile_password))
 
+        .route("/editor/update/profile-sessions", get(edit_profile_sessions::get_edit_profile_sessions))
+        .route("/editor/update/profile-sessions/", get(edit_profile_sessions::get_edit_profile_sessions))
+        .route("/editor/update/profile-sessions", put(edit_profile_sessions::put_update_profile_sessions))
+        .route("/editor/update/profile-sessions/", put(edit_profile_sessions::put_update_profile_sessions))
+        .route("/editor/update/profile-sessions", post(edit_profile_sessions::put_update_profile_sessions))
+        .route("/editor/update/profile-sessions/", post(edit_profile_sessions::put_update_profile_sessions))
+
         .route("/editor/update/profile-picture", get(edit_profile_picture::get_edit_profile_picture))
         .route("/editor/update/profile-picture/", get(edit_profile_picture::get_edit_profile_picture))
         .route("/editor/update/profile-picture", put(edit_profile_picture::put_update_profile_picture).layer(DefaultBodyLimit::max(1024 * 1024 * 8)))
@@ -296,6 +361,11 @@ pub fn initialize() -> Router {
         .route("/lyrics/{band}/{album}/", get(lyrics::get_lyrics))
         .route("/lyrics/{band}/{album}/{song}", get(lyrics::get_lyrics))
         .route("/lyrics/{band}/{album}/{song}/", get(lyrics::get_lyrics))
+        .route("/lyrics/{band}/{album}/{song}/lyrics.lrc", get(lyrics_lrc::get_lyrics_lrc))
+        .route("/lyrics/{band}/{album}/{song}/history", get(lyrics_history::get_lyrics_history))
+        .route("/lyrics/{band}/{album}/{song}/history/", get(lyrics_history::get_lyrics_history))
+        .route("/lyrics/{band}/{album}/{song}/history", post(lyrics_history::post_lyrics_history))
+        .route("/lyrics/{band}/{album}/{song}/history/", post(lyrics_history::post_lyrics_history))
         .route("/lyrics.php", get(lyrics::get_lyrics_redirect))
         .route("/lyrics-booklet/{band}/{album}", get(lyrics_booklet::get_lyrics_booklet))
         .route("/lyrics-booklet/{band}/{album}/", get(lyrics_booklet::get_lyrics_booklet))
@@ -304,8 +374,24 @@ pub fn initialize() -> Router {
         .route("/members/", get(members::get_members))
         .route("/members/{username}", get(members::get_members))
         .route("/members/{username}/", get(members::get_members))
+        .route("/members/{username}/block", post(members::post_member_block))
+        .route("/members/{username}/block/", post(members::post_member_block))
         .route("/members.php", get(members::get_members_redirect))
 
+        .route("/messages", get(messages::get_messages))
+        .route("/messages/", get(messages::get_messages))
+        .route("/messages/{username}", get(messages::get_messages))
+        .route("/messages/{username}/", get(messages::get_messages))
+        .route("/messages/{username}", post(messages::post_messages))
+        .route("/messages/{username}/", post(messages::post_messages))
+
+        .route("/notifications", get(notifications::get_notifications))
+        .route("/notifications/", get(notifications::get_notifications))
+        .route("/notifications", post(notifications::post_notifications))
+        .route("/notifications/", post(notifications::post_notifications))
+        .route("/notifications/{notification_id}", get(notifications::get_open_notification))
+        .route("/notifications/{notification_id}/", get(notifications::get_open_notification))
+
         .route("/photos", get(photos::get_photos))
         .route("/photos/", get(photos::get_photos))
         .route("/photos/{album}", get(photos::get_photos))
@@ -320,6 +406,12 @@ pub fn initialize() -> Router {
 
         .route("/robots.txt", get(robots::get_robots))
 
+        .route("/search", get(search::get_search))
+        .route("/search/", get(search::get_search))
+        .route("/search.json", get(search::get_search_json))
+        .route("/search/suggest", get(search::get_search_suggest))
+        .route("/search/suggest/", get(search::get_search_suggest))
+
         .route("/sign-in", get(sign_in::get_sign_in))
         .route("/sign-in/", get(sign_in::get_sign_in))
         .route("/sign-in", post(sign_in::post_sign_in))
@@ -342,6 +434,8 @@ pub fn initialize() -> Router {
         .route("/tabs/{band}/{song}/", get(tabs::get_tabs))
         .route("/tabs/{band}/{song}/{tab_type}/{contributor}", get(tabs::get_tabs))
         .route("/tabs/{band}/{song}/{tab_type}/{contributor}/", get(tabs::get_tabs))
+        .route("/tabs/{band}/{song}/{tab_type}/{contributor}", post(tabs::post_tabs))
+        .route("/tabs/{band}/{song}/{tab_type}/{contributor}/", post(tabs::post_tabs))
         .route("/tabs.php", get(tabs::get_tabs_redirect))
 
         .route("/terms-of-service", get(terms_of_service::get_terms_of_service))
@@ -0,0 +1,144 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response, Redirect },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database;
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::notifications::{ NotificationsPageTemplate, NotificationsPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct NotificationsPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "query", name = "notifications-page", default = "1")]
+    pub notifications_page: u32,
+}
+pub type NotificationsPageContext = BaseContext<NotificationsPageParams>;
+
+pub async fn get_notifications(
+    Context { mut context }: Context<NotificationsPageParams>,
+) -> Response {
+
+    if context.user.is_none() {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(NotificationsPageContentTemplate, &context),
+                _ => render_template!(NotificationsPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, RouteParamsContext)]
+pub struct OpenNotificationParams {
+    #[route_param_source(source = "path", name = "notification_id", default = "-1")]
+    pub notification_id: i32,
+}
+
+/**
+ * Marks a notification as read, then goes to the page it is about.
+ */
+pub async fn get_open_notification(
+    Context { context }: Context<OpenNotificationParams>,
+) -> Response {
+    let username = match &context.user {
+        Some(user) => user.username.clone(),
+        None => return Redirect::to("/sign-in/").into_response(),
+    };
+
+    match database::get_notification_by_id(&username, context.params.notification_id).await {
+        Ok(notification) => {
+            if let Err(error) = database::mark_notification_read(&username, notification.id).await {
+                tracing::warn!("Database call failed when user {} tried to read a notification. {:?}", &username, error);
+            }
+            Redirect::to(&notification.link).into_response()
+        },
+        Err(_) => Redirect::to("/notifications/").into_response(),
+    }
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateNotificationsPageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(mark-read|mark-all-read)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "notification", default = "-1")]
+    #[garde(skip)]
+    pub notification_id: i32,
+
+    #[route_param_source(source = "query", name = "notifications-page", default = "1")]
+    #[garde(skip)]
+    pub notifications_page: u32,
+}
+
+pub async fn post_notifications(
+    Context { context }: Context<UpdateNotificationsPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(NotificationsPageParams {
+        validation_report: None,
+        notifications_page: context.params.notifications_page,
+    });
+
+    let username = match &context.user {
+        Some(user) => user.username.clone(),
+        None => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+            );
+            return send_notifications_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_notifications_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let result = match context.params.action.as_str() {
+        "mark-read" => database::mark_notification_read(&username, context.params.notification_id).await,
+        _ => database::mark_all_notifications_read(&username).await,
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to mark notifications as read. {:?}", &username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_notifications_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    send_notifications_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_notifications_page_response(status: StatusCode, context: NotificationsPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(NotificationsPageContentTemplate, &context),
+                    _ => render_template!(NotificationsPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
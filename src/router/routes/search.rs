@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use axum::{
+    http::{ header, StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use macros::{ RouteParamsContext, render_template };
+use serde_json::json;
+
+use crate::database::{ self, SearchResultType };
+use crate::ui_modules::search_suggestions::{ SearchSuggestionsParams, SearchSuggestionsTemplate };
+use crate::ui_pages::search::{ SearchPageTemplate, SearchPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::util::rate_limit::rate_limit_exceeded;
+use crate::util::search_index::find_search_suggestions;
+
+#[derive(Default, RouteParamsContext)]
+pub struct SearchPageParams {
+    #[route_param_source(source = "query", name = "q", default = "")]
+    pub search: String,
+
+    #[route_param_source(source = "query", name = "type", default = "")]
+    pub result_type: String,
+
+    #[route_param_source(source = "query", name = "search-page", default = "1")]
+    pub search_page: u32,
+}
+impl SearchPageParams {
+    /**
+     * An empty or unknown "type" searches every type.
+     */
+    pub fn get_result_type(&self) -> Option<SearchResultType> {
+        SearchResultType::from_str(&self.result_type).ok()
+    }
+}
+pub type SearchPageContext = BaseContext<SearchPageParams>;
+
+pub async fn get_search(
+    Context { context }: Context<SearchPageParams>,
+) -> Response {
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(SearchPageContentTemplate, &context),
+                _ => render_template!(SearchPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+/**
+ * The same search as the /search page, as JSON. Without a "type" every type is searched
+ * for its first few results, with a "type" the results are paged by "search-page".
+ */
+pub async fn get_search_json(
+    Context { context }: Context<SearchPageParams>,
+) -> Response {
+    let result_type = context.params.get_result_type();
+    let page_number = context.params.search_page.max(1);
+
+    let groups = match database::find_search_result_groups(result_type.as_ref(), &context.params.search, page_number).await {
+        Ok(groups) => groups,
+        Err(_) => return (
+            StatusCode::BAD_REQUEST,
+            [
+                (header::CONTENT_TYPE, "application/json"),
+            ],
+            json!({ "error": "Search failed." }).to_string(),
+        ).into_response(),
+    };
+
+    let body = json!({
+        "search": context.params.search.trim(),
+        "type": result_type.map(|result_type| result_type.to_string()),
+        "page": page_number,
+        "groups": groups.iter().map(|group| json!({
+            "type": group.result_type.to_string(),
+            "label": group.result_type.as_display(),
+            "count": group.result_count,
+            "results": group.results.iter().map(|result| json!({
+                "title": result.title,
+                "description": result.description,
+                "href": result.href,
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    });
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/json"),
+        ],
+        body.to_string(),
+    ).into_response()
+}
+
+#[derive(Default, RouteParamsContext)]
+pub struct SearchSuggestParams {
+    #[route_param_source(source = "query", name = "q", default = "")]
+    pub search: String,
+
+    #[route_param_source(source = "query", name = "search", default = "")]
+    pub lyrics_search: String,
+}
+impl SearchSuggestParams {
+    /**
+     * The lyrics search box names its input "search" instead of "q".
+     */
+    pub fn get_search(&self) -> &str {
+        if self.search.trim().is_empty() { self.lyrics_search.trim() } else { self.search.trim() }
+    }
+}
+pub type SearchSuggestContext = BaseContext<SearchSuggestParams>;
+
+/**
+ * The best matching bands, albums and songs while typing in a search box. htmx requests
+ * get a list of links, other requests get JSON.
+ */
+pub async fn get_search_suggest(
+    Context { context }: Context<SearchSuggestParams>,
+) -> Response {
+    let ip_address_rate_limit_key = format!("search_suggest_{}", &context.ip_address);
+    if
+        rate_limit_exceeded(ip_address_rate_limit_key.as_str(), 5, 1)
+    {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let search = context.params.get_search().to_string();
+
+    if context.route_headers.contains_key("HX-Request") {
+        return html_to_response(
+            &context,
+            |_, _| async move {
+                render_template!(SearchSuggestionsTemplate, SearchSuggestionsParams { search })
+            }
+        ).await;
+    }
+
+    let suggestions = find_search_suggestions(&search).await;
+    let body = json!({
+        "search": search,
+        "suggestions": suggestions.iter().map(|suggestion| json!({
+            "type": suggestion.result_type.to_string(),
+            "title": suggestion.title,
+            "description": suggestion.description,
+            "href": suggestion.href,
+        })).collect::<Vec<_>>(),
+    });
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/json"),
+        ],
+        body.to_string(),
+    ).into_response()
+}
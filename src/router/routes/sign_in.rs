@@ -12,8 +12,9 @@ use crate::database;
 use crate::ui_modules::account_summary::{ AccountSummaryTemplate, AccountSummaryParams };
 use crate::ui_pages::sign_in::{ SignInTemplate, SignInContentTemplate };
 use crate::router::{ get_hx_target, html_to_response };
-use crate::router::authn::{ AuthSession, Credentials, UserSession };
+use crate::router::authn::{ login_user_session, AuthnError, AuthSession, Credentials, UserSession };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::util::totp;
 
 #[derive(Default, RouteParamsContext)]
 pub struct SignInPageParams {
@@ -22,6 +23,9 @@ pub struct SignInPageParams {
 
     #[route_param_source(default = "")]
     pub entered_username: String,
+
+    #[route_param_source(source = "none")]
+    pub two_factor_token: String,
 }
 pub type SignInPageContext = BaseContext<SignInPageParams>;
 
@@ -46,6 +50,12 @@ pub struct SignInRequestParams {
 
     #[route_param_source(source="form")]
     pub password: String,
+
+    #[route_param_source(source="form", name="two-factor-token", default="")]
+    pub two_factor_token: String,
+
+    #[route_param_source(source="form", name="two-factor-code", default="")]
+    pub two_factor_code: String,
 }
 
 pub async fn post_sign_in(
@@ -53,16 +63,28 @@ pub async fn post_sign_in(
     Context { context: request_context }: Context<SignInRequestParams>,
 ) -> Response {
     let mut status: StatusCode = StatusCode::OK;
+    let mut two_factor_token: String = String::from("");
 
     let credentials = Credentials {
         username: request_context.params.username.clone(),
         password: request_context.params.password.clone(),
         ip_address: request_context.ip_address.clone(),
+        two_factor_token: request_context.params.two_factor_token.clone(),
+        two_factor_code: request_context.params.two_factor_code.clone(),
     };
     let user = match auth_session.authenticate(credentials).await {
         Ok(Some(user)) => user,
         Ok(None) => {
             status = StatusCode::UNAUTHORIZED;
+            // A wrong code can be retried until the challenge runs out of attempts.
+            if totp::is_sign_in_challenge_active(&request_context.params.two_factor_token) {
+                two_factor_token = request_context.params.two_factor_token.clone();
+            }
+            UserSession::default()
+        },
+        Err(axum_login::Error::Backend(AuthnError::TwoFactorRequired(token))) => {
+            status = StatusCode::ACCEPTED;
+            two_factor_token = token;
             UserSession::default()
         },
         Err(_) => {
@@ -71,7 +93,7 @@ pub async fn post_sign_in(
         },
     };
 
-    if status == StatusCode::OK && auth_session.login(&user).await.is_err() {
+    if status == StatusCode::OK && login_user_session(&mut auth_session, &user).await.is_err() {
         status = StatusCode::INTERNAL_SERVER_ERROR;
     }
 
@@ -79,6 +101,7 @@ pub async fn post_sign_in(
         SignInPageParams {
             status: status.as_u16(),
             entered_username: request_context.params.username.clone(),
+            two_factor_token,
         }
     );
     let hx_target = get_hx_target(&context.route_headers);
@@ -30,6 +30,7 @@ pub async fn post_sign_out(
         SignInPageParams {
             status: 0,
             entered_username: String::from(""),
+            two_factor_token: String::from(""),
         }
     );
 
@@ -10,7 +10,7 @@ use macros::{ RouteParamsContext, render_template };
 use crate::database;
 use crate::ui_pages::sign_up::{ SignUpTemplate, SignUpContentTemplate };
 use crate::router::{ html_to_response };
-use crate::router::authn::{ AuthSession, Credentials, UserSession };
+use crate::router::authn::{ login_user_session, AuthSession, Credentials, UserSession };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
 use crate::util::captcha::{ get_captcha_pow_challenge_by_id, validate_captcha, validate_pow_challenge };
 use crate::util::rate_limit::rate_limit_exceeded;
@@ -150,6 +150,8 @@ pub async fn post_sign_up(
         username: context.params.username.clone(),
         password: context.params.password.clone(),
         ip_address: context.ip_address.clone(),
+        two_factor_token: String::from(""),
+        two_factor_code: String::from(""),
     };
     let mut status: StatusCode = StatusCode::OK;
     let user = match auth_session.authenticate(credentials).await {
@@ -164,7 +166,7 @@ pub async fn post_sign_up(
         },
     };
 
-    if status == StatusCode::OK && auth_session.login(&user).await.is_err() {
+    if status == StatusCode::OK && login_user_session(&mut auth_session, &user).await.is_err() {
         tracing::warn!("Failed to sign in user {} after registration.", &context.params.username);
         page_context.params.validation_report = Some(
             create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
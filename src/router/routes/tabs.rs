@@ -1,18 +1,27 @@
 use axum::{
-    response::{ Response, Redirect },
+    http::{ StatusCode },
+    response::{ IntoResponse, Response, Redirect },
 };
 use askama::Template;
+use garde::{ Report };
 use macros::{ RouteParamsContext, render_template };
 use urlencoding::encode;
 
-use crate::database::{ self, Band, Song, SongTab };
+use crate::database::{ self, Band, Song, SongTab, SongTabType, UserPermission };
 use crate::ui_pages::tabs::{ TabsTemplate, TabsContentTemplate, TabsCommentsTemplate };
-use crate::util::format::to_kebab_case;
+use crate::util::format::{ self, to_kebab_case };
 use crate::router::{ html_to_response };
 use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::router::validation::create_simple_report;
 
 #[derive(Default, RouteParamsContext)]
 pub struct TabsPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub is_restored: bool,
+
     #[route_param_source(source = "path", name = "band", default = "supercar")]
     pub band: String,
 
@@ -27,6 +36,9 @@ pub struct TabsPageParams {
 
     #[route_param_source(source = "query", name = "comments-page", default = "1")]
     pub comments_page: u32,
+
+    #[route_param_source(source = "query", name = "revision", default = "-1")]
+    pub revision_id: i32,
 }
 pub type TabsPageContext = BaseContext<TabsPageParams>;
 
@@ -45,6 +57,117 @@ pub async fn get_tabs(
     ).await
 }
 
+#[derive(Default, RouteParamsContext)]
+pub struct RestoreTabsPageParams {
+    #[route_param_source(source = "path", name = "band", default = "supercar")]
+    pub band: String,
+
+    #[route_param_source(source = "path", name = "song", default = "")]
+    pub song: String,
+
+    #[route_param_source(source = "path", name = "tab_type", default = "")]
+    pub tab_type: String,
+
+    #[route_param_source(source = "path", name = "contributor", default = "")]
+    pub contributor: String,
+
+    #[route_param_source(source = "form", name = "revision", default = "-1")]
+    pub revision_id: i32,
+}
+
+pub async fn post_tabs(
+    Context { context }: Context<RestoreTabsPageParams>,
+) -> Response {
+    let mut page_context = context.clone_with_params(TabsPageParams {
+        validation_report: None,
+        is_restored: false,
+        band: context.params.band.clone(),
+        song: context.params.song.clone(),
+        tab_type: context.params.tab_type.clone(),
+        contributor: context.params.contributor.clone(),
+        comments_page: 1,
+        revision_id: context.params.revision_id,
+    });
+
+    let has_permissions = match &context.user {
+        Some(user) => {
+            user.permissions.contains(&UserPermission::EditTabs) || (
+                user.permissions.contains(&UserPermission::EditOwnTabs)
+                && user.username == context.params.contributor
+            )
+        },
+        None => false,
+    };
+    if !has_permissions {
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+        return send_tabs_page_response(StatusCode::FORBIDDEN, page_context).await;
+    }
+
+    let tab = match find_tab(&context.params).await {
+        Some(tab) => tab,
+        None => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("tabs_missing"), String::from("The tabs do not exist."))
+            );
+            return send_tabs_page_response(StatusCode::NOT_FOUND, page_context).await;
+        },
+    };
+
+    let revisions = database::get_tab_revisions(tab.id).await.unwrap_or_default();
+    let revision = match revisions.iter().position(|revision| revision.id == context.params.revision_id) {
+        Some(index) if index > 0 => &revisions[index],
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("revision"), String::from("The revision can't be restored."))
+            );
+            return send_tabs_page_response(StatusCode::BAD_REQUEST, page_context).await;
+        },
+    };
+
+    let username = &context.user.as_ref().unwrap().username;
+    let restored_tab = SongTab {
+        tab_content: revision.tab_content.clone(),
+        ..tab
+    };
+
+    if let Err(error) = database::update_song_tab(restored_tab, username).await {
+        tracing::warn!("Database call failed when user {} tried to restore tabs. {:?}", username, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_tabs_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.is_restored = true;
+    page_context.params.revision_id = -1;
+    send_tabs_page_response(StatusCode::OK, page_context).await
+}
+
+async fn find_tab(params: &RestoreTabsPageParams) -> Option<SongTab> {
+    let band = database::get_band_by_slug(&params.band).await.ok()?;
+    let song = database::get_song_by_slug_and_band_id(&params.song, band.id).await.ok()?;
+    let tab_type = format::to_snake_case(&params.tab_type).parse::<SongTabType>().unwrap_or_else(|_| SongTabType::Unknown);
+    database::get_song_tab_by_username_type_and_song_id(&params.contributor, &tab_type, song.id).await.ok()
+}
+
+pub async fn send_tabs_page_response(status: StatusCode, context: TabsPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(TabsContentTemplate, &context),
+                    "page-comments" => render_template!(TabsCommentsTemplate, &context),
+                    _ => render_template!(TabsTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
+
 #[derive(Default, RouteParamsContext)]
 pub struct TabsRedirectParams {
     #[route_param_source(source = "query", name = "band", default = "supercar")]
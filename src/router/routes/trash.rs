@@ -0,0 +1,150 @@
+use axum::{
+    http::{ StatusCode },
+    response::{ IntoResponse, Response },
+};
+use askama::Template;
+use garde::{ Validate, Report };
+use macros::{ RouteParamsContext, render_template };
+
+use crate::database::{ self, DeletionContentType, UserPermission };
+use crate::router::context::{ BaseContext, Context, RouteParamContextGenerator };
+use crate::ui_pages::trash::{ TrashPageTemplate, TrashPageContentTemplate };
+use crate::router::{ html_to_response };
+use crate::router::validation::create_simple_report;
+
+#[derive(Default, Debug, RouteParamsContext)]
+pub struct TrashPageParams {
+    #[route_param_source(source = "none")]
+    pub validation_report: Option<Report>,
+
+    #[route_param_source(source = "none")]
+    pub success_action: String,
+
+    #[route_param_source(source = "query", name = "type", default = "lyrics")]
+    pub content_type: String,
+
+    #[route_param_source(source = "query", name = "trash-page", default = "1")]
+    pub trash_page: u32,
+}
+pub type TrashPageContext = BaseContext<TrashPageParams>;
+
+pub async fn get_trash(
+    Context { mut context }: Context<TrashPageParams>,
+) -> Response {
+
+    let has_permissions = match &context.user {
+        Some(user) => {
+            user.permissions.contains(&UserPermission::ApproveQueuedDeletion)
+            || user.permissions.contains(&UserPermission::UndoQueuedDeletion)
+        },
+        None => false,
+    };
+    if !has_permissions {
+        context.params.validation_report = Some(
+            create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+        );
+    }
+
+    html_to_response(
+        &context,
+        |hx_target, context| async move {
+            match hx_target.as_str() {
+                "main-article" => render_template!(TrashPageContentTemplate, &context),
+                _ => render_template!(TrashPageTemplate, &context),
+            }
+        }
+    ).await
+}
+
+#[derive(Default, Debug, RouteParamsContext, Validate)]
+pub struct UpdateTrashPageParams {
+    #[route_param_source(source = "form", name = "action", default = "")]
+    #[garde(
+        pattern(r"^(restore|purge)$"),
+    )]
+    pub action: String,
+
+    #[route_param_source(source = "form", name = "content", default = "-1")]
+    #[garde(skip)]
+    pub content_id: i32,
+
+    #[route_param_source(source = "query", name = "type", default = "lyrics")]
+    #[garde(
+        custom(is_valid_content_type),
+    )]
+    pub content_type: String,
+
+    #[route_param_source(source = "query", name = "trash-page", default = "1")]
+    #[garde(skip)]
+    pub trash_page: u32,
+}
+
+pub async fn post_trash(
+    Context { context }: Context<UpdateTrashPageParams>,
+) -> Response {
+
+    let mut page_context = context.clone_with_params(TrashPageParams {
+        validation_report: None,
+        success_action: String::from(""),
+        content_type: context.params.content_type.clone(),
+        trash_page: context.params.trash_page,
+    });
+
+    if let Err(report) = context.params.validate() {
+        page_context.params.validation_report = Some(report);
+        return send_trash_page_response(StatusCode::BAD_REQUEST, page_context).await;
+    }
+
+    let required_permission = match context.params.action.as_str() {
+        "purge" => UserPermission::ApproveQueuedDeletion,
+        _ => UserPermission::UndoQueuedDeletion,
+    };
+    let user = match &context.user {
+        Some(user) if user.permissions.contains(&required_permission) => user,
+        _ => {
+            page_context.params.validation_report = Some(
+                create_simple_report(String::from("forbidden"), String::from("Not Allowed."))
+            );
+            return send_trash_page_response(StatusCode::FORBIDDEN, page_context).await;
+        },
+    };
+
+    let content_type = context.params.content_type.parse::<DeletionContentType>().unwrap_or_default();
+    let result = match context.params.action.as_str() {
+        "purge" => database::purge_deleted_content(&content_type, context.params.content_id, &user.username).await,
+        _ => database::restore_deleted_content(&content_type, context.params.content_id, &user.username).await,
+    };
+
+    if let Err(error) = result {
+        tracing::warn!("Database call failed when user {} tried to {} trashed content. {:?}", &user.username, &context.params.action, error);
+        page_context.params.validation_report = Some(
+            create_simple_report(String::from("server_error"), String::from("An error occurred with the request."))
+        );
+        return send_trash_page_response(StatusCode::INTERNAL_SERVER_ERROR, page_context).await;
+    }
+
+    page_context.params.success_action = context.params.action.clone();
+    send_trash_page_response(StatusCode::OK, page_context).await
+}
+
+pub async fn send_trash_page_response(status: StatusCode, context: TrashPageContext) -> Response {
+    (
+        status,
+        html_to_response(
+            &context,
+            |hx_target, context| async move {
+                match hx_target.as_str() {
+                    "main-article" => render_template!(TrashPageContentTemplate, &context),
+                    _ => render_template!(TrashPageTemplate, &context),
+                }
+            }
+        ).await
+    ).into_response()
+}
+
+fn is_valid_content_type(value: &str, _: &()) -> garde::Result {
+    match value.parse::<DeletionContentType>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(garde::Error::new("Unknown content type.")),
+    }
+}
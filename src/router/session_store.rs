@@ -0,0 +1,169 @@
+/**
+ * Tower session store backed by the "sessions" table, so that sign ins survive
+ * restarts and are shared between every instance behind the load balancer.
+ */
+
+use std::time::Duration;
+use async_trait::async_trait;
+use axum_login::tower_sessions::{
+    session::{ Id, Record },
+    session_store::{ self, SessionStore },
+    Session,
+};
+use chrono::Utc;
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use tokio::time::interval;
+
+use crate::database::{ self, SessionClient };
+use crate::util::geolocation;
+
+/**
+ * Session data key that holds the signed in user's id. It is copied to the
+ * sessions.user_id column on save, see authn::login.
+ */
+pub const SESSION_USER_ID_KEY: &str = "supercar.user_id";
+pub static SESSION_SWEEP_INTERVAL_SECONDS: u64 = 600;
+
+/**
+ * Session data key that holds where the session was last used from. It is copied
+ * to the sessions table on save, so it can be listed on the active sessions page.
+ */
+pub const SESSION_CLIENT_KEY: &str = "supercar.client";
+pub static SESSION_CLIENT_REFRESH_SECONDS: i64 = 300;
+const USER_AGENT_MAX_LENGTH: usize = 512;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SessionClientData {
+    pub ip_address: String,
+    pub user_agent: String,
+    pub location: String,
+    pub last_seen: i64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MySqlSessionStore {}
+
+fn backend_error(error: Box<dyn std::error::Error + Send + Sync>) -> session_store::Error {
+    session_store::Error::Backend(error.to_string())
+}
+
+#[async_trait]
+impl SessionStore for MySqlSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        while database::session_record_exists(&record.id.to_string()).await.map_err(backend_error)? {
+            record.id = Id::default();
+        }
+        self.save(record).await
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let serialized_record = serde_json::to_string(record)
+            .map_err(|error| session_store::Error::Encode(error.to_string()))?;
+        let user_id = record.data
+            .get(SESSION_USER_ID_KEY)
+            .and_then(|value| value.as_i64())
+            .and_then(|value| i32::try_from(value).ok());
+        let client = record.data
+            .get(SESSION_CLIENT_KEY)
+            .and_then(|value| serde_json::from_value::<SessionClientData>(value.clone()).ok())
+            .map(|client| SessionClient {
+                ip_address: client.ip_address,
+                user_agent: client.user_agent,
+                location: client.location,
+            })
+            .unwrap_or_default();
+        database::save_session_record(
+            &record.id.to_string(),
+            user_id,
+            &serialized_record,
+            record.expiry_date.unix_timestamp(),
+            &client,
+        ).await.map_err(backend_error)
+    }
+
+    async fn load(&self, id: &Id) -> session_store::Result<Option<Record>> {
+        let session_record = database::get_session_record(&id.to_string(), Utc::now().timestamp())
+            .await
+            .map_err(backend_error)?;
+        match session_record {
+            Some(session_record) => Ok(Some(
+                serde_json::from_str::<Record>(&session_record.record)
+                    .map_err(|error| session_store::Error::Decode(error.to_string()))?
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id: &Id) -> session_store::Result<()> {
+        database::delete_session_record(&id.to_string()).await.map_err(backend_error)
+    }
+}
+
+/**
+ * Public handle for a session. The id is the value of the session cookie,
+ * so pages refer to sessions by its hash instead.
+ */
+pub fn hash_session_id(id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(id.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/**
+ * Records the IP address and user agent a signed in session is being used from.
+ * The session is only written when the client changed or the last write is old,
+ * so that the last seen time stays accurate without saving on every request.
+ */
+pub async fn touch_session_client(session: &Session, ip_address: &str, user_agent: &str) {
+    let now = Utc::now().timestamp();
+    let user_agent: String = user_agent.chars().take(USER_AGENT_MAX_LENGTH).collect();
+    let existing_client = session.get::<SessionClientData>(SESSION_CLIENT_KEY).await.ok().flatten();
+
+    let client = match existing_client {
+        Some(client) if client.ip_address == ip_address && client.user_agent == user_agent => {
+            if now - client.last_seen < SESSION_CLIENT_REFRESH_SECONDS {
+                return;
+            }
+            SessionClientData { last_seen: now, ..client }
+        },
+        Some(client) if client.ip_address == ip_address => {
+            SessionClientData { user_agent, last_seen: now, ..client }
+        },
+        _ => {
+            let location = match geolocation::find(ip_address).await {
+                Ok(location) => location.describe(),
+                Err(_) => String::from(""),
+            };
+            SessionClientData {
+                ip_address: ip_address.to_string(),
+                user_agent,
+                location,
+                last_seen: now,
+            }
+        },
+    };
+
+    if let Err(error) = session.insert(SESSION_CLIENT_KEY, client).await {
+        tracing::warn!("Failed to update session client. {:?}", error);
+    }
+}
+
+pub async fn init_session_expiry_sweep() {
+    let mut interval = interval(Duration::from_secs(SESSION_SWEEP_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        match database::delete_expired_session_records(Utc::now().timestamp()).await {
+            Ok(count) => {
+                if count > 0 {
+                    tracing::info!("Removed {} expired sessions.", count);
+                }
+            },
+            Err(error) => {
+                tracing::warn!("Error occurred when removing expired sessions. {:?}", error);
+            },
+        }
+    }
+}
@@ -9,6 +9,15 @@ pub fn report_has_field(report: &Report, field_name: &str) -> bool {
     false
 }
 
+pub fn get_report_field_message(report: &Report, field_name: &str) -> Option<String> {
+    for (path, error) in report.iter() {
+        if path.to_string() == field_name {
+            return Some(error.message().to_string())
+        }
+    }
+    None
+}
+
 pub fn create_simple_report(path: String, error_message: String) -> Report {
     let mut report = Report::new();
     let path = garde::Path::new(path);
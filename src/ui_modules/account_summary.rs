@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use askama::Template;
 use urlencoding::encode;
 
-use crate::database::{ self };
+use crate::database::{ self, UserPermission };
 use crate::router::context::{ RouteContext, UserContext };
 use crate::util::user::create_user_profile_href;
 
@@ -21,6 +21,12 @@ where &'a Ctx: RouteContext + UserContext {
     username: String,
     profile_picture_filename: String,
     redirect_url_encoded: String,
+    unread_notification_count: u32,
+    unread_message_count: u32,
+    can_moderate_comments: bool,
+    open_comment_report_count: u32,
+    can_review_deletions: bool,
+    pending_deletion_count: u32,
 }
 impl<'a, Ctx> AccountSummaryTemplate<'a, Ctx>
 where &'a Ctx: RouteContext + UserContext {
@@ -43,12 +49,50 @@ where &'a Ctx: RouteContext + UserContext {
         
         let user = database::get_user_by_username(&username).await?;
 
+        let (unread_notification_count, unread_message_count) = if let Some(user) = context.user() {
+            (
+                database::get_unread_notification_count(&username).await?,
+                database::get_unread_direct_message_count(user.id).await?,
+            )
+        } else {
+            (0, 0)
+        };
+
+        let can_moderate_comments = match context.user() {
+            Some(user) => user.permissions.contains(&UserPermission::DeleteComment),
+            None => false,
+        };
+        let open_comment_report_count = if can_moderate_comments {
+            database::get_open_comment_report_count().await?
+        } else {
+            0
+        };
+
+        let can_review_deletions = match context.user() {
+            Some(user) => {
+                user.permissions.contains(&UserPermission::ApproveQueuedDeletion)
+                || user.permissions.contains(&UserPermission::UndoQueuedDeletion)
+            },
+            None => false,
+        };
+        let pending_deletion_count = if can_review_deletions {
+            database::get_pending_deletions_count().await?
+        } else {
+            0
+        };
+
         Ok(AccountSummaryTemplate {
             phantom: PhantomData,
             first_name,
             username,
             profile_picture_filename: user.profile_picture_filename,
             redirect_url_encoded: encode(redirect_url).to_string(),
+            unread_notification_count,
+            unread_message_count,
+            can_moderate_comments,
+            open_comment_report_count,
+            can_review_deletions,
+            pending_deletion_count,
         })
     }
 }
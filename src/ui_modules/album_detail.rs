@@ -2,7 +2,12 @@ use std::error::Error;
 use std::marker::PhantomData;
 use askama::Template;
 
-use crate::database::{ self, Album, JoinedSongSlugs };
+use crate::database::{ self, Album, AlbumTrackListing };
+
+struct AlbumDisc {
+    disc_number: i32,
+    songs: Vec<AlbumTrackListing>,
+}
 
 pub struct AlbumDetailParams {
     pub album: Album,
@@ -15,7 +20,7 @@ pub struct AlbumDetailTemplate<'a> {
     phantom: PhantomData<&'a ()>,
     pub album: Album,
     band_slug: String,
-    songs: Vec<JoinedSongSlugs>,
+    discs: Vec<AlbumDisc>,
     has_lyrics_booklet: bool,
 }
 impl<'a> AlbumDetailTemplate<'a> {
@@ -24,14 +29,27 @@ impl<'a> AlbumDetailTemplate<'a> {
     ) -> Result<AlbumDetailTemplate<'a>, Box<dyn Error>> {
         let AlbumDetailParams { album, band_slug } = params;
 
-        let songs = database::get_song_slugs_by_ids(&album.song_ids()).await?;
+        let mut discs: Vec<AlbumDisc> = Vec::new();
+        for track in database::get_album_track_listing(album.id).await? {
+            match discs.last_mut() {
+                Some(disc) if disc.disc_number == track.disc_number => {
+                    disc.songs.push(track);
+                },
+                _ => {
+                    discs.push(AlbumDisc {
+                        disc_number: track.disc_number,
+                        songs: vec![track],
+                    });
+                },
+            }
+        }
         let images = database::get_lyrics_booklet_images(&band_slug, &album.album_slug).await;
 
         Ok(AlbumDetailTemplate {
             phantom: PhantomData,
             album,
             band_slug,
-            songs,
+            discs,
             has_lyrics_booklet: images.len() > 0,
         })
     }
@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use askama::Template;
 use urlencoding::encode;
 
-use crate::database::{ self, Comment, CommentSectionName, QueryOrder };
+use crate::database::{ self, Comment, CommentSectionName, QueryOrder, UserPermission };
 use crate::router::context::{ RouteContext, UserContext };
 use crate::util::format::make_content_links;
 use crate::util::user::{ create_user_profile_href, is_guest_user };
@@ -23,6 +23,8 @@ where &'a Ctx: RouteContext + UserContext {
     phantom: PhantomData<&'a Ctx>,
     comments: Vec<Comment>,
     redirect_url_encoded: String,
+    username: String,
+    can_report: bool,
 }
 impl<'a, Ctx> ChatBoxTemplate<'a, Ctx>
 where &'a Ctx: RouteContext + UserContext {
@@ -34,7 +36,7 @@ where &'a Ctx: RouteContext + UserContext {
         let comments = database::get_comments_in_range(
             (page_number - 1) * COMMENTS_PER_PAGE,
             COMMENTS_PER_PAGE, &CommentSectionName::Chatbox, None,
-            -1, &QueryOrder::Desc,
+            -1, &QueryOrder::Desc, context.user().as_ref().map(|user| user.id),
         ).await?;
 
         let redirect_url_encoded = encode(
@@ -42,10 +44,24 @@ where &'a Ctx: RouteContext + UserContext {
                 .split('#').collect::<Vec<&str>>().first().unwrap()
         ).to_string();
 
+        let can_report = match context.user() {
+            Some(user) => user.permissions.contains(&UserPermission::CreateOwnComment),
+            None => false,
+        };
+
         Ok(ChatBoxTemplate {
             phantom: PhantomData,
             comments,
             redirect_url_encoded,
+            username: context.username().to_string(),
+            can_report,
         })
     }
+
+    fn get_report_href(&self, comment: &Comment) -> String {
+        format!("/comment-section/chatbox/-1/{}/report/?redirect-to={}%23site-chatbox",
+            comment.id,
+            self.redirect_url_encoded,
+        )
+    }
 }
@@ -3,8 +3,8 @@ use askama::Template;
 use urlencoding::encode;
 
 use crate::database;
-use crate::database::{ CommentWithReplies, CommentSectionName };
-use crate::router::context::{ RouteContext, UserContext };
+use crate::database::{ CommentWithReplies, CommentSectionName, QueryOrder, UserPermission };
+use crate::router::context::{ QueryHashMap, RouteContext, UserContext };
 use crate::ui_primitives::comment_group::CommentGroupTemplate;
 use crate::ui_primitives::comment::CommentTemplate;
 use crate::ui_primitives::pagination::PaginationTemplate;
@@ -32,6 +32,8 @@ where &'a Ctx: RouteContext + UserContext {
     section: &'a CommentSectionName,
     section_tag_id: Option<i32>,
     redirect_url_encoded: String,
+    is_top_sort: bool,
+    viewer: CommentViewer,
 }
 impl<'a, Ctx> CommentSectionTemplate<'a, Ctx>
 where &'a Ctx: RouteContext + UserContext {
@@ -41,11 +43,25 @@ where &'a Ctx: RouteContext + UserContext {
         let CommentSectionParams { context, section, section_tag_id, page_number } = params;
         let title = get_title(section);
 
-        let comment_count = database::get_comments_count(section, section_tag_id, -1).await?;
+        let is_top_sort = context.route_query().get("comments-sort").map(|sort| sort == "top").unwrap_or(false);
+        let query_order = if is_top_sort { &QueryOrder::Top } else { &QueryOrder::Desc };
+
+        let viewer_id = context.user().as_ref().map(|user| user.id);
+        let comment_count = database::get_comments_count(section, section_tag_id, -1, viewer_id).await?;
         let comments_with_replies = database::get_comments_in_range_with_replies(
-            (page_number - 1) * COMMENTS_PER_PAGE, COMMENTS_PER_PAGE, section, section_tag_id, -1
+            (page_number - 1) * COMMENTS_PER_PAGE, COMMENTS_PER_PAGE, section, section_tag_id, -1, query_order, viewer_id
         ).await?;
 
+        let viewer = match context.user() {
+            Some(user) => CommentViewer {
+                username: user.username.clone(),
+                can_like: user.permissions.contains(&UserPermission::CreateOwnComment),
+                can_delete_own: user.permissions.contains(&UserPermission::DeleteOwnComment),
+                can_delete_any: user.permissions.contains(&UserPermission::DeleteComment),
+            },
+            None => CommentViewer::default(),
+        };
+
         let profile_picture_filename = if let Some(user) = context.user() {
             let user = database::get_user_by_username(&user.username).await?;
             user.profile_picture_filename
@@ -66,6 +82,8 @@ where &'a Ctx: RouteContext + UserContext {
             section,
             section_tag_id,
             redirect_url_encoded,
+            is_top_sort,
+            viewer,
             pagination: PaginationTemplate::<Ctx> {
                 context,
                 current_page: page_number,
@@ -84,6 +102,24 @@ where &'a Ctx: RouteContext + UserContext {
         )
     }
 
+    fn create_sort_href(&self, sort: &str) -> String {
+        let mut query = self.pagination.context.route_query().clone();
+        query.remove("comments-page");
+        query.insert(String::from("comments-sort"), sort.to_string());
+        format!("?{}#page-comments", &query.to_query_string())
+    }
+
+}
+
+/**
+ * What the signed in member can do with each comment in the section.
+ */
+#[derive(Default)]
+struct CommentViewer {
+    username: String,
+    can_like: bool,
+    can_delete_own: bool,
+    can_delete_any: bool,
 }
 
 fn get_comment_group<'a>(
@@ -91,6 +127,7 @@ fn get_comment_group<'a>(
     section: &'a CommentSectionName,
     section_tag_id: &'a Option<i32>,
     redirect_url_encoded: &'a str,
+    viewer: &'a CommentViewer,
 ) -> CommentGroupTemplate<'a> {
     CommentGroupTemplate {
         comments: get_comments(
@@ -98,6 +135,7 @@ fn get_comment_group<'a>(
             section,
             section_tag_id,
             redirect_url_encoded,
+            viewer,
         ),
     }
 }
@@ -107,20 +145,33 @@ fn get_comments<'a>(
     section: &'a CommentSectionName,
     section_tag_id: &'a Option<i32>,
     redirect_url_encoded: &'a str,
+    viewer: &'a CommentViewer,
 ) -> Vec<CommentTemplate<'a>> {
     let mut comment_templates: Vec<CommentTemplate<'a>> = Vec::with_capacity(comments_with_replies.len());
     for comment_with_replies in comments_with_replies {
+        let comment = &comment_with_replies.comment;
+        let is_own_comment = !viewer.username.is_empty() && comment.username == viewer.username;
         comment_templates.push(
             CommentTemplate {
                 username: &comment_with_replies.comment.username,
                 profile_picture_filename: &comment_with_replies.comment.profile_picture_filename,
                 comment: &comment_with_replies.comment.comment,
                 post_time: &comment_with_replies.comment.post_time,
-                replies: get_comments(&comment_with_replies.replies, section, section_tag_id, redirect_url_encoded),
+                replies: get_comments(&comment_with_replies.replies, section, section_tag_id, redirect_url_encoded, viewer),
                 section: section,
                 section_tag_id: section_tag_id.unwrap_or_else(|| -1),
                 reply_id: comment_with_replies.comment.id,
                 redirect_url_encoded: redirect_url_encoded,
+                likes: comment_with_replies.comment.likes.unwrap_or(0),
+                viewer_has_liked: comment_with_replies.comment.viewer_has_liked,
+                can_like: viewer.can_like && !is_own_comment,
+                is_edited: comment.edit_time.is_some(),
+                can_view_edits: viewer.can_delete_any || is_own_comment,
+                is_deleted: comment.is_deleted,
+                is_hidden: comment.is_hidden(),
+                can_edit: viewer.can_delete_own && is_own_comment,
+                can_delete: viewer.can_delete_any || (viewer.can_delete_own && is_own_comment),
+                can_report: viewer.can_like && !is_own_comment,
             }
         );
     }
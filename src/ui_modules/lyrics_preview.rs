@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::marker::PhantomData;
+use askama::Template;
+
+use crate::database::LyricsLanguage;
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::combined_lyrics::CombinedLyricsTemplate;
+use crate::util::furigana::find_furigana_error;
+use crate::util::lyrics::find_lyrics_misalignment;
+use crate::util::romaji::{ check_romaji_lines, get_long_vowel_style };
+
+pub struct LyricsPreviewParams {
+    pub kanji: String,
+    pub romaji: String,
+    pub translation: String,
+    pub language: LyricsLanguage,
+}
+
+#[derive(Template)]
+#[template(path = "ui_modules/lyrics_preview.html")]
+pub struct LyricsPreviewTemplate<'a> {
+    phantom: PhantomData<&'a ()>,
+    misalignment_alert: Option<AlertTemplate<'a>>,
+    furigana_alert: Option<AlertTemplate<'a>>,
+    romaji_alert: Option<AlertTemplate<'a>>,
+    combined_lyrics: CombinedLyricsTemplate,
+}
+impl<'a> LyricsPreviewTemplate<'a> {
+    pub async fn new(
+        params: LyricsPreviewParams,
+    ) -> Result<LyricsPreviewTemplate<'a>, Box<dyn Error>> {
+        let LyricsPreviewParams { kanji, romaji, translation, language } = params;
+
+        let misalignment_alert = find_lyrics_misalignment(&kanji, &romaji, &translation, language.as_display()).map(|misalignment| {
+            AlertTemplate {
+                variant: "danger",
+                message_html: format!("<p>{}</p>", misalignment.message),
+            }
+        });
+        let furigana_alert = find_furigana_error(&kanji).map(|furigana_error| {
+            AlertTemplate {
+                variant: "danger",
+                message_html: format!("<p>{}</p>", furigana_error.message),
+            }
+        });
+        let mut combined_lyrics = CombinedLyricsTemplate::from_lyrics(&kanji, &romaji, &translation, language);
+
+        let romaji_warnings = check_romaji_lines(&kanji, &romaji, get_long_vowel_style());
+        let romaji_alert = if romaji_warnings.is_empty() {
+            None
+        } else {
+            let line_label = if romaji_warnings.len() == 1 { "line" } else { "lines" };
+            let line_numbers = romaji_warnings.iter()
+                .map(|warning| warning.line_number.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            Some(AlertTemplate {
+                variant: "info",
+                message_html: format!("<p>The rōmaji on {} {} looks different from the kana. It can still be submitted if it's intentional.</p>", line_label, line_numbers),
+            })
+        };
+        for warning in romaji_warnings {
+            if let Some(line) = combined_lyrics.lines.get_mut(warning.line_number - 1) {
+                line.expected_romaji = Some(warning.expected_romaji);
+            }
+        }
+
+        Ok(LyricsPreviewTemplate {
+            phantom: PhantomData,
+            misalignment_alert,
+            furigana_alert,
+            romaji_alert,
+            combined_lyrics,
+        })
+    }
+}
@@ -1,38 +1,88 @@
 use std::error::Error;
 use std::marker::PhantomData;
+use std::str::FromStr;
 use askama::Template;
+use urlencoding::encode;
 
-use crate::database::{ self, AlbumSearchResult, Band, SongSearchResult };
+use crate::database::{ self, AlbumSearchResult, Band, LyricsContentSearchResult, LyricsLanguage, SongSearchResult };
+use crate::router::context::RouteContext;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::lyrics::{ find_matching_line, LyricsMatchingLine };
 
-pub struct LyricsSearchResultsParams {
+const LYRICS_RESULTS_PER_PAGE: u32 = 10;
+
+pub struct LyricsSearchResultsParams<'a, Ctx>
+where &'a Ctx: RouteContext {
+    pub context: &'a Ctx,
     pub search: String,
+    pub page_number: u32,
 }
 
 #[derive(Template)]
 #[template(path = "ui_modules/lyrics_search_results.html")]
-pub struct LyricsSearchResultsTemplate<'a> {
+pub struct LyricsSearchResultsTemplate<'a, Ctx>
+where &'a Ctx: RouteContext {
     phantom: PhantomData<&'a ()>,
     search: String,
     songs: Vec<SongSearchResult>,
+    lyrics_results: Vec<(LyricsContentSearchResult, Option<LyricsMatchingLine>)>,
+    lyrics_results_count: u32,
+    lyrics_results_start: u32,
     albums: Vec<AlbumSearchResult>,
     bands: Vec<Band>,
+    pagination: Option<PaginationTemplate<'a, Ctx>>,
 }
-impl<'a> LyricsSearchResultsTemplate<'a> {
+impl<'a, Ctx> LyricsSearchResultsTemplate<'a, Ctx>
+where &'a Ctx: RouteContext {
     pub async fn new(
-        params: LyricsSearchResultsParams,
-    ) -> Result<LyricsSearchResultsTemplate<'a>, Box<dyn Error>> {
-        let LyricsSearchResultsParams { search } = params;
+        params: LyricsSearchResultsParams<'a, Ctx>,
+    ) -> Result<LyricsSearchResultsTemplate<'a, Ctx>, Box<dyn Error>> {
+        let LyricsSearchResultsParams { context, search, page_number } = params;
+        let page_number = page_number.max(1);
 
         let songs = database::find_songs_with_translations_by_name(&search).await?;
         let albums = database::find_albums_by_name(&search).await?;
         let bands = database::find_bands_by_name(&search).await?;
 
+        let lyrics_results_count = database::get_lyrics_content_search_count(&search).await?;
+        let lyrics_results_start = (page_number - 1) * LYRICS_RESULTS_PER_PAGE + 1;
+        let lyrics_results = database::find_lyrics_by_content(
+            &search, lyrics_results_start - 1, LYRICS_RESULTS_PER_PAGE,
+        ).await?
+            .into_iter()
+            .map(|result| {
+                let translation_name = LyricsLanguage::from_str(&result.translation_language_code)
+                    .map(|language| language.as_display())
+                    .unwrap_or_default();
+                let matching_line = find_matching_line(
+                    &result.kanji_content, &result.romaji_content, &result.translation_content, translation_name, &search,
+                );
+                (result, matching_line)
+            })
+            .collect();
+
+        let pagination = if lyrics_results_count > LYRICS_RESULTS_PER_PAGE {
+            Some(PaginationTemplate::<Ctx> {
+                context,
+                current_page: page_number,
+                page_count: (lyrics_results_count / LYRICS_RESULTS_PER_PAGE) + (if lyrics_results_count % LYRICS_RESULTS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "search-page",
+                hx_target: "main-article",
+            })
+        } else {
+            None
+        };
+
         Ok(LyricsSearchResultsTemplate {
             phantom: PhantomData,
             search,
             songs,
+            lyrics_results,
+            lyrics_results_count,
+            lyrics_results_start,
             albums,
             bands,
+            pagination,
         })
     }
 }
@@ -41,6 +91,26 @@ pub fn create_song_href(search_result: &SongSearchResult) -> String {
     format!("/lyrics/{}/{}/{}/", search_result.band_slug, search_result.album_slug, search_result.song_slug)
 }
 
+pub fn create_lyrics_href(search_result: &LyricsContentSearchResult) -> String {
+    let mut href = format!("/lyrics/{}/{}/{}/?contributor={}",
+        search_result.band_slug, search_result.album_slug, search_result.song_slug, encode(&search_result.username),
+    );
+    if !search_result.translation_language_code.is_empty() {
+        href.push_str(&format!("&language={}", encode(&search_result.translation_language_code)));
+    }
+    href
+}
+
+/**
+ * In the order of LyricsLanguage::all(), so the badges line up between results.
+ */
+pub fn get_translation_languages(search_result: &SongSearchResult) -> Vec<LyricsLanguage> {
+    let language_codes: Vec<&str> = search_result.translation_languages.split(',').collect();
+    LyricsLanguage::all().into_iter()
+        .filter(|language| language_codes.contains(&language.to_string().as_str()))
+        .collect()
+}
+
 pub fn create_album_href(search_result: &AlbumSearchResult) -> String {
     format!("/lyrics/{}/{}/", search_result.band_slug, search_result.album_slug)
 }
@@ -2,10 +2,11 @@ use std::error::Error;
 use std::marker::PhantomData;
 use askama::Template;
 
-use crate::database::{ self, User, UserGender };
+use crate::database::{ self, User, UserGender, UserPermission, UserPreference };
 use crate::router::context::{ RouteContext, UserContext };
 use crate::ui_primitives::alert::AlertTemplate;
 use crate::util::format::make_content_links;
+use crate::util::user::{ create_user_block_href, create_user_messages_href };
 
 pub struct MemberProfileParams<'a, Ctx>
 where &'a Ctx: RouteContext + UserContext {
@@ -20,6 +21,9 @@ where &'a Ctx: RouteContext + UserContext {
     phantom: PhantomData<&'a Ctx>,
     pub user: User,
     email_alert: Option<AlertTemplate<'a>>,
+    can_send_message: bool,
+    can_block: bool,
+    is_blocked: bool,
 }
 impl<'a, Ctx> MemberProfileTemplate<'a, Ctx>
 where &'a Ctx: RouteContext + UserContext {
@@ -43,10 +47,32 @@ where &'a Ctx: RouteContext + UserContext {
             )
         }
 
+        let mut is_blocked = false;
+        let can_block = match context.user() {
+            Some(viewer) => viewer.id != user.id,
+            None => false,
+        };
+        if can_block {
+            is_blocked = database::get_user_blocklist(context.user().as_ref().unwrap().id).await?.contains(&user.id);
+        }
+
+        let can_send_message = match context.user() {
+            Some(viewer) => {
+                viewer.id != user.id
+                    && !is_blocked
+                    && viewer.permissions.contains(&UserPermission::SendDms)
+                    && user.preferences.contains(&UserPreference::AllowDms)
+            },
+            None => false,
+        };
+
         Ok(MemberProfileTemplate {
             phantom: PhantomData,
             user,
             email_alert,
+            can_send_message,
+            can_block,
+            is_blocked,
         })
     }
 }
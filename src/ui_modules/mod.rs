@@ -4,6 +4,7 @@ pub mod album_list;
 pub mod chat_box;
 pub mod comment_section;
 pub mod lyrics_edit_bar;
+pub mod lyrics_preview;
 pub mod lyrics_search_results;
 pub mod member_list;
 pub mod member_profile;
@@ -13,6 +14,7 @@ pub mod photo_list;
 pub mod photo_view;
 pub mod photos_edit_bar;
 pub mod recent_translations;
+pub mod search_suggestions;
 pub mod sidebar;
 pub mod sign_in;
 pub mod site_activity;
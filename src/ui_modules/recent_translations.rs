@@ -2,33 +2,42 @@ use std::error::Error;
 use std::marker::PhantomData;
 use askama::Template;
 
-use crate::database::{ self, RecentLyricTranslation };
+use crate::database::{ self, LyricsLanguage, RecentLyricTranslation };
 
 pub struct RecentTranslationsParams {
     pub band_id: i32,
+    pub band_slug: String,
+    pub language: String,
 }
 
 #[derive(Template)]
 #[template(path = "ui_modules/recent_translations.html")]
 pub struct RecentTranslationsTemplate<'a> {
     phantom: PhantomData<&'a ()>,
+    band_slug: String,
+    languages: Vec<LyricsLanguage>,
+    selected_language: LyricsLanguage,
     translations: Vec<RecentLyricTranslation>,
 }
 impl<'a> RecentTranslationsTemplate<'a> {
     pub async fn new(
         params: RecentTranslationsParams,
     ) -> Result<RecentTranslationsTemplate<'a>, Box<dyn Error>> {
-        let RecentTranslationsParams { band_id } = params;
+        let RecentTranslationsParams { band_id, band_slug, language } = params;
 
-        let translations = database::get_recent_lyric_translations_by_band_id(band_id).await?;
+        let selected_language = LyricsLanguage::from_code(&language);
+        let translations = database::get_recent_lyric_translations_by_band_id(band_id, &selected_language).await?;
 
         Ok(RecentTranslationsTemplate {
             phantom: PhantomData,
+            band_slug,
+            languages: LyricsLanguage::all(),
+            selected_language,
             translations,
         })
     }
 }
 
 fn create_song_href(song: &RecentLyricTranslation) -> String {
-    format!("/lyrics/{}/{}/{}/", song.band_slug, song.album_slug, song.song_slug)
+    format!("/lyrics/{}/{}/{}/?language={}", song.band_slug, song.album_slug, song.song_slug, song.language_code)
 }
@@ -0,0 +1,28 @@
+use std::error::Error;
+use std::marker::PhantomData;
+use askama::Template;
+
+use crate::util::search_index::{ find_search_suggestions, SearchIndexEntry };
+
+pub struct SearchSuggestionsParams {
+    pub search: String,
+}
+
+#[derive(Template)]
+#[template(path = "ui_modules/search_suggestions.html")]
+pub struct SearchSuggestionsTemplate<'a> {
+    phantom: PhantomData<&'a ()>,
+    suggestions: Vec<SearchIndexEntry>,
+}
+impl<'a> SearchSuggestionsTemplate<'a> {
+    pub async fn new(
+        params: SearchSuggestionsParams,
+    ) -> Result<SearchSuggestionsTemplate<'a>, Box<dyn Error>> {
+        let SearchSuggestionsParams { search } = params;
+
+        Ok(SearchSuggestionsTemplate {
+            phantom: PhantomData,
+            suggestions: find_search_suggestions(&search).await,
+        })
+    }
+}
@@ -31,7 +31,7 @@ where &'a Ctx: RouteContext + UserContext {
         if context.is_signed_in() {
             account_summary = Some(AccountSummaryTemplate::new(AccountSummaryParams { context }).await?);
         } else {
-            sign_in = Some(SignInTemplate::new(SignInParams { context, status: 0, entered_username: "" }).await?);
+            sign_in = Some(SignInTemplate::new(SignInParams { context, status: 0, entered_username: "", two_factor_token: "" }).await?);
         }
         let chat_box = ChatBoxTemplate::new(ChatBoxParams { context, page_number: 1 }).await?;
 
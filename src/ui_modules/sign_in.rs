@@ -11,6 +11,7 @@ where &'a Ctx: RouteContext {
     pub context: &'a Ctx,
     pub status: u16,
     pub entered_username: &'a str,
+    pub two_factor_token: &'a str,
 }
 
 #[derive(Template)]
@@ -21,15 +22,20 @@ where &'a Ctx: RouteContext {
     alert: Option<AlertTemplate<'a>>,
     redirect_url_encoded: String,
     entered_username: &'a str,
+    two_factor_token: &'a str,
 }
 impl<'a, Ctx> SignInTemplate<'a, Ctx>
 where &'a Ctx: RouteContext {
     pub async fn new(
         params: SignInParams<'a, Ctx>,
     ) -> Result<SignInTemplate<'a, Ctx>, Box<dyn Error>> {
-        let SignInParams { context, status, entered_username } = params;
+        let SignInParams { context, status, entered_username, two_factor_token } = params;
 
         let alert = match status {
+            401 if !two_factor_token.is_empty() => Some(AlertTemplate {
+                variant: "danger",
+                message_html: String::from("The code is invalid or was already used. Please try again."),
+            }),
             401 => Some(AlertTemplate {
                 variant: "danger",
                 message_html: String::from("The username / password combination is invalid. Please try again."),
@@ -51,6 +57,7 @@ where &'a Ctx: RouteContext {
             alert,
             redirect_url_encoded: encode(redirect_url).to_string(),
             entered_username,
+            two_factor_token,
         })
     }
 }
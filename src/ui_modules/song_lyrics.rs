@@ -1,9 +1,14 @@
 use std::error::Error;
 use std::marker::PhantomData;
 use askama::Template;
+use urlencoding::encode;
 
-use crate::database::{ Lyrics, Song };
+use crate::database::{ Lyrics, LyricsLanguage, Song };
+use crate::ui_primitives::combined_lyrics::CombinedLyricsTemplate;
+use crate::util::furigana::{ parse_furigana, FuriganaSegment };
+use crate::util::lrc::parse_line_timestamps;
 use crate::util::user::create_user_profile_href;
+use crate::util::video::create_synchronized_video_embed_iframe;
 
 pub struct SongLyricsParams {
     pub album_name: String,
@@ -12,12 +17,7 @@ pub struct SongLyricsParams {
     pub band_slug: String,
     pub song: Song,
     pub lyrics: Option<Lyrics>,
-}
-
-pub struct CombinedLyricsLine {
-    pub kanji: String,
-    pub romaji: String,
-    pub english: String,
+    pub language: String,
 }
 
 #[derive(Template)]
@@ -30,27 +30,59 @@ pub struct SongLyricsTemplate<'a> {
     band_slug: String,
     pub song: Song,
     lyrics: Option<Lyrics>,
-    combined_lyrics: Vec<CombinedLyricsLine>,
+    languages: Vec<LyricsLanguage>,
+    selected_language: LyricsLanguage,
+    translation: String,
+    combined_lyrics: Option<CombinedLyricsTemplate>,
+    kanji_lines: Vec<Vec<FuriganaSegment>>,
+    has_furigana: bool,
+    has_timestamps: bool,
+    video_embed_html: String,
 }
 impl<'a> SongLyricsTemplate<'a> {
     pub async fn new(
         params: SongLyricsParams,
     ) -> Result<SongLyricsTemplate<'a>, Box<dyn Error>> {
-        let SongLyricsParams { album_name, album_slug, band_name, band_slug, lyrics, song } = params;
+        let SongLyricsParams { album_name, album_slug, band_name, band_slug, lyrics, song, language } = params;
+
+        let languages: Vec<LyricsLanguage> = match &lyrics {
+            Some(lyrics) => LyricsLanguage::all().into_iter()
+                .filter(|language| !lyrics.get_translation(language).is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+        let requested_language = LyricsLanguage::from_code(&language);
+        let selected_language = if languages.contains(&requested_language) {
+            requested_language
+        } else {
+            languages.first().cloned().unwrap_or_default()
+        };
+        let translation = lyrics.as_ref()
+            .map(|lyrics| lyrics.get_translation(&selected_language).to_string())
+            .unwrap_or_default();
 
-        let mut combined_lyrics: Vec<CombinedLyricsLine> = Vec::new();
-        if let Some(lyrics) = &lyrics {
-            let kanji_lines = lyrics.kanji_content.lines();
-            let romaji_lines = lyrics.romaji_content.lines();
-            let english_lines = lyrics.english_content.lines();
-            for (kanji, (romaji, english)) in kanji_lines.zip(romaji_lines.zip(english_lines)) {
-                combined_lyrics.push(CombinedLyricsLine {
-                    kanji: kanji.to_string(),
-                    romaji: romaji.to_string(),
-                    english: english.to_string(),
-                })
+        let kanji_lines: Vec<Vec<FuriganaSegment>> = match &lyrics {
+            Some(lyrics) => lyrics.kanji_content.lines().map(parse_furigana).collect(),
+            None => Vec::new(),
+        };
+        let has_furigana = kanji_lines.iter()
+            .flatten()
+            .any(|segment| matches!(segment, FuriganaSegment::Ruby(_)));
+        let mut has_timestamps = false;
+        let combined_lyrics = lyrics.as_ref().map(|lyrics| {
+            let mut combined_lyrics = CombinedLyricsTemplate::from_lyrics(
+                &lyrics.kanji_content, &lyrics.romaji_content, &translation, selected_language.clone(),
+            );
+            for (line, timestamp) in combined_lyrics.lines.iter_mut().zip(parse_line_timestamps(&lyrics.line_timestamps)) {
+                line.start_time_ms = timestamp;
+                has_timestamps = has_timestamps || timestamp.is_some();
             }
-        }
+            combined_lyrics
+        });
+        let video_embed_html = match &lyrics {
+            Some(lyrics) if has_timestamps => create_synchronized_video_embed_iframe(&lyrics.video_url),
+            _ => String::from(""),
+        };
 
         Ok(SongLyricsTemplate {
             phantom: PhantomData,
@@ -58,9 +90,40 @@ impl<'a> SongLyricsTemplate<'a> {
             album_slug,
             band_name,
             band_slug,
+            languages,
+            selected_language,
+            translation,
             combined_lyrics,
+            kanji_lines,
+            has_furigana,
+            has_timestamps,
+            video_embed_html,
             song,
             lyrics,
         })
     }
+
+    fn get_history_href(&self) -> String {
+        let contributor = self.lyrics.as_ref().map(|lyrics| lyrics.username.as_str()).unwrap_or("");
+        format!("/lyrics/{}/{}/{}/history/?contributor={}",
+            self.band_slug, self.album_slug, self.song.song_slug, encode(contributor),
+        )
+    }
+
+    /**
+     * "text" is "kanji", "romaji", or the code of a translation language, "format" is empty or "enhanced".
+     */
+    fn get_lrc_href(&self, text: &str, format: &str) -> String {
+        let contributor = self.lyrics.as_ref().map(|lyrics| lyrics.username.as_str()).unwrap_or("");
+        format!("/lyrics/{}/{}/{}/lyrics.lrc?contributor={}&text={}&format={}",
+            self.band_slug, self.album_slug, self.song.song_slug, encode(contributor), text, format,
+        )
+    }
+
+    fn get_language_href(&self, language: &LyricsLanguage) -> String {
+        let contributor = self.lyrics.as_ref().map(|lyrics| lyrics.username.as_str()).unwrap_or("");
+        format!("/lyrics/{}/{}/{}/?contributor={}&language={}",
+            self.band_slug, self.album_slug, self.song.song_slug, encode(contributor), language,
+        )
+    }
 }
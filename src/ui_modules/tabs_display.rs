@@ -1,8 +1,10 @@
 use std::error::Error;
 use std::marker::PhantomData;
 use askama::Template;
+use urlencoding::encode;
 
-use crate::database::{ self, SongTab, SongTabType };
+use crate::database::{ self, SongTab, SongTabType, TabRevision };
+use crate::util::diff::{ diff_lines, has_changes, pair_side_by_side, SideBySideDiffRow };
 use crate::util::format;
 use crate::util::user::create_user_profile_href;
 
@@ -13,6 +15,8 @@ pub struct TabsDisplayParams {
     pub song_slug: String,
     pub tab_type: String,
     pub contributor: String,
+    pub revision_id: i32,
+    pub can_restore: bool,
 }
 
 #[derive(Template)]
@@ -23,14 +27,20 @@ pub struct TabsDisplayTemplate<'a> {
     pub band_name: String,
     pub song_slug: String,
     pub song_name: String,
+    pub tab_type: String,
     pub contributor: String,
     pub tab: SongTab,
+    pub revisions: Vec<TabRevision>,
+    pub selected_revision: Option<TabRevision>,
+    pub revision_diff: Vec<SideBySideDiffRow>,
+    pub revision_has_changes: bool,
+    pub can_restore: bool,
 }
 impl<'a> TabsDisplayTemplate<'a> {
     pub async fn new(
         params: TabsDisplayParams,
     ) -> Result<TabsDisplayTemplate<'a>, Box<dyn Error>> {
-        let TabsDisplayParams { band_id, band_slug, band_name, song_slug, tab_type, contributor } = params;
+        let TabsDisplayParams { band_id, band_slug, band_name, song_slug, tab_type, contributor, revision_id, can_restore } = params;
 
         let song = database::get_song_by_slug_and_band_id(&song_slug, band_id).await?;
         let tab = database::get_song_tab_by_username_type_and_song_id(
@@ -38,15 +48,47 @@ impl<'a> TabsDisplayTemplate<'a> {
             &format::to_snake_case(&tab_type).parse::<SongTabType>().unwrap_or_else(|_| SongTabType::Unknown),
             song.id
         ).await?;
-        
+        let revisions = database::get_tab_revisions(tab.id).await?;
+
+        // A selected revision is compared to the one before it.
+        let mut selected_revision = None;
+        let mut revision_diff = Vec::new();
+        let mut revision_has_changes = false;
+        if let Some(selected_index) = revisions.iter().position(|revision| revision.id == revision_id) {
+            let empty_revision = TabRevision::default();
+            let previous_revision = revisions.get(selected_index + 1).unwrap_or(&empty_revision);
+            let lines = diff_lines(&previous_revision.tab_content, &revisions[selected_index].tab_content);
+            revision_has_changes = has_changes(&lines);
+            revision_diff = pair_side_by_side(lines);
+            selected_revision = Some(revisions[selected_index].clone());
+        }
+
         Ok(TabsDisplayTemplate {
             phantom: PhantomData,
             band_slug,
             band_name,
             song_slug,
             song_name: song.song_name,
+            tab_type,
             contributor,
             tab,
+            revisions,
+            selected_revision,
+            revision_diff,
+            revision_has_changes,
+            can_restore,
         })
     }
+
+    fn get_tab_href(&self) -> String {
+        format!("/tabs/{}/{}/{}/{}/", self.band_slug, self.song_slug, self.tab_type, encode(&self.contributor))
+    }
+
+    fn get_revision_href(&self, revision: &TabRevision) -> String {
+        format!("{}?revision={}", self.get_tab_href(), revision.id)
+    }
+
+    fn is_selected_revision(&self, revision: &TabRevision) -> bool {
+        self.selected_revision.as_ref().is_some_and(|selected_revision| selected_revision.id == revision.id)
+    }
 }
@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::io;
+use askama::Template;
+use garde::{ Report };
+use urlencoding::encode;
+
+use crate::database::{ self, Comment, CommentEdit, UserPermission };
+use crate::router::routes::comment_section::CommentEditPageContext;
+use crate::router::validation::report_has_field;
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::user::{ is_guest_user };
+
+struct CommentEditPageTemplateCommon<'a> {
+    validation_alert: Option<AlertTemplate<'a>>,
+    section: String,
+    section_tag_id: i32,
+    comment_id: i32,
+    comment: Option<Comment>,
+    comment_edits: Vec<CommentEdit>,
+    comment_entry: String,
+    can_edit: bool,
+    can_delete: bool,
+    can_view_edits: bool,
+    redirect_url: String,
+    redirect_url_encoded: String,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/comment_edit.html")]
+pub struct CommentEditPageTemplate<'a> {
+    active_page: &'a str,
+    content: CommentEditPageTemplateCommon<'a>,
+}
+impl<'a> CommentEditPageTemplate<'a> {
+    pub async fn new(context: &'a CommentEditPageContext) -> Result<CommentEditPageTemplate<'a>, Box<dyn Error>> {
+        let active_page: &str = "";
+
+        let content = create_common_params(context).await?;
+
+        Ok(CommentEditPageTemplate { active_page, content })
+    }
+}
+
+async fn create_common_params<'a>(context: &'a CommentEditPageContext) -> Result<CommentEditPageTemplateCommon<'a>, Box<dyn Error>> {
+    let validation_alert = get_validation_alert(&context.params.validation_report);
+
+    let comment = database::get_comment_by_id(context.params.comment_id).await.ok();
+
+    if let Some(comment) = &comment {
+        if comment.section.to_string() != context.params.section
+            || comment.section_tag_id.unwrap_or_else(|| -1) != context.params.section_tag_id {
+            return Err(
+                Box::new(
+                    io::Error::new(io::ErrorKind::Other, "Comment is not in this section.")
+                )
+            );
+        }
+    }
+
+    let (can_edit, can_delete, can_view_edits) = match (&context.user, &comment) {
+        (Some(user), Some(comment)) => {
+            let is_own_comment = comment.username == user.username;
+            let can_delete_any = user.permissions.contains(&UserPermission::DeleteComment);
+            let can_edit = is_own_comment
+                && user.permissions.contains(&UserPermission::DeleteOwnComment);
+            (can_edit, can_edit || can_delete_any, is_own_comment || can_delete_any)
+        },
+        _ => (false, false, false),
+    };
+
+    // Previous versions may hold text the author took back, so only the author and moderators see them.
+    let comment_edits = match &comment {
+        Some(comment) if can_view_edits => database::get_comment_edits(comment.id).await?,
+        _ => Vec::new(),
+    };
+
+    let comment_entry = if !context.params.comment.is_empty() {
+        context.params.comment.clone()
+    } else {
+        comment.as_ref().map(|comment| comment.comment.clone()).unwrap_or_default()
+    };
+
+    let current_url = context.route_original_uri.to_string();
+    let redirect_url = context.route_query
+        .get("redirect-to")
+        .unwrap_or(&current_url);
+
+    Ok(
+        CommentEditPageTemplateCommon {
+            validation_alert,
+            section: context.params.section.clone(),
+            section_tag_id: context.params.section_tag_id,
+            comment_id: context.params.comment_id,
+            comment,
+            comment_edits,
+            comment_entry,
+            can_edit,
+            can_delete,
+            can_view_edits,
+            redirect_url: redirect_url.to_string(),
+            redirect_url_encoded: encode(redirect_url).to_string(),
+        }
+    )
+}
+
+fn get_submit_action<'a>(content: &CommentEditPageTemplateCommon) -> String {
+    format!("/comment-section/{}/{}/{}/edit/?redirect-to={}",
+        content.section,
+        content.section_tag_id,
+        content.comment_id,
+        content.redirect_url_encoded,
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please notify the site admins if this continues to happen.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to use this form.</p>");
+            }
+            if report_has_field(report, "comment") {
+                message_html.push_str("<p>The comment you entered is either too long or contains invalid characters.</p>");
+            }
+            if report_has_field(report, "rate_limit") {
+                message_html.push_str("<p>You are submitting changes too quickly.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
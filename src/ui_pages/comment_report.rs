@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::io;
+use askama::Template;
+use garde::{ Report };
+use urlencoding::encode;
+
+use crate::database::{ self, Comment, CommentReportReason };
+use crate::router::routes::comment_section::CommentReportPageContext;
+use crate::router::validation::report_has_field;
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::user::{ is_guest_user };
+
+struct CommentReportPageTemplateCommon<'a> {
+    validation_alert: Option<AlertTemplate<'a>>,
+    is_signed_in: bool,
+    is_submitted: bool,
+    section: String,
+    section_tag_id: i32,
+    comment_id: i32,
+    comment: Option<Comment>,
+    reasons: Vec<CommentReportReason>,
+    reason: &'a str,
+    details: &'a str,
+    redirect_url: String,
+    redirect_url_encoded: String,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/comment_report.html")]
+pub struct CommentReportPageTemplate<'a> {
+    active_page: &'a str,
+    content: CommentReportPageTemplateCommon<'a>,
+}
+impl<'a> CommentReportPageTemplate<'a> {
+    pub async fn new(context: &'a CommentReportPageContext) -> Result<CommentReportPageTemplate<'a>, Box<dyn Error>> {
+        let active_page: &str = "";
+
+        let content = create_common_params(context).await?;
+
+        Ok(CommentReportPageTemplate { active_page, content })
+    }
+}
+
+async fn create_common_params<'a>(context: &'a CommentReportPageContext) -> Result<CommentReportPageTemplateCommon<'a>, Box<dyn Error>> {
+    let validation_alert = get_validation_alert(&context.params.validation_report, context.params.is_submitted);
+
+    let comment = database::get_comment_by_id(context.params.comment_id).await.ok()
+        .filter(|comment| !comment.is_hidden());
+
+    if let Some(comment) = &comment {
+        if comment.section.to_string() != context.params.section
+            || comment.section_tag_id.unwrap_or_else(|| -1) != context.params.section_tag_id {
+            return Err(
+                Box::new(
+                    io::Error::new(io::ErrorKind::Other, "Comment is not in this section.")
+                )
+            );
+        }
+    }
+
+    let current_url = context.route_original_uri.to_string();
+    let redirect_url = context.route_query
+        .get("redirect-to")
+        .unwrap_or(&current_url);
+
+    Ok(
+        CommentReportPageTemplateCommon {
+            validation_alert,
+            is_signed_in: context.user.is_some(),
+            is_submitted: context.params.is_submitted,
+            section: context.params.section.clone(),
+            section_tag_id: context.params.section_tag_id,
+            comment_id: context.params.comment_id,
+            comment,
+            reasons: CommentReportReason::all(),
+            reason: &context.params.reason,
+            details: &context.params.details,
+            redirect_url: redirect_url.to_string(),
+            redirect_url_encoded: encode(redirect_url).to_string(),
+        }
+    )
+}
+
+fn get_submit_action<'a>(content: &CommentReportPageTemplateCommon) -> String {
+    format!("/comment-section/{}/{}/{}/report/?redirect-to={}",
+        content.section,
+        content.section_tag_id,
+        content.comment_id,
+        content.redirect_url_encoded,
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, is_submitted: bool) -> Option<AlertTemplate<'a>> {
+    if is_submitted {
+        return Some(AlertTemplate {
+            variant: "success",
+            message_html: String::from("<p>Thanks for the report. A moderator will review the comment soon.</p>"),
+        });
+    }
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please notify the site admins if this continues to happen.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to use this form.</p>");
+            }
+            if report_has_field(report, "reason") {
+                message_html.push_str("<p>Please choose a reason for the report.</p>");
+            }
+            if report_has_field(report, "details") {
+                message_html.push_str("<p>The details you entered are too long.</p>");
+            }
+            if report_has_field(report, "rate_limit") {
+                message_html.push_str("<p>You are submitting reports too quickly.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,162 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, CommentReport, ReportedComment, COMMENT_VISIBILITY_HIDDEN };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::user::{ create_user_profile_href, is_guest_user };
+use crate::router::routes::comment_reports::{ CommentReportsPageContext };
+use crate::router::validation::report_has_field;
+
+const REPORTS_PER_PAGE: u32 = 10;
+const HIDDEN_COMMENTS_SHOWN: u32 = 50;
+
+struct ReportedCommentWithReports {
+    reported_comment: ReportedComment,
+    reports: Vec<CommentReport>,
+}
+
+struct CommentReportsTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    reported_comments: Vec<ReportedCommentWithReports>,
+    hidden_comments: Vec<ReportedComment>,
+    pagination: PaginationTemplate<'a, CommentReportsPageContext>,
+    current_page: u32,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/comment_reports.html")]
+pub struct CommentReportsPageTemplate<'a> {
+    active_page: &'a str,
+    content: CommentReportsTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, CommentReportsPageContext>,
+}
+impl<'a> CommentReportsPageTemplate<'a> {
+    pub async fn new(
+        context: &'a CommentReportsPageContext
+    ) -> Result<CommentReportsPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(CommentReportsPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/comment_reports.html", block = "page_content")]
+pub struct CommentReportsPageContentTemplate<'a> {
+    content: CommentReportsTemplateCommon<'a>,
+}
+impl<'a> CommentReportsPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a CommentReportsPageContext
+    ) -> Result<CommentReportsPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(CommentReportsPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_comment_href(reported_comment: &ReportedComment) -> String {
+    format!("/comment-section/{}/{}/{}/edit/?redirect-to=%2Fmoderation%2Fcomment-reports%2F",
+        reported_comment.section,
+        reported_comment.section_tag_id.unwrap_or(-1),
+        reported_comment.comment_id,
+    )
+}
+
+fn is_hidden(reported_comment: &ReportedComment) -> bool {
+    reported_comment.visibility.unwrap_or(0) == COMMENT_VISIBILITY_HIDDEN
+}
+
+async fn create_common_params<'a>(context: &'a CommentReportsPageContext) -> Result<CommentReportsTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+
+    let validation_alert = get_validation_alert(&context.params.validation_report, &context.params.success_action);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let current_page = context.params.reports_page.max(1);
+    let mut reported_comments: Vec<ReportedCommentWithReports> = Vec::new();
+    let mut hidden_comments: Vec<ReportedComment> = Vec::new();
+    let mut reported_comment_count: u32 = 0;
+    if has_access {
+        hidden_comments = database::get_hidden_comments(HIDDEN_COMMENTS_SHOWN).await?;
+        reported_comment_count = database::get_open_comment_report_count().await?;
+        for reported_comment in database::get_reported_comments_in_range(
+            (current_page - 1) * REPORTS_PER_PAGE, REPORTS_PER_PAGE,
+        ).await? {
+            let reports = database::get_open_comment_reports_by_comment_id(reported_comment.comment_id).await?;
+            reported_comments.push(ReportedCommentWithReports { reported_comment, reports });
+        }
+    }
+
+    Ok(
+        CommentReportsTemplateCommon {
+            has_access,
+            validation_alert,
+            reported_comments,
+            hidden_comments,
+            current_page,
+            pagination: PaginationTemplate::<CommentReportsPageContext> {
+                context,
+                current_page,
+                page_count: (reported_comment_count / REPORTS_PER_PAGE) + (if reported_comment_count % REPORTS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "reports-page",
+                hx_target: "main-article",
+            },
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, success_action: &str) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to moderate comments.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            let message_html = match success_action {
+                "dismiss" => "<p>The reports were dismissed.</p>",
+                "hide" => "<p>The comment is now hidden.</p>",
+                "unhide" => "<p>The comment is visible again.</p>",
+                "delete" => "<p>The comment was deleted.</p>",
+                _ => return None,
+            };
+            Some(AlertTemplate {
+                variant: "success",
+                message_html: String::from(message_html),
+            })
+        },
+    }
+}
@@ -48,7 +48,7 @@ async fn create_common_params<'a>(context: &'a CommentSectionPageContext) -> Res
 
     let mut allow_submit = true;
     if let Some(report) = &context.params.validation_report {
-        allow_submit = !report_has_field(report, "comment");
+        allow_submit = !report_has_field(report, "comment") && !report_has_field(report, "blocked");
     }
 
     let mut username = String::from("Guest");
@@ -79,6 +79,13 @@ async fn create_common_params<'a>(context: &'a CommentSectionPageContext) -> Res
     };
 
     if let Some(reply_to_comment) = &reply_to_comment {
+        if reply_to_comment.is_hidden() {
+            return Err(
+                Box::new(
+                    io::Error::new(io::ErrorKind::Other, "Comment is hidden.")
+                )
+            );
+        }
         if reply_to_comment.section.to_string() != context.params.section
             || reply_to_comment.section_tag_id.unwrap_or_else(|| -1) != context.params.section_tag_id {
             return Err(
@@ -138,6 +145,9 @@ fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>
             if report_has_field(report, "reply_comment_missing") {
                 message_html.push_str("<p>The comment you are replying to doesn't exist. Maybe it was deleted?</p>");
             }
+            if report_has_field(report, "blocked") {
+                message_html.push_str("<p>This member is not accepting comments from you.</p>");
+            }
             if report_has_field(report, "captcha_required") {
                 message_html.push_str("<p>Please fill out the captcha.</p>");
             }
@@ -112,6 +112,9 @@ fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>
             if report_has_field(report, "band_missing") {
                 message_html.push_str("<p>The specified band does not exist.</p>");
             }
+            if report_has_field(report, "albums_exist") {
+                message_html.push_str("<p>The band has albums, and cannot be deleted until all of its albums are deleted and removed from the deletion queue.</p>");
+            }
 
             Some(AlertTemplate {
                 variant: "danger",
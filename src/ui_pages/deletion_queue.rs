@@ -0,0 +1,144 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, QueuedDeletion, UserPermission };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::deletion_queue::{ DeletionQueuePageContext };
+use crate::router::validation::report_has_field;
+
+const DELETIONS_PER_PAGE: u32 = 20;
+
+struct DeletionQueueTemplateCommon<'a> {
+    has_access: bool,
+    can_approve: bool,
+    can_undo: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    queued_deletions: Vec<QueuedDeletion>,
+    pagination: PaginationTemplate<'a, DeletionQueuePageContext>,
+    current_page: u32,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/deletion_queue.html")]
+pub struct DeletionQueuePageTemplate<'a> {
+    active_page: &'a str,
+    content: DeletionQueueTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, DeletionQueuePageContext>,
+}
+impl<'a> DeletionQueuePageTemplate<'a> {
+    pub async fn new(
+        context: &'a DeletionQueuePageContext
+    ) -> Result<DeletionQueuePageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(DeletionQueuePageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/deletion_queue.html", block = "page_content")]
+pub struct DeletionQueuePageContentTemplate<'a> {
+    content: DeletionQueueTemplateCommon<'a>,
+}
+impl<'a> DeletionQueuePageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a DeletionQueuePageContext
+    ) -> Result<DeletionQueuePageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(DeletionQueuePageContentTemplate {
+            content,
+        })
+    }
+}
+
+async fn create_common_params<'a>(context: &'a DeletionQueuePageContext) -> Result<DeletionQueueTemplateCommon<'a>, Box<dyn Error>> {
+
+    let (can_approve, can_undo) = match &context.user {
+        Some(user) => (
+            user.permissions.contains(&UserPermission::ApproveQueuedDeletion),
+            user.permissions.contains(&UserPermission::UndoQueuedDeletion),
+        ),
+        None => (false, false),
+    };
+    let has_access: bool = can_approve || can_undo;
+
+    let validation_alert = get_validation_alert(&context.params.validation_report, &context.params.success_action);
+
+    let current_page = context.params.deletions_page.max(1);
+    let mut queued_deletions: Vec<QueuedDeletion> = Vec::new();
+    let mut queued_deletion_count: u32 = 0;
+    if has_access {
+        queued_deletion_count = database::get_pending_deletions_count().await?;
+        queued_deletions = database::get_pending_deletions_in_range(
+            (current_page - 1) * DELETIONS_PER_PAGE, DELETIONS_PER_PAGE,
+        ).await?;
+    }
+
+    Ok(
+        DeletionQueueTemplateCommon {
+            has_access,
+            can_approve,
+            can_undo,
+            validation_alert,
+            queued_deletions,
+            current_page,
+            pagination: PaginationTemplate::<DeletionQueuePageContext> {
+                context,
+                current_page,
+                page_count: (queued_deletion_count / DELETIONS_PER_PAGE) + (if queued_deletion_count % DELETIONS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "deletions-page",
+                hx_target: "main-article",
+            },
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, success_action: &str) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to review this deletion.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+            if report_has_field(report, "deletion") {
+                message_html.push_str("<p>This deletion was already reviewed.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            let message_html = match success_action {
+                "approve" => "<p>The content was permanently deleted.</p>",
+                "undo" => "<p>The content was restored.</p>",
+                _ => return None,
+            };
+            Some(AlertTemplate {
+                variant: "success",
+                message_html: String::from(message_html),
+            })
+        },
+    }
+}
@@ -6,7 +6,7 @@ use garde::{ Report };
 use crate::database::{ self, Band, AlbumType };
 use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
 use crate::ui_primitives::alert::AlertTemplate;
-use crate::router::routes::edit_album::{ EditAlbumPageContext };
+use crate::router::routes::edit_album::{ EditAlbumPageContext, MAX_ALBUM_DISCS, MAX_ALBUM_TRACKS };
 use crate::router::validation::report_has_field;
 
 struct SelectOption {
@@ -14,6 +14,15 @@ struct SelectOption {
     text: String,
 }
 
+#[derive(Clone)]
+struct TrackRow {
+    disc_number: String,
+    song_name: String,
+}
+
+const BLANK_TRACK_ROWS: usize = 10;
+const MIN_TRACK_ROWS: usize = 20;
+
 struct EditAlbumTemplateCommon<'a> {
     is_create: bool,
     has_access: bool,
@@ -31,7 +40,9 @@ struct EditAlbumTemplateCommon<'a> {
     release_year: String,
     release_month: String,
     release_day_of_month: String,
-    songs: Vec<String>,
+    tracks: Vec<TrackRow>,
+    max_tracks: usize,
+    max_discs: i32,
     temporary_cover_picture_filename: String,
     cover_picture_file_path: String,
 }
@@ -121,21 +132,24 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
 
     let (
         album_name, selected_album_type, publisher, release_year, release_month, release_day_of_month,
-        songs, temporary_cover_picture_filename, cover_picture_file_path,
+        mut tracks, temporary_cover_picture_filename, cover_picture_file_path,
     ) = if is_create || validation_alert.is_some() {
         let cover_picture_file_path = if !context.params.temporary_cover_picture_filename.is_empty() {
             format!("/assets/images/tmp/{}", &context.params.temporary_cover_picture_filename)
         } else {
             String::from("")
         };
-        let mut songs: Vec<String> = context.params.songs
+        let discs = context.params.discs.split(',').collect::<Vec<&str>>();
+        let tracks = context.params.songs
             .split(',')
             .map(|s| s.replace("%2C", ","))
-            .map(|song| {
-                song.to_string()
+            .enumerate()
+            .filter(|(_, song_name)| !song_name.is_empty())
+            .map(|(index, song_name)| TrackRow {
+                disc_number: discs.get(index).unwrap_or(&"1").to_string(),
+                song_name,
             })
             .collect::<Vec<_>>();
-        songs.resize(40, String::from(""));
         (
             context.params.album_name.clone(),
             context.params.album_type.clone(),
@@ -143,7 +157,7 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
             context.params.release_year.clone(),
             context.params.release_month.clone(),
             context.params.release_day_of_month.clone(),
-            songs,
+            tracks,
             context.params.temporary_cover_picture_filename.clone(),
             cover_picture_file_path,
         )
@@ -157,13 +171,13 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
             -1
         };
         let album = database::get_album_by_slug_and_band_id(&context.params.album, selected_band_id).await?;
-        let mut songs = database::get_song_slugs_by_ids(&album.song_ids()).await?
+        let tracks = database::get_album_track_listing(album.id).await?
             .into_iter()
-            .map(|song| {
-                song.song_name
+            .map(|track| TrackRow {
+                disc_number: track.disc_number.to_string(),
+                song_name: track.song_name,
             })
             .collect::<Vec<_>>();
-        songs.resize(40, String::from(""));
         (
             album.album_name,
             album.album_type.as_key().to_string(),
@@ -171,7 +185,7 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
             format!("{}", album.release_day.year()),
             format!("{}", album.release_day.month()),
             format!("{}", album.release_day.day()),
-            songs,
+            tracks,
             String::from(""),
             format!("/assets/images/album-covers/{}", album.cover_picture_filename),
         )
@@ -199,8 +213,13 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
     for day in 1..32 {
         days.push(SelectOption { value: day.to_string(), text: day.to_string() });
     }
-    
-    
+
+    // Leave some empty rows to add tracks to, continuing on the last disc.
+    let last_disc_number = tracks.last()
+        .map(|track| track.disc_number.clone())
+        .unwrap_or_else(|| String::from("1"));
+    let track_row_count = (tracks.len() + BLANK_TRACK_ROWS).max(MIN_TRACK_ROWS).min(MAX_ALBUM_TRACKS);
+    tracks.resize(track_row_count, TrackRow { disc_number: last_disc_number, song_name: String::from("") });
 
     Ok(
         EditAlbumTemplateCommon {
@@ -221,7 +240,9 @@ async fn create_common_params<'a>(context: &'a EditAlbumPageContext) -> Result<E
             release_year,
             release_month,
             release_day_of_month,
-            songs,
+            tracks,
+            max_tracks: MAX_ALBUM_TRACKS,
+            max_discs: MAX_ALBUM_DISCS,
             temporary_cover_picture_filename,
             cover_picture_file_path,
         }
@@ -260,6 +281,9 @@ fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>
             if report_has_field(report, "release_year") {
                 message_html.push_str("<p><strong>Release Year:</strong> Invalid year specified.</p>");
             }
+            if report_has_field(report, "songs") {
+                message_html.push_str(&format!("<p><strong>Tracks:</strong> An album can have at most {} tracks.</p>", MAX_ALBUM_TRACKS));
+            }
             if report_has_field(report, "temporary_cover_picture_filename") {
                 message_html.push_str("<p><strong>Cover Image:</strong> Please upload a jpeg or png file that is less than 6 megabytes large.</p>");
             }
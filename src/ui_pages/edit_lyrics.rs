@@ -2,11 +2,14 @@ use std::error::Error;
 use askama::Template;
 use garde::{ Report };
 
-use crate::database::{ self, AlbumSummary, Band, JoinedSongSlugs, Lyrics, Song };
+use crate::database::{ self, AlbumSummary, Band, JoinedSongSlugs, Lyrics, LyricsLanguage, Song };
 use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
 use crate::ui_primitives::alert::AlertTemplate;
-use crate::router::routes::edit_lyrics::{ EditLyricsPageContext };
-use crate::router::validation::report_has_field;
+use crate::router::routes::edit_lyrics::{ EditLyricsPageContext, FillRomajiPageContext, ImportLrcPageContext };
+use crate::router::validation::{ get_report_field_message, report_has_field };
+use crate::util::furigana::strip_furigana;
+use crate::util::lrc::{ format_line_timestamps, match_lrc_to_lines, parse_lrc };
+use crate::util::romaji::{ fill_romaji_lines, get_long_vowel_style };
 
 struct EditLyricsTemplateCommon<'a> {
     is_create: bool,
@@ -21,8 +24,13 @@ struct EditLyricsTemplateCommon<'a> {
     validation_alert: Option<AlertTemplate<'a>>,
     kanji: String,
     romaji: String,
-    english: String,
+    languages: Vec<LyricsLanguage>,
+    selected_language: LyricsLanguage,
+    translation: String,
     comment: String,
+    timestamps: String,
+    timestamps_alert: Option<AlertTemplate<'a>>,
+    video_url: String,
 }
 
 #[derive(Template)]
@@ -85,6 +93,97 @@ impl<'a> EditLyricsSelectBandAlbumSongTemplate<'a> {
     }
 }
 
+#[derive(Template)]
+#[template(path = "ui_pages/edit_lyrics.html", block = "translation_field")]
+pub struct EditLyricsTranslationFieldTemplate<'a> {
+    content: EditLyricsTemplateCommon<'a>,
+}
+impl<'a> EditLyricsTranslationFieldTemplate<'a> {
+    pub async fn new(
+        context: &'a EditLyricsPageContext
+    ) -> Result<EditLyricsTranslationFieldTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditLyricsTranslationFieldTemplate {
+            content,
+        })
+    }
+}
+
+struct EditLyricsRomajiFieldCommon {
+    romaji: String,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_lyrics.html", block = "romaji_field")]
+pub struct EditLyricsRomajiFieldTemplate {
+    content: EditLyricsRomajiFieldCommon,
+}
+impl EditLyricsRomajiFieldTemplate {
+    pub async fn new(
+        context: &FillRomajiPageContext
+    ) -> Result<EditLyricsRomajiFieldTemplate, Box<dyn Error>> {
+
+        let romaji = fill_romaji_lines(&context.params.kanji, &context.params.romaji, get_long_vowel_style());
+
+        Ok(EditLyricsRomajiFieldTemplate {
+            content: EditLyricsRomajiFieldCommon {
+                romaji,
+            },
+        })
+    }
+}
+
+struct EditLyricsTimestampsFieldCommon<'a> {
+    timestamps: String,
+    timestamps_alert: Option<AlertTemplate<'a>>,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_lyrics.html", block = "timestamps_field")]
+pub struct EditLyricsTimestampsFieldTemplate<'a> {
+    content: EditLyricsTimestampsFieldCommon<'a>,
+}
+impl<'a> EditLyricsTimestampsFieldTemplate<'a> {
+    pub async fn new(
+        context: &ImportLrcPageContext
+    ) -> Result<EditLyricsTimestampsFieldTemplate<'a>, Box<dyn Error>> {
+
+        let document = parse_lrc(&context.params.lrc);
+        let (timestamps, timestamps_alert) = if document.lines.is_empty() {
+            (
+                context.params.timestamps.to_string(),
+                Some(AlertTemplate {
+                    variant: "danger",
+                    message_html: String::from("<p>No timed lines were found. LRC lines start with a timestamp, like [01:23.45].</p>"),
+                }),
+            )
+        } else {
+            let (line_timestamps, extra_line_count) = match_lrc_to_lines(&document, &strip_furigana(&context.params.kanji));
+            let timestamps_alert = match extra_line_count {
+                0 => None,
+                extra_line_count if extra_line_count > 0 => Some(AlertTemplate {
+                    variant: "info",
+                    message_html: format!("<p>The LRC has {} more lines than the kanji, they were left out.</p>", extra_line_count),
+                }),
+                missing_line_count => Some(AlertTemplate {
+                    variant: "info",
+                    message_html: format!("<p>The LRC has {} fewer lines than the kanji, the last lines have no timestamps.</p>", -missing_line_count),
+                }),
+            };
+            (format_line_timestamps(&line_timestamps), timestamps_alert)
+        };
+
+        Ok(EditLyricsTimestampsFieldTemplate {
+            content: EditLyricsTimestampsFieldCommon {
+                timestamps,
+                timestamps_alert,
+            },
+        })
+    }
+}
+
 fn get_page_title<'a>(content: &EditLyricsTemplateCommon<'a>) -> &'a str {
     match content.is_create {
         true => "Create Lyrics",
@@ -101,6 +200,18 @@ fn get_submit_action<'a>(content: &EditLyricsTemplateCommon<'a>) -> String {
     );
 }
 
+/**
+ * Switching languages reloads the translation field with the saved translation in that language.
+ */
+fn get_translation_field_href<'a>(content: &EditLyricsTemplateCommon<'a>) -> String {
+    if content.is_create {
+        return format!("/editor/create/lyrics/{}/{}/", content.selected_band_slug, content.selected_album_slug);
+    }
+    return format!("/editor/update/lyrics/{}/{}/{}/",
+        content.selected_band_slug, content.selected_album_slug, content.selected_song_slug
+    );
+}
+
 fn create_cancel_href<'a>(content: &EditLyricsTemplateCommon<'a>) -> String {
     if content.is_create {
         if !content.selected_song_slug.is_empty() && !content.selected_album_slug.is_empty() {
@@ -164,12 +275,16 @@ async fn create_common_params<'a>(context: &'a EditLyricsPageContext) -> Result<
         }
     }
 
-    let (kanji, romaji, english, comment) = if is_create || validation_alert.is_some() {
+    let selected_language = LyricsLanguage::from_code(&context.params.language);
+
+    let (kanji, romaji, translation, comment, timestamps, video_url) = if is_create || validation_alert.is_some() {
         (
             context.params.kanji.to_string(),
             context.params.romaji.to_string(),
-            context.params.english.to_string(),
+            context.params.translation.to_string(),
             context.params.comment.to_string(),
+            context.params.timestamps.to_string(),
+            context.params.video_url.to_string(),
         )
     } else if context.user.is_some() {
         let Song { id: song_id, .. } = database::get_song_by_slug_and_band_id(&selected_song_slug, selected_band_id).await?;
@@ -177,11 +292,13 @@ async fn create_common_params<'a>(context: &'a EditLyricsPageContext) -> Result<
         (
             lyrics.kanji_content.to_string(),
             lyrics.romaji_content.to_string(),
-            lyrics.english_content.to_string(),
+            lyrics.get_translation(&selected_language).to_string(),
             lyrics.comment.to_string(),
+            lyrics.line_timestamps.to_string(),
+            lyrics.video_url.to_string(),
         )
     } else {
-        ("".to_string(), "".to_string(), "".to_string(), "".to_string())
+        ("".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string())
     };
 
     Ok(
@@ -198,8 +315,13 @@ async fn create_common_params<'a>(context: &'a EditLyricsPageContext) -> Result<
             no_songs_alert,
             kanji,
             romaji,
-            english,
+            languages: LyricsLanguage::all(),
+            selected_language,
+            translation,
             comment,
+            timestamps,
+            timestamps_alert: None,
+            video_url,
         }
     )
 }
@@ -224,14 +346,27 @@ fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>
             if report_has_field(report, "kanji") {
                 message_html.push_str("<p><strong>Kanji:</strong> This field is required.</p>");
             }
+            if let Some(message) = get_report_field_message(report, "furigana") {
+                message_html.push_str(&format!("<p><strong>Kanji:</strong> {} Readings are written in braces right after the kanji, like 漢字{{かんじ}}.</p>", message));
+            }
             if report_has_field(report, "romaji") {
                 message_html.push_str("<p><strong>Rōmaji:</strong> This field is required.</p>");
             }
-            if report_has_field(report, "english") {
-                message_html.push_str("<p><strong>English:</strong> This field is required.</p>");
+            if report_has_field(report, "language") {
+                message_html.push_str("<p><strong>Language:</strong> The selected language is not supported.</p>");
+            }
+            if report_has_field(report, "translation") {
+                message_html.push_str("<p><strong>Translation:</strong> This field is required.</p>");
+            }
+            if let Some(message) = get_report_field_message(report, "timestamps") {
+                message_html.push_str(&format!("<p><strong>Timestamps:</strong> {}</p>", message));
+            }
+            if let Some(message) = get_report_field_message(report, "video_url") {
+                message_html.push_str(&format!("<p><strong>Video:</strong> {}</p>", message));
             }
-            if report_has_field(report, "lines_mismatch") {
-                message_html.push_str("<p>Kanji, Rōmaji, and English must have the same number of lines. Please double check that the line numbers match up.</p>");
+            if let Some(message) = get_report_field_message(report, "lines_mismatch") {
+                message_html.push_str("<p>Kanji, Rōmaji, and the translation must have the same number of lines. Please double check that the line numbers match up.</p>");
+                message_html.push_str(&format!("<p>{}</p>", message));
             }
 
             Some(AlertTemplate {
@@ -0,0 +1,139 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database;
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::totp;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::edit_profile_2fa::{ EditProfile2faPageContext };
+use crate::router::validation::report_has_field;
+
+struct EditProfile2faTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    username: &'a str,
+    totp_enabled: bool,
+    secret_base32: String,
+    recovery_codes: &'a Vec<String>,
+    unused_recovery_code_count: u32,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_2fa.html")]
+pub struct EditProfile2faPageTemplate<'a> {
+    active_page: &'a str,
+    content: EditProfile2faTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, EditProfile2faPageContext>,
+}
+impl<'a> EditProfile2faPageTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfile2faPageContext
+    ) -> Result<EditProfile2faPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfile2faPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_2fa.html", block = "page_content")]
+pub struct EditProfile2faPageContentTemplate<'a> {
+    content: EditProfile2faTemplateCommon<'a>,
+}
+impl<'a> EditProfile2faPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfile2faPageContext
+    ) -> Result<EditProfile2faPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfile2faPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_cancel_href<'a>(content: &EditProfile2faTemplateCommon<'a>) -> String {
+    create_user_profile_href(content.username)
+}
+
+async fn create_common_params<'a>(context: &'a EditProfile2faPageContext) -> Result<EditProfile2faTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+    let username: &'a str = if let Some(user) = &context.user {
+        &user.username
+    } else {
+        ""
+    };
+
+    let validation_alert = get_validation_alert(&context.params.validation_report);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let mut totp_enabled = false;
+    let mut secret_base32 = String::from("");
+    let mut unused_recovery_code_count = 0;
+    if has_access {
+        let user = database::get_user_by_username(username).await?;
+        totp_enabled = user.totp_enabled;
+        if totp_enabled {
+            unused_recovery_code_count = database::get_unused_recovery_code_count(user.id).await?;
+        } else {
+            secret_base32 = totp::get_secret_base32(&totp::get_or_create_pending_enrollment(user.id));
+        }
+    }
+
+    Ok(
+        EditProfile2faTemplateCommon {
+            has_access,
+            validation_alert,
+            username,
+            totp_enabled,
+            secret_base32,
+            recovery_codes: &context.params.recovery_codes,
+            unused_recovery_code_count,
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to use this form.</p>");
+            }
+            if report_has_field(report, "current_password") {
+                message_html.push_str("<p><strong>Current Password:</strong> Your current password was not entered correctly.</p>");
+            }
+            if report_has_field(report, "code") {
+                message_html.push_str("<p><strong>Code:</strong> The code is invalid or was already used. Check that the time on your device is correct.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,118 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, UserSummary };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::edit_profile_blocklist::{ EditProfileBlocklistPageContext };
+use crate::router::validation::report_has_field;
+
+struct EditProfileBlocklistTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    username: &'a str,
+    blocked_users: Vec<UserSummary>,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_blocklist.html")]
+pub struct EditProfileBlocklistPageTemplate<'a> {
+    active_page: &'a str,
+    content: EditProfileBlocklistTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, EditProfileBlocklistPageContext>,
+}
+impl<'a> EditProfileBlocklistPageTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfileBlocklistPageContext
+    ) -> Result<EditProfileBlocklistPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfileBlocklistPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_blocklist.html", block = "page_content")]
+pub struct EditProfileBlocklistPageContentTemplate<'a> {
+    content: EditProfileBlocklistTemplateCommon<'a>,
+}
+impl<'a> EditProfileBlocklistPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfileBlocklistPageContext
+    ) -> Result<EditProfileBlocklistPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfileBlocklistPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_cancel_href<'a>(content: &EditProfileBlocklistTemplateCommon<'a>) -> String {
+    create_user_profile_href(content.username)
+}
+
+async fn create_common_params<'a>(context: &'a EditProfileBlocklistPageContext) -> Result<EditProfileBlocklistTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+    let username: &'a str = if let Some(user) = &context.user {
+        &user.username
+    } else {
+        ""
+    };
+
+    let validation_alert = get_validation_alert(&context.params.validation_report);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let mut blocked_users: Vec<UserSummary> = Vec::new();
+    if has_access {
+        blocked_users = database::get_blocked_users(context.user.as_ref().unwrap().id).await?;
+    }
+
+    Ok(
+        EditProfileBlocklistTemplateCommon {
+            has_access,
+            validation_alert,
+            username,
+            blocked_users,
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to use this form.</p>");
+            }
+            if report_has_field(report, "username") {
+                message_html.push_str("<p>That member doesn't exist.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,155 @@
+use std::error::Error;
+use askama::Template;
+use chrono::Utc;
+use garde::{ Report };
+
+use crate::database::{ self, SessionRecord };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::edit_profile_sessions::{ EditProfileSessionsPageContext };
+use crate::router::session_store::hash_session_id;
+use crate::router::validation::report_has_field;
+
+struct ActiveSession {
+    session: String,
+    is_current: bool,
+    record: SessionRecord,
+}
+
+struct EditProfileSessionsTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    username: &'a str,
+    sessions: Vec<ActiveSession>,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_sessions.html")]
+pub struct EditProfileSessionsPageTemplate<'a> {
+    active_page: &'a str,
+    content: EditProfileSessionsTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, EditProfileSessionsPageContext>,
+}
+impl<'a> EditProfileSessionsPageTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfileSessionsPageContext
+    ) -> Result<EditProfileSessionsPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfileSessionsPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/edit_profile_sessions.html", block = "page_content")]
+pub struct EditProfileSessionsPageContentTemplate<'a> {
+    content: EditProfileSessionsTemplateCommon<'a>,
+}
+impl<'a> EditProfileSessionsPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a EditProfileSessionsPageContext
+    ) -> Result<EditProfileSessionsPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(EditProfileSessionsPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_cancel_href<'a>(content: &EditProfileSessionsTemplateCommon<'a>) -> String {
+    create_user_profile_href(content.username)
+}
+
+fn or_unknown<'a>(value: &'a str) -> &'a str {
+    if value.is_empty() { "Unknown" } else { value }
+}
+
+async fn create_common_params<'a>(context: &'a EditProfileSessionsPageContext) -> Result<EditProfileSessionsTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+    let username: &'a str = if let Some(user) = &context.user {
+        &user.username
+    } else {
+        ""
+    };
+
+    let validation_alert = get_validation_alert(&context.params.validation_report, context.params.revoked_count);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let mut sessions: Vec<ActiveSession> = Vec::new();
+    if has_access {
+        let user_id = context.user.as_ref().unwrap().id;
+        sessions = database::get_session_records_by_user_id(user_id, Utc::now().timestamp())
+            .await?
+            .into_iter()
+            .map(|record| {
+                let session = hash_session_id(&record.id);
+                ActiveSession {
+                    is_current: session == context.params.current_session,
+                    session,
+                    record,
+                }
+            })
+            .collect();
+        sessions.sort_by_key(|session| !session.is_current);
+    }
+
+    Ok(
+        EditProfileSessionsTemplateCommon {
+            has_access,
+            validation_alert,
+            username,
+            sessions,
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, revoked_count: u64) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to use this form.</p>");
+            }
+            if report_has_field(report, "session") {
+                message_html.push_str("<p>That session can't be revoked. It may have already signed out.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            if revoked_count > 0 {
+                Some(AlertTemplate {
+                    variant: "success",
+                    message_html: format!("<p>Signed out of {} session{}.</p>", revoked_count, if revoked_count == 1 { "" } else { "s" }),
+                })
+            } else {
+                None
+            }
+        },
+    }
+}
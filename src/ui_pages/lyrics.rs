@@ -23,7 +23,7 @@ struct LyricsTemplateCommon<'a> {
     comment_section: Option<CommentSectionTemplate<'a, LyricsPageContext>>,
     lyrics_edit_bar: LyricsEditBarTemplate<'a, LyricsPageContext>,
     recent_translations: Option<RecentTranslationsTemplate<'a>>,
-    search_results: Option<LyricsSearchResultsTemplate<'a>>,
+    search_results: Option<LyricsSearchResultsTemplate<'a, LyricsPageContext>>,
     seo_title: String,
     song_lyrics: Option<SongLyricsTemplate<'a>>,
 }
@@ -41,7 +41,7 @@ pub struct LyricsTemplate<'a> {
     lyrics_edit_bar: LyricsEditBarTemplate<'a, LyricsPageContext>,
     needs_title_update: bool,
     recent_translations: Option<RecentTranslationsTemplate<'a>>,
-    search_results: Option<LyricsSearchResultsTemplate<'a>>,
+    search_results: Option<LyricsSearchResultsTemplate<'a, LyricsPageContext>>,
     seo_title: String,
     sidebar: SidebarTemplate<'a, LyricsPageContext>,
     song_list: SongListTemplate<'a>,
@@ -78,7 +78,7 @@ pub struct LyricsContentTemplate<'a> {
     lyrics_edit_bar: LyricsEditBarTemplate<'a, LyricsPageContext>,
     needs_title_update: bool,
     recent_translations: Option<RecentTranslationsTemplate<'a>>,
-    search_results: Option<LyricsSearchResultsTemplate<'a>>,
+    search_results: Option<LyricsSearchResultsTemplate<'a, LyricsPageContext>>,
     seo_title: String,
     song_list: SongListTemplate<'a>,
     song_lyrics: Option<SongLyricsTemplate<'a>>,
@@ -138,9 +138,11 @@ async fn create_common_params<'a>(context: &'a LyricsPageContext) -> Result<Lyri
 
     if !context.params.search.is_empty() {
         search_results = Some(
-            LyricsSearchResultsTemplate::new(
+            LyricsSearchResultsTemplate::<LyricsPageContext>::new(
                 LyricsSearchResultsParams {
+                    context,
                     search: context.params.search.clone(),
+                    page_number: context.params.search_page,
                 }
             ).await?
         );
@@ -178,6 +180,7 @@ async fn create_common_params<'a>(context: &'a LyricsPageContext) -> Result<Lyri
                 song, album_slug: album.album_slug, album_name: album.album_name,
                 band_slug: band_slug.clone(), band_name: band_name.clone(),
                 lyrics,
+                language: context.params.language.clone(),
             }).await?
         );
     } else if !context.params.album.is_empty() {
@@ -188,7 +191,11 @@ async fn create_common_params<'a>(context: &'a LyricsPageContext) -> Result<Lyri
         );
     } else {
         recent_translations = Some(
-            RecentTranslationsTemplate::new(RecentTranslationsParams { band_id }).await?
+            RecentTranslationsTemplate::new(RecentTranslationsParams {
+                band_id,
+                band_slug: band_slug.clone(),
+                language: context.params.language.clone(),
+            }).await?
         );
     }
 
@@ -0,0 +1,200 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+use urlencoding::encode;
+
+use crate::database::{ self, Lyrics, LyricsLanguage, LyricsRevision, Song, UserPermission };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::util::diff::{ diff_lines, has_changes, DiffLine };
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::lyrics_history::{ LyricsHistoryPageContext };
+use crate::router::validation::report_has_field;
+
+struct LyricsRevisionDiff {
+    title: &'static str,
+    lines: Vec<DiffLine>,
+    has_changes: bool,
+}
+
+struct LyricsHistoryTemplateCommon<'a> {
+    validation_alert: Option<AlertTemplate<'a>>,
+    band_name: String,
+    lyrics_href: String,
+    song: Option<Song>,
+    lyrics: Option<Lyrics>,
+    revisions: Vec<LyricsRevision>,
+    selected_revision_id: i32,
+    revision_diffs: Vec<LyricsRevisionDiff>,
+    can_rollback: bool,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/lyrics_history.html")]
+pub struct LyricsHistoryPageTemplate<'a> {
+    active_page: &'a str,
+    content: LyricsHistoryTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, LyricsHistoryPageContext>,
+}
+impl<'a> LyricsHistoryPageTemplate<'a> {
+    pub async fn new(
+        context: &'a LyricsHistoryPageContext
+    ) -> Result<LyricsHistoryPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "lyrics";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(LyricsHistoryPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/lyrics_history.html", block = "page_content")]
+pub struct LyricsHistoryPageContentTemplate<'a> {
+    content: LyricsHistoryTemplateCommon<'a>,
+}
+impl<'a> LyricsHistoryPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a LyricsHistoryPageContext
+    ) -> Result<LyricsHistoryPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(LyricsHistoryPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_revision_href(content: &LyricsHistoryTemplateCommon, revision: &LyricsRevision) -> String {
+    let contributor = content.lyrics.as_ref().map(|lyrics| lyrics.username.as_str()).unwrap_or("");
+    format!("{}history/?contributor={}&revision={}", content.lyrics_href, encode(contributor), revision.id)
+}
+
+fn get_contributor_lyrics_href(content: &LyricsHistoryTemplateCommon) -> String {
+    let contributor = content.lyrics.as_ref().map(|lyrics| lyrics.username.as_str()).unwrap_or("");
+    format!("{}?contributor={}", content.lyrics_href, encode(contributor))
+}
+
+fn get_submit_action(content: &LyricsHistoryTemplateCommon) -> String {
+    format!("{}history/", content.lyrics_href)
+}
+
+async fn create_common_params<'a>(context: &'a LyricsHistoryPageContext) -> Result<LyricsHistoryTemplateCommon<'a>, Box<dyn Error>> {
+    let validation_alert = get_validation_alert(&context.params.validation_report, context.params.is_rolled_back);
+
+    let band = database::get_band_by_slug(&context.params.band).await?;
+    let lyrics_href = format!("/lyrics/{}/{}/{}/", band.band_slug, context.params.album, context.params.song);
+    let song = database::get_song_by_slug_and_band_id(&context.params.song, band.id).await.ok();
+
+    let mut lyrics: Option<Lyrics> = None;
+    if let Some(song) = &song {
+        lyrics = if context.params.contributor.is_empty() {
+            database::get_lyrics_by_song_id(song.id).await?.into_iter().next()
+        } else {
+            database::get_lyrics_by_username_and_song_id(&context.params.contributor, song.id).await.ok()
+        };
+    }
+
+    let revisions = match &lyrics {
+        Some(lyrics) => database::get_lyrics_revisions(lyrics.id).await?,
+        None => Vec::new(),
+    };
+
+    // Each revision is compared to the one before it, the newest revision is shown by default.
+    let selected_index = revisions.iter()
+        .position(|revision| revision.id == context.params.revision_id)
+        .unwrap_or(0);
+    let mut selected_revision_id: i32 = -1;
+    let mut revision_diffs: Vec<LyricsRevisionDiff> = Vec::new();
+    if let Some(revision) = revisions.get(selected_index) {
+        selected_revision_id = revision.id;
+        let empty_revision = LyricsRevision::default();
+        let previous_revision = revisions.get(selected_index + 1).unwrap_or(&empty_revision);
+        let mut columns = vec![
+            ("Kanji", previous_revision.kanji_content.as_str(), revision.kanji_content.as_str()),
+            ("Rōmaji", previous_revision.romaji_content.as_str(), revision.romaji_content.as_str()),
+        ];
+        for language in LyricsLanguage::all() {
+            let (previous_content, revision_content) = (previous_revision.get_translation(&language), revision.get_translation(&language));
+            if !previous_content.is_empty() || !revision_content.is_empty() {
+                columns.push((language.as_display(), previous_content, revision_content));
+            }
+        }
+        if !previous_revision.line_timestamps.is_empty() || !revision.line_timestamps.is_empty() {
+            columns.push(("Timestamps", previous_revision.line_timestamps.as_str(), revision.line_timestamps.as_str()));
+        }
+        for (title, previous_content, revision_content) in columns {
+            let lines = diff_lines(previous_content, revision_content);
+            revision_diffs.push(LyricsRevisionDiff {
+                title,
+                has_changes: has_changes(&lines),
+                lines,
+            });
+        }
+    }
+
+    let can_rollback = match (&context.user, &lyrics) {
+        (Some(user), Some(lyrics)) => {
+            user.permissions.contains(&UserPermission::EditLyrics) || (
+                user.permissions.contains(&UserPermission::EditOwnLyrics)
+                && user.username == lyrics.username
+            )
+        },
+        _ => false,
+    };
+
+    Ok(
+        LyricsHistoryTemplateCommon {
+            validation_alert,
+            band_name: band.band_name,
+            lyrics_href,
+            song,
+            lyrics,
+            revisions,
+            selected_revision_id,
+            revision_diffs,
+            can_rollback,
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, is_rolled_back: bool) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to roll back these lyrics.</p>");
+            }
+            if report_has_field(report, "lyrics_missing") || report_has_field(report, "contributor") {
+                message_html.push_str("<p>The lyrics don't exist. Maybe they were deleted?</p>");
+            }
+            if report_has_field(report, "revision") {
+                message_html.push_str("<p>Choose an earlier revision to roll back to.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            if !is_rolled_back {
+                return None;
+            }
+            Some(AlertTemplate {
+                variant: "success",
+                message_html: String::from("<p>The lyrics were rolled back. The change was saved as a new revision.</p>"),
+            })
+        },
+    }
+}
@@ -0,0 +1,189 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, ConversationSummary, DirectMessage, UserPermission, UserPreference };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::format::make_content_links;
+use crate::util::user::{ create_user_messages_href, create_user_profile_href };
+use crate::router::routes::messages::{ MessagesPageContext };
+use crate::router::validation::{ create_simple_report, report_has_field };
+
+const CONVERSATIONS_PER_PAGE: u32 = 20;
+const MESSAGES_PER_PAGE: u32 = 30;
+
+struct MessagesTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    other_username: &'a str,
+    conversations: Vec<ConversationSummary>,
+    messages: Vec<DirectMessage>,
+    can_send: bool,
+    message: &'a str,
+    pagination: PaginationTemplate<'a, MessagesPageContext>,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/messages.html")]
+pub struct MessagesPageTemplate<'a> {
+    active_page: &'a str,
+    content: MessagesTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, MessagesPageContext>,
+}
+impl<'a> MessagesPageTemplate<'a> {
+    pub async fn new(
+        context: &'a MessagesPageContext
+    ) -> Result<MessagesPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(MessagesPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/messages.html", block = "page_content")]
+pub struct MessagesPageContentTemplate<'a> {
+    content: MessagesTemplateCommon<'a>,
+}
+impl<'a> MessagesPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a MessagesPageContext
+    ) -> Result<MessagesPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(MessagesPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_message_preview(conversation: &ConversationSummary) -> String {
+    let preview: String = conversation.last_message.chars().take(100).collect();
+    if preview.len() < conversation.last_message.len() {
+        format!("{}…", preview)
+    } else {
+        preview
+    }
+}
+
+async fn create_common_params<'a>(context: &'a MessagesPageContext) -> Result<MessagesTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+
+    let mut validation_alert = get_validation_alert(&context.params.validation_report);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let current_page = context.params.messages_page.max(1);
+    let mut conversations: Vec<ConversationSummary> = Vec::new();
+    let mut messages: Vec<DirectMessage> = Vec::new();
+    let mut can_send = false;
+    let mut item_count: u32 = 0;
+    let mut items_per_page: u32 = CONVERSATIONS_PER_PAGE;
+
+    if has_access {
+        let user = context.user.as_ref().unwrap();
+        if context.params.username.is_empty() {
+            item_count = database::get_conversations_count(user.id).await?;
+            conversations = database::get_conversations_in_range(
+                user.id, (current_page - 1) * CONVERSATIONS_PER_PAGE, CONVERSATIONS_PER_PAGE,
+            ).await?;
+        } else {
+            items_per_page = MESSAGES_PER_PAGE;
+            match database::get_user_by_username(&context.params.username).await {
+                Ok(other_user) => {
+                    if let Some(conversation_id) = database::get_conversation_id(user.id, other_user.id).await? {
+                        item_count = database::get_direct_messages_count(conversation_id).await?;
+                        messages = database::get_direct_messages_in_range(
+                            conversation_id, (current_page - 1) * MESSAGES_PER_PAGE, MESSAGES_PER_PAGE,
+                        ).await?;
+                        messages.reverse();
+                        if let Err(error) = database::mark_conversation_read(conversation_id, user.id).await {
+                            tracing::warn!("Failed to mark messages as read for user {}. {:?}", &user.username, error);
+                        }
+                    }
+                    can_send = user.permissions.contains(&UserPermission::SendDms)
+                        && other_user.id != user.id
+                        && other_user.preferences.contains(&UserPreference::AllowDms)
+                        && !database::is_either_user_blocked(user.id, other_user.id).await?;
+                    if !can_send && validation_alert.is_none() {
+                        validation_alert = Some(AlertTemplate {
+                            variant: "info",
+                            message_html: String::from("<p>You can't send messages to this member.</p>"),
+                        });
+                    }
+                },
+                Err(_) => {
+                    validation_alert = get_validation_alert(&Some(
+                        create_simple_report(String::from("recipient_missing"), String::from("Member not found."))
+                    ));
+                },
+            }
+        }
+    }
+
+    Ok(
+        MessagesTemplateCommon {
+            has_access,
+            validation_alert,
+            other_username: &context.params.username,
+            conversations,
+            messages,
+            can_send,
+            message: &context.params.message,
+            pagination: PaginationTemplate::<MessagesPageContext> {
+                context,
+                current_page,
+                page_count: (item_count / items_per_page) + (if item_count % items_per_page > 0 { 1 } else { 0 }),
+                page_query_name: "messages-page",
+                hx_target: "main-article",
+            },
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>Please sign in to send and read messages.</p>");
+            }
+            if report_has_field(report, "recipient_missing") {
+                message_html.push_str("<p>That member doesn't exist.</p>");
+            }
+            if report_has_field(report, "recipient_not_accepting") {
+                message_html.push_str("<p>This member is not accepting messages from you.</p>");
+            }
+            if report_has_field(report, "message") {
+                message_html.push_str("<p>The message is either empty or too long.</p>");
+            }
+            if report_has_field(report, "rate_limit") {
+                message_html.push_str("<p>You are sending messages too quickly.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
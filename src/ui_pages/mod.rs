@@ -1,5 +1,8 @@
 pub mod bio;
 pub mod chat_box;
+pub mod comment_edit;
+pub mod comment_report;
+pub mod comment_reports;
 pub mod comment_section;
 pub mod community_guidelines;
 pub mod delete_album;
@@ -10,14 +13,18 @@ pub mod delete_photo_album;
 pub mod delete_tabs;
 pub mod delete_video;
 pub mod delete_video_category;
+pub mod deletion_queue;
 pub mod edit_band;
 pub mod edit_album;
 pub mod edit_lyrics;
 pub mod edit_photo;
 pub mod edit_photo_album;
+pub mod edit_profile_2fa;
+pub mod edit_profile_blocklist;
 pub mod edit_profile_info;
 pub mod edit_profile_password;
 pub mod edit_profile_picture;
+pub mod edit_profile_sessions;
 pub mod edit_tabs;
 pub mod edit_video;
 pub mod edit_video_category;
@@ -25,14 +32,19 @@ pub mod forgot_password;
 pub mod home;
 pub mod lyrics;
 pub mod lyrics_booklet;
+pub mod lyrics_history;
 pub mod members;
+pub mod messages;
+pub mod notifications;
 pub mod page_not_found;
 pub mod photos;
 pub mod privacy_policy;
+pub mod search;
 pub mod sign_in;
 pub mod sign_up;
 pub mod tabs;
 pub mod terms_of_service;
+pub mod trash;
 pub mod videos;
 
 pub const PAGE_NAME_HOME: &str = "home";
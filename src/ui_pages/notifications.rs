@@ -0,0 +1,146 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, Notification, NotificationType };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::notifications::{ NotificationsPageContext };
+use crate::router::validation::report_has_field;
+
+const NOTIFICATIONS_PER_PAGE: u32 = 20;
+
+struct NotificationsTemplateCommon<'a> {
+    has_access: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    notifications: Vec<Notification>,
+    unread_count: u32,
+    pagination: PaginationTemplate<'a, NotificationsPageContext>,
+    current_page: u32,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/notifications.html")]
+pub struct NotificationsPageTemplate<'a> {
+    active_page: &'a str,
+    content: NotificationsTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, NotificationsPageContext>,
+}
+impl<'a> NotificationsPageTemplate<'a> {
+    pub async fn new(
+        context: &'a NotificationsPageContext
+    ) -> Result<NotificationsPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(NotificationsPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/notifications.html", block = "page_content")]
+pub struct NotificationsPageContentTemplate<'a> {
+    content: NotificationsTemplateCommon<'a>,
+}
+impl<'a> NotificationsPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a NotificationsPageContext
+    ) -> Result<NotificationsPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(NotificationsPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn get_notification_message(notification: &Notification) -> &'static str {
+    match notification.notification_type {
+        NotificationType::DirectMessage => "sent you a message.",
+        NotificationType::CommentReply => "replied to your comment.",
+        NotificationType::LyricPostComment => "commented on lyrics you posted.",
+        NotificationType::TabsComment => "commented on tabs you posted.",
+        NotificationType::PhotoComment => "commented on a photo you posted.",
+        NotificationType::VideoComment => "commented on a video you posted.",
+        NotificationType::ProfileComment => "commented on your profile.",
+    }
+}
+
+fn get_notification_href(notification: &Notification) -> String {
+    format!("/notifications/{}/", notification.id)
+}
+
+async fn create_common_params<'a>(context: &'a NotificationsPageContext) -> Result<NotificationsTemplateCommon<'a>, Box<dyn Error>> {
+
+    let mut has_access: bool = context.user.is_some();
+
+    let validation_alert = get_validation_alert(&context.params.validation_report);
+    if let Some(report) = &context.params.validation_report {
+        if report_has_field(report, "forbidden") {
+            has_access = false;
+        }
+    }
+
+    let current_page = context.params.notifications_page.max(1);
+    let mut notifications: Vec<Notification> = Vec::new();
+    let mut notification_count: u32 = 0;
+    let mut unread_count: u32 = 0;
+    if has_access {
+        let username = &context.user.as_ref().unwrap().username;
+        notification_count = database::get_notifications_count(username).await?;
+        unread_count = database::get_unread_notification_count(username).await?;
+        notifications = database::get_notifications_in_range(
+            username, (current_page - 1) * NOTIFICATIONS_PER_PAGE, NOTIFICATIONS_PER_PAGE,
+        ).await?;
+    }
+
+    Ok(
+        NotificationsTemplateCommon {
+            has_access,
+            validation_alert,
+            notifications,
+            unread_count,
+            current_page,
+            pagination: PaginationTemplate::<NotificationsPageContext> {
+                context,
+                current_page,
+                page_count: (notification_count / NOTIFICATIONS_PER_PAGE) + (if notification_count % NOTIFICATIONS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "notifications-page",
+                hx_target: "main-article",
+            },
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>Please sign in to see your notifications.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => None,
+    }
+}
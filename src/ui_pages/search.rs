@@ -0,0 +1,105 @@
+use std::error::Error;
+use askama::Template;
+use urlencoding::encode;
+
+use crate::database::{ self, SearchResultGroup, SearchResultType, SEARCH_RESULTS_PER_PAGE };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::router::routes::search::{ SearchPageContext };
+
+struct SearchTemplateCommon<'a> {
+    search: String,
+    result_type: Option<SearchResultType>,
+    result_types: Vec<SearchResultType>,
+    groups: Vec<SearchResultGroup>,
+    pagination: Option<PaginationTemplate<'a, SearchPageContext>>,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/search.html")]
+pub struct SearchPageTemplate<'a> {
+    active_page: &'a str,
+    content: SearchTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, SearchPageContext>,
+}
+impl<'a> SearchPageTemplate<'a> {
+    pub async fn new(
+        context: &'a SearchPageContext
+    ) -> Result<SearchPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(SearchPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/search.html", block = "page_content")]
+pub struct SearchPageContentTemplate<'a> {
+    content: SearchTemplateCommon<'a>,
+}
+impl<'a> SearchPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a SearchPageContext
+    ) -> Result<SearchPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(SearchPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn is_current_type(result_type: &SearchResultType, current_result_type: &Option<SearchResultType>) -> bool {
+    current_result_type.as_ref() == Some(result_type)
+}
+
+fn create_type_href(search: &str, result_type: &SearchResultType) -> String {
+    format!("/search/?q={}&type={}", encode(search), result_type)
+}
+
+fn create_all_types_href(search: &str) -> String {
+    format!("/search/?q={}", encode(search))
+}
+
+fn has_more_results(group: &SearchResultGroup) -> bool {
+    group.result_count as usize > group.results.len()
+}
+
+async fn create_common_params<'a>(context: &'a SearchPageContext) -> Result<SearchTemplateCommon<'a>, Box<dyn Error>> {
+    let search = context.params.search.trim().to_string();
+    let result_type = context.params.get_result_type();
+    let current_page = context.params.search_page.max(1);
+
+    let groups = database::find_search_result_groups(result_type.as_ref(), &search, current_page).await?;
+
+    let mut pagination = None;
+    if result_type.is_some() {
+        if let Some(group) = groups.first() {
+            pagination = Some(PaginationTemplate::<SearchPageContext> {
+                context,
+                current_page,
+                page_count: (group.result_count / SEARCH_RESULTS_PER_PAGE) + (if group.result_count % SEARCH_RESULTS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "search-page",
+                hx_target: "main-article",
+            });
+        }
+    }
+
+    Ok(
+        SearchTemplateCommon {
+            search,
+            result_type,
+            result_types: SearchResultType::all(),
+            groups,
+            pagination,
+        }
+    )
+}
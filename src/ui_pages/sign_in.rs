@@ -16,6 +16,7 @@ impl<'a> SignInTemplate<'a> {
             context,
             status: context.params.status,
             entered_username: &context.params.entered_username,
+            two_factor_token: &context.params.two_factor_token,
         }).await?;
         Ok(SignInTemplate { active_page, sign_in })
     }
@@ -32,6 +33,7 @@ impl<'a> SignInContentTemplate<'a> {
             context,
             status: context.params.status,
             entered_username: &context.params.entered_username,
+            two_factor_token: &context.params.two_factor_token,
         }).await?;
         Ok(SignInContentTemplate { sign_in })
     }
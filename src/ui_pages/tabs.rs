@@ -1,17 +1,21 @@
 use std::error::Error;
 use askama::Template;
+use garde::{ Report };
 
-use crate::database::{ self, Band, CommentSectionName, SongTabType };
+use crate::database::{ self, Band, CommentSectionName, SongTabType, UserPermission };
 use crate::ui_modules::comment_section::{ CommentSectionParams, CommentSectionTemplate };
 use crate::ui_modules::sidebar::{ SidebarParams, SidebarTemplate };
 use crate::ui_modules::tabs_display::{ TabsDisplayTemplate, TabsDisplayParams };
 use crate::ui_modules::tabs_edit_bar::{ TabsEditBarTemplate, TabsEditBarParams };
 use crate::ui_modules::tabs_song_detail::{ TabsSongDetailTemplate, TabsSongDetailParams };
 use crate::ui_modules::tabs_song_list::{ TabsSongListTemplate, TabsSongListParams };
+use crate::ui_primitives::alert::AlertTemplate;
 use crate::router::routes::tabs::{ TabsPageContext };
+use crate::router::validation::report_has_field;
 use crate::util::format;
 
 struct TabsTemplateCommon<'a> {
+    validation_alert: Option<AlertTemplate<'a>>,
     bands: Vec<Band>,
     band_name: String,
     seo_title: String,
@@ -71,6 +75,7 @@ impl<'a> TabsCommentsTemplate<'a> {
 }
 
 async fn create_common_params<'a>(context: &'a TabsPageContext) -> Result<TabsTemplateCommon<'a>, Box<dyn Error>> {
+    let validation_alert = get_validation_alert(&context.params.validation_report, context.params.is_restored);
     let bands = database::get_all_bands().await?;
 
     let mut band_id: i32 = 0;
@@ -92,6 +97,15 @@ async fn create_common_params<'a>(context: &'a TabsPageContext) -> Result<TabsTe
     };
 
     if !context.params.contributor.is_empty() {
+        let can_restore = match &context.user {
+            Some(user) => {
+                user.permissions.contains(&UserPermission::EditTabs) || (
+                    user.permissions.contains(&UserPermission::EditOwnTabs)
+                    && user.username == context.params.contributor
+                )
+            },
+            None => false,
+        };
         tabs_display = Some(
             TabsDisplayTemplate::new(
                 TabsDisplayParams {
@@ -101,6 +115,8 @@ async fn create_common_params<'a>(context: &'a TabsPageContext) -> Result<TabsTe
                     song_slug: context.params.song.clone(),
                     tab_type: context.params.tab_type.clone(),
                     contributor: context.params.contributor.clone(),
+                    revision_id: context.params.revision_id,
+                    can_restore,
                 }
             ).await?
         );
@@ -164,6 +180,7 @@ async fn create_common_params<'a>(context: &'a TabsPageContext) -> Result<TabsTe
 
     Ok(
         TabsTemplateCommon {
+            validation_alert,
             bands,
             band_name,
             seo_title,
@@ -175,3 +192,38 @@ async fn create_common_params<'a>(context: &'a TabsPageContext) -> Result<TabsTe
         }
     )
 }
+
+fn get_validation_alert<'a>(report: &Option<Report>, is_restored: bool) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to restore these tabs.</p>");
+            }
+            if report_has_field(report, "tabs_missing") {
+                message_html.push_str("<p>The tabs don't exist. Maybe they were deleted?</p>");
+            }
+            if report_has_field(report, "revision") {
+                message_html.push_str("<p>Choose an earlier revision to restore.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            if !is_restored {
+                return None;
+            }
+            Some(AlertTemplate {
+                variant: "success",
+                message_html: String::from("<p>The tabs were restored. The change was saved as a new revision.</p>"),
+            })
+        },
+    }
+}
@@ -0,0 +1,156 @@
+use std::error::Error;
+use askama::Template;
+use garde::{ Report };
+
+use crate::database::{ self, DeletionContentType, TrashedContent, UserPermission };
+use crate::ui_modules::sidebar::{ SidebarTemplate, SidebarParams };
+use crate::ui_primitives::alert::AlertTemplate;
+use crate::ui_primitives::pagination::PaginationTemplate;
+use crate::util::trash::get_trash_retention_days;
+use crate::util::user::create_user_profile_href;
+use crate::router::routes::trash::{ TrashPageContext };
+use crate::router::validation::report_has_field;
+
+const TRASH_ITEMS_PER_PAGE: u32 = 20;
+
+struct TrashTemplateCommon<'a> {
+    has_access: bool,
+    can_purge: bool,
+    can_restore: bool,
+    validation_alert: Option<AlertTemplate<'a>>,
+    content_type: DeletionContentType,
+    content_types: Vec<DeletionContentType>,
+    trashed_content: Vec<TrashedContent>,
+    retention_days: u32,
+    pagination: PaginationTemplate<'a, TrashPageContext>,
+    current_page: u32,
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/trash.html")]
+pub struct TrashPageTemplate<'a> {
+    active_page: &'a str,
+    content: TrashTemplateCommon<'a>,
+    sidebar: SidebarTemplate<'a, TrashPageContext>,
+}
+impl<'a> TrashPageTemplate<'a> {
+    pub async fn new(
+        context: &'a TrashPageContext
+    ) -> Result<TrashPageTemplate<'a>, Box<dyn Error>> {
+        let active_page = "";
+        let sidebar = SidebarTemplate::new(SidebarParams { context }).await?;
+
+        let content = create_common_params(context).await?;
+
+        Ok(TrashPageTemplate {
+            active_page,
+            content,
+            sidebar,
+        })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_pages/trash.html", block = "page_content")]
+pub struct TrashPageContentTemplate<'a> {
+    content: TrashTemplateCommon<'a>,
+}
+impl<'a> TrashPageContentTemplate<'a> {
+    pub async fn new(
+        context: &'a TrashPageContext
+    ) -> Result<TrashPageContentTemplate<'a>, Box<dyn Error>> {
+
+        let content = create_common_params(context).await?;
+
+        Ok(TrashPageContentTemplate {
+            content,
+        })
+    }
+}
+
+fn is_current_type(content_type: &DeletionContentType, current_content_type: &DeletionContentType) -> bool {
+    content_type == current_content_type
+}
+
+async fn create_common_params<'a>(context: &'a TrashPageContext) -> Result<TrashTemplateCommon<'a>, Box<dyn Error>> {
+
+    let (can_purge, can_restore) = match &context.user {
+        Some(user) => (
+            user.permissions.contains(&UserPermission::ApproveQueuedDeletion),
+            user.permissions.contains(&UserPermission::UndoQueuedDeletion),
+        ),
+        None => (false, false),
+    };
+    let has_access: bool = can_purge || can_restore;
+
+    let validation_alert = get_validation_alert(&context.params.validation_report, &context.params.success_action);
+
+    let content_type = context.params.content_type.parse::<DeletionContentType>().unwrap_or_default();
+    let current_page = context.params.trash_page.max(1);
+    let mut trashed_content: Vec<TrashedContent> = Vec::new();
+    let mut trashed_content_count: u32 = 0;
+    if has_access {
+        trashed_content_count = database::get_trashed_content_count(&content_type).await?;
+        trashed_content = database::get_trashed_content_in_range(
+            &content_type, (current_page - 1) * TRASH_ITEMS_PER_PAGE, TRASH_ITEMS_PER_PAGE,
+        ).await?;
+    }
+
+    Ok(
+        TrashTemplateCommon {
+            has_access,
+            can_purge,
+            can_restore,
+            validation_alert,
+            content_type,
+            content_types: DeletionContentType::all(),
+            trashed_content,
+            retention_days: get_trash_retention_days(),
+            current_page,
+            pagination: PaginationTemplate::<TrashPageContext> {
+                context,
+                current_page,
+                page_count: (trashed_content_count / TRASH_ITEMS_PER_PAGE) + (if trashed_content_count % TRASH_ITEMS_PER_PAGE > 0 { 1 } else { 0 }),
+                page_query_name: "trash-page",
+                hx_target: "main-article",
+            },
+        }
+    )
+}
+
+fn get_validation_alert<'a>(report: &Option<Report>, success_action: &str) -> Option<AlertTemplate<'a>> {
+    match report {
+        Some(report) => {
+            let mut message_html: String = "".to_owned();
+
+            if report_has_field(report, "server_error") {
+                message_html.push_str("<p>A system error occurred. Please try again later.</p>");
+            }
+            if report_has_field(report, "forbidden") {
+                message_html.push_str("<p>You do not have sufficient permissions to manage deleted content.</p>");
+            }
+            if report_has_field(report, "action") {
+                message_html.push_str("<p>Unknown action.</p>");
+            }
+            if report_has_field(report, "content_type") {
+                message_html.push_str("<p>Unknown content type.</p>");
+            }
+
+            Some(AlertTemplate {
+                variant: "danger",
+                message_html,
+            })
+        },
+        _ => {
+            let message_html = match success_action {
+                "restore" => "<p>The content was restored.</p>",
+                "purge" => "<p>The content was permanently deleted.</p>",
+                _ => return None,
+            };
+            Some(AlertTemplate {
+                variant: "success",
+                message_html: String::from(message_html),
+            })
+        },
+    }
+}
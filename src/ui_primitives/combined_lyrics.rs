@@ -0,0 +1,63 @@
+use askama::Template;
+
+use crate::database::LyricsLanguage;
+use crate::util::furigana::{ parse_furigana, FuriganaSegment };
+
+pub struct CombinedLyricsLine {
+    pub kanji: String,
+    pub kanji_segments: Vec<FuriganaSegment>,
+    pub romaji: String,
+    pub translation: String,
+    pub is_misaligned: bool,
+    pub expected_romaji: Option<String>,
+    pub start_time_ms: Option<u32>,
+}
+impl CombinedLyricsLine {
+    pub fn is_stanza_break(&self) -> bool {
+        self.kanji.trim().is_empty() && self.romaji.trim().is_empty() && self.translation.trim().is_empty()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "ui_primitives/combined_lyrics.html")]
+pub struct CombinedLyricsTemplate {
+    pub lines: Vec<CombinedLyricsLine>,
+    pub language: LyricsLanguage,
+}
+impl CombinedLyricsTemplate {
+    /**
+     * Columns with fewer lines are padded with blank lines, so nothing is left out.
+     */
+    pub fn from_lyrics(kanji: &str, romaji: &str, translation: &str, language: LyricsLanguage) -> CombinedLyricsTemplate {
+        let kanji_lines: Vec<&str> = kanji.lines().collect();
+        let romaji_lines: Vec<&str> = romaji.lines().collect();
+        let translation_lines: Vec<&str> = translation.lines().collect();
+        let line_count = kanji_lines.len().max(romaji_lines.len()).max(translation_lines.len());
+
+        let mut lines: Vec<CombinedLyricsLine> = Vec::with_capacity(line_count);
+        for index in 0..line_count {
+            let columns = [kanji_lines.get(index), romaji_lines.get(index), translation_lines.get(index)];
+            let is_misaligned = columns.iter().any(|line| is_blank_line(*line))
+                && !columns.iter().all(|line| is_blank_line(*line));
+            let [kanji, romaji, translation] = columns.map(|line| line.unwrap_or(&"").to_string());
+            lines.push(CombinedLyricsLine {
+                kanji_segments: parse_furigana(&kanji),
+                kanji,
+                romaji,
+                translation,
+                is_misaligned,
+                expected_romaji: None,
+                start_time_ms: None,
+            });
+        }
+
+        CombinedLyricsTemplate { lines, language }
+    }
+}
+
+fn is_blank_line(line: Option<&&str>) -> bool {
+    match line {
+        Some(line) => line.trim().is_empty(),
+        None => true,
+    }
+}
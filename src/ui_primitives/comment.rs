@@ -2,6 +2,7 @@ use askama::Template;
 use chrono::NaiveDateTime;
 
 use crate::database::{ CommentSectionName };
+use crate::ui_primitives::comment_likes::CommentLikesTemplate;
 use crate::util::format::make_content_links;
 use crate::util::user::{ create_user_profile_href, is_guest_user };
 
@@ -17,6 +18,16 @@ pub struct CommentTemplate<'a> {
     pub section_tag_id: i32,
     pub reply_id: i32,
     pub redirect_url_encoded: &'a str,
+    pub likes: i32,
+    pub viewer_has_liked: bool,
+    pub can_like: bool,
+    pub is_edited: bool,
+    pub can_view_edits: bool,
+    pub is_deleted: bool,
+    pub is_hidden: bool,
+    pub can_edit: bool,
+    pub can_delete: bool,
+    pub can_report: bool,
 }
 impl<'a> CommentTemplate<'a> {
     fn get_reply_href(&self) -> String {
@@ -26,5 +37,35 @@ impl<'a> CommentTemplate<'a> {
             self.reply_id,
             self.redirect_url_encoded,
         )
-    }   
+    }
+
+    fn get_edit_href(&self) -> String {
+        format!("/comment-section/{}/{}/{}/edit/?redirect-to={}%23page-comments",
+            self.section,
+            self.section_tag_id,
+            self.reply_id,
+            self.redirect_url_encoded,
+        )
+    }
+
+    fn get_report_href(&self) -> String {
+        format!("/comment-section/{}/{}/{}/report/?redirect-to={}%23page-comments",
+            self.section,
+            self.section_tag_id,
+            self.reply_id,
+            self.redirect_url_encoded,
+        )
+    }
+
+    fn get_likes(&self) -> CommentLikesTemplate<'a> {
+        CommentLikesTemplate {
+            section: self.section,
+            section_tag_id: self.section_tag_id,
+            comment_id: self.reply_id,
+            likes: self.likes,
+            viewer_has_liked: self.viewer_has_liked,
+            can_like: self.can_like,
+            redirect_url_encoded: self.redirect_url_encoded,
+        }
+    }
 }
@@ -0,0 +1,25 @@
+use askama::Template;
+
+use crate::database::{ CommentSectionName };
+
+#[derive(Template)]
+#[template(path = "ui_primitives/comment_likes.html")]
+pub struct CommentLikesTemplate<'a> {
+    pub section: &'a CommentSectionName,
+    pub section_tag_id: i32,
+    pub comment_id: i32,
+    pub likes: i32,
+    pub viewer_has_liked: bool,
+    pub can_like: bool,
+    pub redirect_url_encoded: &'a str,
+}
+impl<'a> CommentLikesTemplate<'a> {
+    fn get_like_href(&self) -> String {
+        format!("/comment-section/{}/{}/{}/like/?redirect-to={}",
+            self.section,
+            self.section_tag_id,
+            self.comment_id,
+            self.redirect_url_encoded,
+        )
+    }
+}
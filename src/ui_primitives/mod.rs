@@ -1,6 +1,8 @@
 pub mod alert;
 pub mod captcha;
+pub mod combined_lyrics;
 pub mod comment;
 pub mod comment_group;
+pub mod comment_likes;
 pub mod pagination;
 pub mod tabs;
\ No newline at end of file
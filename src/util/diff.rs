@@ -0,0 +1,279 @@
+/**
+ * Line-level diffs between two versions of a text, for revision histories.
+ * Uses the longest common subsequence of lines, after trimming the lines both
+ * versions start and end with, which keeps the table small for typical edits.
+ */
+
+/**
+ * Largest LCS table built for one diff, about 1 MB. When the changed lines of both versions
+ * would need more, they are shown as all removed and then all added instead.
+ */
+const DIFF_MAX_TABLE_CELLS: usize = 250_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+    pub old_line_number: Option<usize>,
+    pub new_line_number: Option<usize>,
+}
+impl DiffLine {
+    pub fn is_added(&self) -> bool {
+        self.kind == DiffLineKind::Added
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.kind == DiffLineKind::Removed
+    }
+}
+
+/**
+ * One row of a two column diff, either side is empty where a line only exists in the other version.
+ */
+#[derive(Clone, Debug)]
+pub struct SideBySideDiffRow {
+    pub old: Option<DiffLine>,
+    pub new: Option<DiffLine>,
+}
+
+pub fn diff_lines(old_text: &str, new_text: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old_text.lines().collect();
+    let new_lines: Vec<&str> = new_text.lines().collect();
+
+    let mut prefix_length = 0;
+    while prefix_length < old_lines.len() && prefix_length < new_lines.len()
+        && old_lines[prefix_length] == new_lines[prefix_length] {
+        prefix_length += 1;
+    }
+    let mut suffix_length = 0;
+    while suffix_length < old_lines.len() - prefix_length && suffix_length < new_lines.len() - prefix_length
+        && old_lines[old_lines.len() - 1 - suffix_length] == new_lines[new_lines.len() - 1 - suffix_length] {
+        suffix_length += 1;
+    }
+
+    let old_middle = &old_lines[prefix_length..old_lines.len() - suffix_length];
+    let new_middle = &new_lines[prefix_length..new_lines.len() - suffix_length];
+
+    let is_table_too_large = (old_middle.len() + 1).saturating_mul(new_middle.len() + 1) > DIFF_MAX_TABLE_CELLS;
+
+    // common_lengths[i][j] is the LCS length of old_middle[i..] and new_middle[j..].
+    let mut common_lengths: Vec<Vec<u32>> = Vec::new();
+    if !is_table_too_large {
+        common_lengths = vec![vec![0u32; new_middle.len() + 1]; old_middle.len() + 1];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                common_lengths[i][j] = if old_middle[i] == new_middle[j] {
+                    common_lengths[i + 1][j + 1] + 1
+                } else {
+                    common_lengths[i + 1][j].max(common_lengths[i][j + 1])
+                };
+            }
+        }
+    }
+
+    let mut diff: Vec<DiffLine> = Vec::with_capacity(old_lines.len().max(new_lines.len()));
+    for (index, line) in old_lines.iter().take(prefix_length).enumerate() {
+        diff.push(unchanged_line(line, index, index));
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if !is_table_too_large && i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            diff.push(unchanged_line(old_middle[i], prefix_length + i, prefix_length + j));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (is_table_too_large || j == new_middle.len() || common_lengths[i + 1][j] >= common_lengths[i][j + 1]) {
+            diff.push(DiffLine {
+                kind: DiffLineKind::Removed,
+                text: old_middle[i].to_string(),
+                old_line_number: Some(prefix_length + i + 1),
+                new_line_number: None,
+            });
+            i += 1;
+        } else {
+            diff.push(DiffLine {
+                kind: DiffLineKind::Added,
+                text: new_middle[j].to_string(),
+                old_line_number: None,
+                new_line_number: Some(prefix_length + j + 1),
+            });
+            j += 1;
+        }
+    }
+
+    for index in 0..suffix_length {
+        diff.push(unchanged_line(
+            old_lines[old_lines.len() - suffix_length + index],
+            old_lines.len() - suffix_length + index,
+            new_lines.len() - suffix_length + index,
+        ));
+    }
+
+    diff
+}
+
+pub fn has_changes(diff: &[DiffLine]) -> bool {
+    diff.iter().any(|line| line.kind != DiffLineKind::Unchanged)
+}
+
+/**
+ * Lines removed and added in the same place are put on the same row, so an edited line
+ * can be compared with the line it replaced.
+ */
+pub fn pair_side_by_side(diff: Vec<DiffLine>) -> Vec<SideBySideDiffRow> {
+    let mut rows: Vec<SideBySideDiffRow> = Vec::with_capacity(diff.len());
+    let mut removed_lines: Vec<DiffLine> = Vec::new();
+    let mut added_lines: Vec<DiffLine> = Vec::new();
+
+    for line in diff {
+        match line.kind {
+            DiffLineKind::Removed => removed_lines.push(line),
+            DiffLineKind::Added => added_lines.push(line),
+            DiffLineKind::Unchanged => {
+                push_changed_rows(&mut rows, &mut removed_lines, &mut added_lines);
+                rows.push(SideBySideDiffRow {
+                    old: Some(line.clone()),
+                    new: Some(line),
+                });
+            },
+        }
+    }
+    push_changed_rows(&mut rows, &mut removed_lines, &mut added_lines);
+
+    rows
+}
+
+fn push_changed_rows(
+    rows: &mut Vec<SideBySideDiffRow>,
+    removed_lines: &mut Vec<DiffLine>,
+    added_lines: &mut Vec<DiffLine>,
+) {
+    let mut removed_lines = removed_lines.drain(..);
+    let mut added_lines = added_lines.drain(..);
+    loop {
+        let old = removed_lines.next();
+        let new = added_lines.next();
+        if old.is_none() && new.is_none() {
+            break;
+        }
+        rows.push(SideBySideDiffRow { old, new });
+    }
+}
+
+fn unchanged_line(text: &str, old_index: usize, new_index: usize) -> DiffLine {
+    DiffLine {
+        kind: DiffLineKind::Unchanged,
+        text: text.to_string(),
+        old_line_number: Some(old_index + 1),
+        new_line_number: Some(new_index + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(diff: &[DiffLine]) -> Vec<DiffLineKind> {
+        diff.iter().map(|line| line.kind.clone()).collect()
+    }
+
+    #[test]
+    fn unchanged_text_has_no_changes() {
+        let diff = diff_lines("a\nb\nc", "a\nb\nc");
+        assert!(!has_changes(&diff));
+        assert_eq!(diff.len(), 3);
+    }
+
+    #[test]
+    fn common_prefix_and_suffix_are_kept_with_line_numbers() {
+        let diff = diff_lines("a\nb\nc\nd", "a\nx\nc\nd");
+        assert_eq!(kinds(&diff), vec![
+            DiffLineKind::Unchanged,
+            DiffLineKind::Removed,
+            DiffLineKind::Added,
+            DiffLineKind::Unchanged,
+            DiffLineKind::Unchanged,
+        ]);
+        assert_eq!(diff[1].text, "b");
+        assert_eq!(diff[1].old_line_number, Some(2));
+        assert_eq!(diff[2].text, "x");
+        assert_eq!(diff[2].new_line_number, Some(2));
+        assert_eq!(diff[4].old_line_number, Some(4));
+        assert_eq!(diff[4].new_line_number, Some(4));
+    }
+
+    #[test]
+    fn added_lines_at_the_end_follow_the_prefix() {
+        let diff = diff_lines("a\nb", "a\nb\nc");
+        assert_eq!(kinds(&diff), vec![DiffLineKind::Unchanged, DiffLineKind::Unchanged, DiffLineKind::Added]);
+        assert_eq!(diff[2].new_line_number, Some(3));
+    }
+
+    #[test]
+    fn lines_common_to_the_middle_are_unchanged() {
+        let diff = diff_lines("start\na\nb\nc\nend", "start\nb\nc\nd\nend");
+        assert_eq!(kinds(&diff), vec![
+            DiffLineKind::Unchanged,
+            DiffLineKind::Removed,
+            DiffLineKind::Unchanged,
+            DiffLineKind::Unchanged,
+            DiffLineKind::Added,
+            DiffLineKind::Unchanged,
+        ]);
+    }
+
+    #[test]
+    fn over_the_table_cap_everything_is_removed_then_added() {
+        let old_text = (0..600).map(|index| format!("old {}", index)).collect::<Vec<String>>().join("\n");
+        let new_text = (0..600).map(|index| if index == 300 { String::from("old 300") } else { format!("new {}", index) })
+            .collect::<Vec<String>>().join("\n");
+        let diff = diff_lines(&old_text, &new_text);
+        assert_eq!(diff.len(), 1200);
+        assert!(diff[..600].iter().all(|line| line.is_removed()));
+        assert!(diff[600..].iter().all(|line| line.is_added()));
+    }
+
+    #[test]
+    fn over_the_table_cap_prefix_and_suffix_are_still_trimmed() {
+        let middle_old = (0..600).map(|index| format!("old {}", index)).collect::<Vec<String>>().join("\n");
+        let middle_new = (0..600).map(|index| format!("new {}", index)).collect::<Vec<String>>().join("\n");
+        let diff = diff_lines(
+            &format!("first\n{}\nlast", middle_old),
+            &format!("first\n{}\nlast", middle_new),
+        );
+        assert_eq!(diff.first().unwrap().kind, DiffLineKind::Unchanged);
+        assert_eq!(diff.last().unwrap().kind, DiffLineKind::Unchanged);
+        assert_eq!(diff.last().unwrap().old_line_number, Some(602));
+    }
+
+    #[test]
+    fn side_by_side_pairs_removed_and_added_lines() {
+        let rows = pair_side_by_side(diff_lines("a\nb\nc\nd", "a\nx\ny\nd"));
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].old.as_ref().unwrap().text, "a");
+        assert_eq!(rows[0].new.as_ref().unwrap().text, "a");
+        assert_eq!(rows[1].old.as_ref().unwrap().text, "b");
+        assert_eq!(rows[1].new.as_ref().unwrap().text, "x");
+        assert_eq!(rows[2].old.as_ref().unwrap().text, "c");
+        assert_eq!(rows[2].new.as_ref().unwrap().text, "y");
+        assert_eq!(rows[3].old.as_ref().unwrap().text, "d");
+    }
+
+    #[test]
+    fn side_by_side_leaves_a_side_empty_for_uneven_changes() {
+        let rows = pair_side_by_side(diff_lines("a\nb\nd", "a\nx\ny\nd"));
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1].old.as_ref().unwrap().text, "b");
+        assert_eq!(rows[1].new.as_ref().unwrap().text, "x");
+        assert!(rows[2].old.is_none());
+        assert_eq!(rows[2].new.as_ref().unwrap().text, "y");
+    }
+}
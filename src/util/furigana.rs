@@ -0,0 +1,210 @@
+/**
+ * Furigana annotations in kanji lyrics, written as the reading in braces right after
+ * the kanji it belongs to, such as 漢字{かんじ}. The annotation covers the kanji
+ * directly before the opening brace, kana before it is left as plain text.
+ */
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuriganaRuby {
+    pub base: String,
+    pub reading: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FuriganaSegment {
+    Text(String),
+    Ruby(FuriganaRuby),
+}
+
+#[allow(unused)]
+pub struct FuriganaError {
+    pub line_number: usize,
+    pub message: String,
+}
+
+/**
+ * Annotations that aren't closed, or don't follow kanji, are kept as plain text.
+ */
+pub fn parse_furigana(line: &str) -> Vec<FuriganaSegment> {
+    let mut segments: Vec<FuriganaSegment> = Vec::new();
+    let mut text = String::new();
+    let mut rest = line;
+
+    while let Some(open_index) = rest.find('{') {
+        let (before, after) = rest.split_at(open_index);
+        text.push_str(before);
+
+        let base_start = find_base_start(&text);
+        let close_index = after.find('}');
+        match close_index {
+            Some(close_index) if base_start < text.len() && close_index > 1 && !after[1..close_index].contains('{') => {
+                let base = text.split_off(base_start);
+                if !text.is_empty() {
+                    segments.push(FuriganaSegment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(FuriganaSegment::Ruby(FuriganaRuby {
+                    base,
+                    reading: after[1..close_index].trim().to_string(),
+                }));
+                rest = &after[close_index + 1..];
+            },
+            _ => {
+                text.push('{');
+                rest = &after[1..];
+            },
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(FuriganaSegment::Text(text));
+    }
+
+    segments
+}
+
+/**
+ * The kanji lyrics with each annotated kanji replaced by its reading.
+ */
+pub fn furigana_to_reading(text: &str) -> String {
+    map_furigana_lines(text, |segment| match segment {
+        FuriganaSegment::Text(text) => text,
+        FuriganaSegment::Ruby(ruby) => ruby.reading,
+    })
+}
+
+/**
+ * The kanji lyrics with the annotations removed.
+ */
+pub fn strip_furigana(text: &str) -> String {
+    map_furigana_lines(text, |segment| match segment {
+        FuriganaSegment::Text(text) => text,
+        FuriganaSegment::Ruby(ruby) => ruby.base,
+    })
+}
+
+pub fn find_furigana_error(text: &str) -> Option<FuriganaError> {
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut text_before = String::new();
+        let mut open_reading: Option<String> = None;
+
+        for character in line.chars() {
+            match (character, &mut open_reading) {
+                ('{', Some(_)) => {
+                    return Some(create_furigana_error(line_number, "starts a reading inside of another reading"));
+                },
+                ('{', None) => {
+                    if find_base_start(&text_before) == text_before.len() {
+                        return Some(create_furigana_error(line_number, "has a reading in braces that doesn't follow kanji"));
+                    }
+                    open_reading = Some(String::new());
+                },
+                ('}', Some(reading)) => {
+                    if reading.trim().is_empty() {
+                        return Some(create_furigana_error(line_number, "has an empty reading in braces"));
+                    }
+                    open_reading = None;
+                    text_before.clear();
+                },
+                ('}', None) => {
+                    return Some(create_furigana_error(line_number, "has a closing brace without an opening brace"));
+                },
+                (character, Some(reading)) => reading.push(character),
+                (character, None) => text_before.push(character),
+            }
+        }
+
+        if open_reading.is_some() {
+            return Some(create_furigana_error(line_number, "has an opening brace without a closing brace"));
+        }
+    }
+    None
+}
+
+fn create_furigana_error(line_number: usize, problem: &str) -> FuriganaError {
+    FuriganaError {
+        line_number,
+        message: format!("Line {} of the kanji {}.", line_number, problem),
+    }
+}
+
+/**
+ * Byte index where the run of kanji at the end of the text starts.
+ */
+fn find_base_start(text: &str) -> usize {
+    let mut base_start = text.len();
+    for (index, character) in text.char_indices().rev() {
+        if !is_furigana_base(character) {
+            break;
+        }
+        base_start = index;
+    }
+    base_start
+}
+
+fn is_furigana_base(character: char) -> bool {
+    matches!(character, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '々' | '〆' | 'ヶ')
+}
+
+fn map_furigana_lines(text: &str, map_segment: fn(FuriganaSegment) -> String) -> String {
+    text.lines()
+        .map(|line| parse_furigana(line).into_iter().map(map_segment).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ruby(base: &str, reading: &str) -> FuriganaSegment {
+        FuriganaSegment::Ruby(FuriganaRuby {
+            base: String::from(base),
+            reading: String::from(reading),
+        })
+    }
+
+    fn text(text: &str) -> FuriganaSegment {
+        FuriganaSegment::Text(String::from(text))
+    }
+
+    #[test]
+    fn annotates_the_kanji_before_the_braces() {
+        assert_eq!(parse_furigana("夜空{よぞら}を見上{みあ}げる"), vec![
+            ruby("夜空", "よぞら"),
+            text("を"),
+            ruby("見上", "みあ"),
+            text("げる"),
+        ]);
+    }
+
+    #[test]
+    fn kana_before_the_kanji_is_plain_text() {
+        assert_eq!(parse_furigana("この街{まち}"), vec![text("この"), ruby("街", "まち")]);
+    }
+
+    #[test]
+    fn unbalanced_or_misplaced_braces_are_plain_text() {
+        assert_eq!(parse_furigana("空{そら"), vec![text("空{そら")]);
+        assert_eq!(parse_furigana("そら{sky}"), vec![text("そら{sky}")]);
+        assert_eq!(parse_furigana("空{}"), vec![text("空{}")]);
+        assert_eq!(parse_furigana("空{そ{ら}"), vec![text("空{そ{ら}")]);
+    }
+
+    #[test]
+    fn reads_and_strips_annotations_line_by_line() {
+        let kanji = "夜空{よぞら}を\n見{み}る";
+        assert_eq!(furigana_to_reading(kanji), "よぞらを\nみる");
+        assert_eq!(strip_furigana(kanji), "夜空を\n見る");
+    }
+
+    #[test]
+    fn finds_unbalanced_annotations() {
+        assert!(find_furigana_error("夜空{よぞら}を\n見{み}る").is_none());
+        assert_eq!(find_furigana_error("一\n空{そら").unwrap().line_number, 2);
+        assert!(find_furigana_error("空そら}").unwrap().message.contains("closing brace without an opening brace"));
+        assert!(find_furigana_error("空{そ{ら}}").unwrap().message.contains("inside of another reading"));
+        assert!(find_furigana_error("そら{sky}").unwrap().message.contains("doesn't follow kanji"));
+        assert!(find_furigana_error("空{ }").unwrap().message.contains("empty reading"));
+    }
+}
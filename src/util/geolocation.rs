@@ -1,54 +1,399 @@
 /**
- * This determines an imprecise geolocation based on IP address, via ip-api.com.
+ * This determines an imprecise geolocation based on IP address.
  * It is used to implement geo-fencing to protect from user session hijacking.
+ *
+ * The lookup is done by a provider configured in secrets.toml, either a local
+ * IP range database file (CSV or MMDB) or the ip-api.com web service. Results
+ * are kept in an in-memory LRU cache so that the same IP isn't looked up again
+ * on every request.
  */
 
+use std::collections::BTreeMap;
 use std::f64::consts::PI;
+use std::fs;
 use std::io;
 use std::error::Error;
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{ Duration, Instant };
+use async_trait::async_trait;
+use lru::LruCache;
+use maxminddb::{ geoip2, Reader };
 use reqwest::Client;
 use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+pub static GEOLOCATION_PROVIDER: OnceCell<Box<dyn GeolocationProvider>> = OnceCell::const_new();
+pub static GEO_FENCE_RADIUS_KM: OnceCell<f64> = OnceCell::const_new();
 
 #[derive(Deserialize)]
-pub struct IpApiResponse {
-    pub query: String,
-    pub lat: f64,
-    pub lon: f64,
-    pub timezone: String,
+struct SecretsConfig {
+    #[serde(default)]
+    geolocation: SecretsConfigGeolocation,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct SecretsConfigGeolocation {
+    provider: String,
+    database_path: String,
+    geo_fence_radius_km: f64,
+    cache_capacity: usize,
+    cache_ttl_seconds: u64,
+}
+impl Default for SecretsConfigGeolocation {
+    fn default() -> Self {
+        SecretsConfigGeolocation {
+            provider: String::from("ip-api"),
+            database_path: String::from(""),
+            geo_fence_radius_km: 100.0,
+            cache_capacity: 10000,
+            cache_ttl_seconds: 86400,
+        }
+    }
 }
 
 #[allow(unused)]
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Geolocation {
     pub ip: String,
     pub latitude: f64,
     pub longitude: f64,
     pub timezone: String,
+    pub city: String,
+    pub region: String,
+    pub country: String,
+}
+impl Geolocation {
+    /**
+     * Human readable location, such as "Aomori, Aomori, Japan".
+     */
+    pub fn describe(&self) -> String {
+        [&self.city, &self.region, &self.country]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| part.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
 }
 
-pub async fn find(ip: &str) -> Result<Geolocation, Box<dyn Error>>{
-    if ip == "" {
-        return Err(Box::new(io::Error::new(io::ErrorKind::Other, "IP Address Not Provided.")))
+#[async_trait]
+pub trait GeolocationProvider: Send + Sync {
+    async fn find(&self, ip: IpAddr) -> Result<Geolocation, Box<dyn Error + Send + Sync>>;
+}
+
+fn not_found_error(message: &str) -> Box<dyn Error + Send + Sync> {
+    Box::new(io::Error::new(io::ErrorKind::NotFound, message.to_string()))
+}
+
+/**
+ * Looks up locations with the ip-api.com web service. This sends every IP
+ * address to a third party, so prefer a local database file when possible.
+ */
+
+#[derive(Deserialize)]
+pub struct IpApiResponse {
+    pub query: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: String,
+    #[serde(default)]
+    pub city: String,
+    #[serde(default, rename = "regionName")]
+    pub region_name: String,
+    #[serde(default)]
+    pub country: String,
+}
+
+#[derive(Default)]
+pub struct IpApiGeolocationProvider {
+    client: Client,
+}
+
+#[async_trait]
+impl GeolocationProvider for IpApiGeolocationProvider {
+    async fn find(&self, ip: IpAddr) -> Result<Geolocation, Box<dyn Error + Send + Sync>> {
+        let uri = format!("http://ip-api.com/json/{}", &ip);
+
+        let response = self.client
+            .get(uri)
+            .send()
+            .await?
+            .json::<IpApiResponse>()
+            .await?;
+
+        Ok(Geolocation {
+            ip: response.query,
+            latitude: response.lat,
+            longitude: response.lon,
+            timezone: response.timezone,
+            city: response.city,
+            region: response.region_name,
+            country: response.country,
+        })
+    }
+}
+
+/**
+ * Looks up locations in a MaxMind format database, such as GeoLite2 City or
+ * DB-IP City Lite. The whole file is read into memory.
+ */
+
+pub struct MmdbGeolocationProvider {
+    reader: Reader<Vec<u8>>,
+}
+impl MmdbGeolocationProvider {
+    pub fn open(path: &str) -> Result<MmdbGeolocationProvider, Box<dyn Error + Send + Sync>> {
+        Ok(MmdbGeolocationProvider {
+            reader: Reader::open_readfile(path)?,
+        })
+    }
+}
+
+#[async_trait]
+impl GeolocationProvider for MmdbGeolocationProvider {
+    async fn find(&self, ip: IpAddr) -> Result<Geolocation, Box<dyn Error + Send + Sync>> {
+        let city: geoip2::City = self.reader.lookup(ip)?;
+
+        let location = city.location.ok_or_else(|| not_found_error("IP address has no location."))?;
+
+        Ok(Geolocation {
+            ip: ip.to_string(),
+            latitude: location.latitude.ok_or_else(|| not_found_error("IP address has no latitude."))?,
+            longitude: location.longitude.ok_or_else(|| not_found_error("IP address has no longitude."))?,
+            timezone: location.time_zone.unwrap_or_default().to_string(),
+            city: english_name(city.city.and_then(|city| city.names)),
+            region: english_name(
+                city.subdivisions
+                    .and_then(|subdivisions| subdivisions.into_iter().next())
+                    .and_then(|subdivision| subdivision.names)
+            ),
+            country: english_name(city.country.and_then(|country| country.names)),
+        })
+    }
+}
+
+fn english_name(names: Option<BTreeMap<&str, &str>>) -> String {
+    names
+        .and_then(|names| names.get("en").map(|name| name.to_string()))
+        .unwrap_or_default()
+}
+
+/**
+ * Looks up locations in a CSV file of IP ranges. Each row is expected to be:
+ * start_ip,end_ip,continent,country,region,city,latitude,longitude[,timezone]
+ * which is the layout of the DB-IP City Lite CSV download.
+ */
+
+struct IpRange {
+    start: u128,
+    end: u128,
+    latitude: f64,
+    longitude: f64,
+    timezone: String,
+    city: String,
+    region: String,
+    country: String,
+}
+
+pub struct IpRangeCsvGeolocationProvider {
+    ranges: Vec<IpRange>,
+}
+impl IpRangeCsvGeolocationProvider {
+    pub fn open(path: &str) -> Result<IpRangeCsvGeolocationProvider, Box<dyn Error + Send + Sync>> {
+        let csv = fs::read_to_string(path)?;
+        let mut ranges: Vec<IpRange> = Vec::new();
+
+        for line in csv.lines() {
+            let columns = split_csv_line(line);
+            if columns.len() < 8 {
+                continue;
+            }
+            let (Ok(start), Ok(end), Ok(latitude), Ok(longitude)) = (
+                columns[0].parse::<IpAddr>(),
+                columns[1].parse::<IpAddr>(),
+                columns[6].parse::<f64>(),
+                columns[7].parse::<f64>(),
+            ) else {
+                // Header row, or a row that is malformed.
+                continue;
+            };
+            ranges.push(IpRange {
+                start: ip_to_number(start),
+                end: ip_to_number(end),
+                latitude,
+                longitude,
+                timezone: columns.get(8).cloned().unwrap_or_default(),
+                city: columns[5].clone(),
+                region: columns[4].clone(),
+                country: columns[3].clone(),
+            });
+        }
+
+        ranges.sort_by_key(|range| range.start);
+
+        Ok(IpRangeCsvGeolocationProvider {
+            ranges,
+        })
+    }
+}
+
+#[async_trait]
+impl GeolocationProvider for IpRangeCsvGeolocationProvider {
+    async fn find(&self, ip: IpAddr) -> Result<Geolocation, Box<dyn Error + Send + Sync>> {
+        let ip_number = ip_to_number(ip);
+        let index = self.ranges.partition_point(|range| range.start <= ip_number);
+        if index == 0 {
+            return Err(not_found_error("IP address is not in the database."));
+        }
+        let range = &self.ranges[index - 1];
+        if range.end < ip_number {
+            return Err(not_found_error("IP address is not in the database."));
+        }
+
+        Ok(Geolocation {
+            ip: ip.to_string(),
+            latitude: range.latitude,
+            longitude: range.longitude,
+            timezone: range.timezone.clone(),
+            city: range.city.clone(),
+            region: range.region.clone(),
+            country: range.country.clone(),
+        })
+    }
+}
+
+/**
+ * IPv4 addresses are mapped into the IPv6 space so both can share one sorted list.
+ */
+fn ip_to_number(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    let mut column = String::new();
+    let mut is_quoted = false;
+    let mut characters = line.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if is_quoted && characters.peek() == Some(&'"') => {
+                column.push('"');
+                characters.next();
+            },
+            '"' => is_quoted = !is_quoted,
+            ',' if !is_quoted => columns.push(std::mem::take(&mut column)),
+            _ => column.push(character),
+        }
+    }
+    columns.push(column);
+
+    columns
+}
+
+/**
+ * Remembers the results of another provider for a while.
+ * Failed lookups are not remembered.
+ */
+
+pub struct CachedGeolocationProvider {
+    provider: Box<dyn GeolocationProvider>,
+    cache: Mutex<LruCache<IpAddr, (Instant, Geolocation)>>,
+    ttl: Duration,
+}
+impl CachedGeolocationProvider {
+    pub fn new(provider: Box<dyn GeolocationProvider>, capacity: NonZeroUsize, ttl: Duration) -> CachedGeolocationProvider {
+        CachedGeolocationProvider {
+            provider,
+            cache: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+}
+
+#[async_trait]
+impl GeolocationProvider for CachedGeolocationProvider {
+    async fn find(&self, ip: IpAddr) -> Result<Geolocation, Box<dyn Error + Send + Sync>> {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some((cached_time, location)) = cache.get(&ip) {
+                if cached_time.elapsed() < self.ttl {
+                    return Ok(location.clone());
+                }
+                cache.pop(&ip);
+            }
+        }
+
+        let location = self.provider.find(ip).await?;
+        self.cache.lock().unwrap().put(ip, (Instant::now(), location.clone()));
+        Ok(location)
     }
+}
+
+pub fn init_geolocation() {
+    let secrets_toml = fs::read_to_string(format!(
+        "{}/config/secrets.toml",
+        env!("CARGO_MANIFEST_DIR")
+    )).expect("Failed to read secrets.toml file.");
+    let config: SecretsConfig = toml::from_str(&secrets_toml)
+        .expect("Failed to parse secrets.toml file.");
+    let config = config.geolocation;
+
+    tracing::info!("Geolocation provider: {}", config.provider);
+
+    let provider: Box<dyn GeolocationProvider> = match config.provider.as_str() {
+        "ip-range-file" => {
+            let database_path = if config.database_path.starts_with('/') {
+                config.database_path.clone()
+            } else {
+                format!("{}/{}", env!("CARGO_MANIFEST_DIR"), config.database_path)
+            };
+            if database_path.to_lowercase().ends_with(".mmdb") {
+                Box::new(
+                    MmdbGeolocationProvider::open(&database_path)
+                        .expect("Failed to open the geolocation database file.")
+                )
+            } else {
+                Box::new(
+                    IpRangeCsvGeolocationProvider::open(&database_path)
+                        .expect("Failed to read the geolocation database file.")
+                )
+            }
+        },
+        "ip-api" => Box::new(IpApiGeolocationProvider::default()),
+        _ => panic!("Unknown geolocation provider in secrets.toml, expected \"ip-range-file\" or \"ip-api\"."),
+    };
 
-    let uri = format!("http://ip-api.com/json/{}", &ip);
-    let client = Client::new();
-
-    let response = client
-        .get(uri)
-        .send()
-        .await?
-        .json::<IpApiResponse>()
-        .await?;
-
-    let result = Geolocation {
-        ip: response.query,
-        latitude: response.lat,
-        longitude: response.lon,
-        timezone: response.timezone,
+    let provider: Box<dyn GeolocationProvider> = match NonZeroUsize::new(config.cache_capacity) {
+        Some(capacity) => Box::new(
+            CachedGeolocationProvider::new(provider, capacity, Duration::from_secs(config.cache_ttl_seconds))
+        ),
+        None => provider,
     };
 
-    Ok(result)
+    GEOLOCATION_PROVIDER.set(provider).map_err(|_| ()).expect("Geolocation provider already initialized.");
+    GEO_FENCE_RADIUS_KM.set(config.geo_fence_radius_km).expect("Geo-fence radius already initialized.");
+}
+
+pub async fn find(ip: &str) -> Result<Geolocation, Box<dyn Error + Send + Sync>> {
+    // X-Forwarded-For may contain a list of proxies, the first one is the client.
+    let ip = ip.split(',').next().unwrap_or("").trim();
+    if ip == "" {
+        return Err(Box::new(io::Error::new(io::ErrorKind::Other, "IP Address Not Provided.")))
+    }
+    let ip = ip.parse::<IpAddr>()?;
+
+    GEOLOCATION_PROVIDER
+        .get()
+        .expect("Geolocation provider is not initialized.")
+        .find(ip)
+        .await
 }
 
 fn degrees_to_radians(degrees: f64) -> f64 {
@@ -72,5 +417,6 @@ fn haversine(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
 }
 
 pub fn is_within_geo_fence(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> bool {
-    return haversine(lat1, lon1, lat2, lon2) <= 100.0; // Within 100km
+    let radius_km = GEO_FENCE_RADIUS_KM.get().copied().unwrap_or(100.0);
+    return haversine(lat1, lon1, lat2, lon2) <= radius_km;
 }
@@ -201,3 +201,27 @@ pub async fn transfer_temporary_image_upload(
 
     Ok(permanent_filename_with_extension)
 }
+
+/**
+ * Removes an image and its thumbnail from a permanent folder, for content that was deleted.
+ */
+pub async fn remove_permanent_image(
+    permanent_path: &str,
+    permanent_image_filename: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if permanent_image_filename.is_empty() || permanent_image_filename.contains('/') || permanent_image_filename.contains("..") {
+        return Ok(());
+    }
+
+    let folder_path = get_permanent_storage_base_path().await.join(permanent_path);
+    for path in [folder_path.join("thumbs").join(permanent_image_filename), folder_path.join(permanent_image_filename)] {
+        if let Err(error) = tokio::fs::remove_file(&path).await {
+            if error.kind() != io::ErrorKind::NotFound {
+                tracing::warn!("Error occurred when removing image {:?}. {:?}", &path, error);
+                return Err(Box::new(error));
+            }
+        }
+    }
+
+    Ok(())
+}
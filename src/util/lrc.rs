@@ -0,0 +1,315 @@
+/**
+ * Synchronized lyrics in the LRC format, where each line starts with the time it is sung,
+ * such as [01:23.45]. Enhanced LRC also times single words with tags like <01:23.45>.
+ *
+ * Lyrics store their timestamps one per line, lined up with the kanji lines, and a
+ * blank line where a line has no timestamp.
+ */
+
+/**
+ * Larger [offset:] values are clamped to this, no song needs its lyrics moved by more than an hour.
+ */
+const LRC_MAX_OFFSET_MS: i64 = 3_600_000;
+
+#[derive(Clone, Debug, Default)]
+pub struct LrcLine {
+    pub time_ms: u32,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LrcDocument {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub author: String,
+    pub lines: Vec<LrcLine>,
+}
+
+/**
+ * Lines with more than one timestamp are repeated at each time, word timings of enhanced
+ * LRC are dropped, and the [offset:] tag is applied. Lines are sorted by time.
+ */
+pub fn parse_lrc(text: &str) -> LrcDocument {
+    let mut document = LrcDocument::default();
+    let mut offset_ms: i64 = 0;
+
+    for line in text.lines() {
+        let mut rest = line.trim();
+        let mut times: Vec<u32> = Vec::new();
+
+        while rest.starts_with('[') {
+            let Some(close_index) = rest.find(']') else {
+                break;
+            };
+            let tag = &rest[1..close_index];
+            rest = rest[close_index + 1..].trim_start();
+
+            if let Some(time_ms) = parse_timestamp(tag) {
+                times.push(time_ms);
+            } else if let Some((key, value)) = tag.split_once(':') {
+                let value = value.trim().to_string();
+                match key.trim().to_lowercase().as_str() {
+                    "ti" => document.title = value,
+                    "ar" => document.artist = value,
+                    "al" => document.album = value,
+                    "by" => document.author = value,
+                    "offset" => offset_ms = value.parse::<i64>().unwrap_or(0).clamp(-LRC_MAX_OFFSET_MS, LRC_MAX_OFFSET_MS),
+                    _ => {},
+                }
+            }
+        }
+
+        let text = strip_word_timestamps(rest);
+        for time_ms in times {
+            document.lines.push(LrcLine {
+                time_ms: (time_ms as i64 - offset_ms).clamp(0, u32::MAX as i64) as u32,
+                text: text.clone(),
+            });
+        }
+    }
+    document.lines.sort_by_key(|line| line.time_ms);
+
+    document
+}
+
+/**
+ * Accepts mm:ss, mm:ss.x, mm:ss.xx and mm:ss.xxx, with a "." or ":" before the fraction.
+ */
+pub fn parse_timestamp(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let (minutes, rest) = text.split_once(':')?;
+    let (seconds, fraction) = match rest.find(['.', ':']) {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, ""),
+    };
+    if minutes.is_empty() || seconds.len() != 2 || fraction.len() > 3
+        || !minutes.chars().chain(seconds.chars()).chain(fraction.chars()).all(|character| character.is_ascii_digit()) {
+        return None;
+    }
+
+    let minutes = minutes.parse::<u32>().ok()?;
+    let seconds = seconds.parse::<u32>().ok()?;
+    if seconds >= 60 {
+        return None;
+    }
+    let fraction_ms = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u32>().ok()? * 100,
+        2 => fraction.parse::<u32>().ok()? * 10,
+        _ => fraction.parse::<u32>().ok()?,
+    };
+
+    minutes.checked_mul(60_000)?.checked_add(seconds * 1000 + fraction_ms)
+}
+
+/**
+ * Formats as mm:ss.xx, the precision most LRC players support.
+ */
+pub fn format_timestamp(time_ms: u32) -> String {
+    format!("{:02}:{:02}.{:02}", time_ms / 60_000, (time_ms / 1000) % 60, (time_ms % 1000) / 10)
+}
+
+pub fn parse_line_timestamps(text: &str) -> Vec<Option<u32>> {
+    text.lines().map(parse_timestamp).collect()
+}
+
+pub fn format_line_timestamps(timestamps: &[Option<u32>]) -> String {
+    let last_index = timestamps.iter().rposition(|timestamp| timestamp.is_some());
+    match last_index {
+        Some(last_index) => timestamps[..=last_index].iter()
+            .map(|timestamp| timestamp.map(format_timestamp).unwrap_or_default())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        None => String::new(),
+    }
+}
+
+/**
+ * Finds the first timestamp that can't be read, goes back in time, or has no kanji line.
+ */
+pub fn find_line_timestamps_error(text: &str, kanji: &str) -> Option<String> {
+    let kanji_line_count = kanji.lines().count();
+    let mut previous_time_ms: u32 = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line_number > kanji_line_count {
+            return Some(format!("Line {} has a timestamp, but the kanji only has {} lines.", line_number, kanji_line_count));
+        }
+        let Some(time_ms) = parse_timestamp(line) else {
+            return Some(format!("Line {} is not a timestamp like 01:23.45.", line_number));
+        };
+        if time_ms < previous_time_ms {
+            return Some(format!("Line {}: {} is earlier than the line before it.", line_number, format_timestamp(time_ms)));
+        }
+        previous_time_ms = time_ms;
+    }
+    None
+}
+
+/**
+ * Gives each line of the lyrics the time of an LRC line, in order. Blank lines between
+ * stanzas are skipped on both sides, so they don't need to match up.
+ * Returns the timestamps and the number of LRC lines that were left over, or missing if negative.
+ */
+pub fn match_lrc_to_lines(document: &LrcDocument, lyrics: &str) -> (Vec<Option<u32>>, i64) {
+    let mut lrc_lines = document.lines.iter().filter(|line| !line.text.is_empty());
+    let mut timestamps: Vec<Option<u32>> = Vec::new();
+    let mut missing_count: i64 = 0;
+
+    for line in lyrics.lines() {
+        if line.trim().is_empty() {
+            timestamps.push(None);
+            continue;
+        }
+        match lrc_lines.next() {
+            Some(lrc_line) => timestamps.push(Some(lrc_line.time_ms)),
+            None => {
+                timestamps.push(None);
+                missing_count += 1;
+            },
+        }
+    }
+
+    (timestamps, lrc_lines.count() as i64 - missing_count)
+}
+
+/**
+ * Lines without a timestamp are left out. Enhanced LRC adds a word timestamp at the start
+ * of each line and one at the start of the next line, which marks where the line ends.
+ */
+pub fn create_lrc(document: &LrcDocument, is_enhanced: bool) -> String {
+    let mut lrc = String::new();
+    for (tag, value) in [
+        ("ti", &document.title),
+        ("ar", &document.artist),
+        ("al", &document.album),
+        ("by", &document.author),
+    ] {
+        if !value.is_empty() {
+            lrc.push_str(&format!("[{}:{}]\n", tag, value.replace(['[', ']'], "")));
+        }
+    }
+
+    for (index, line) in document.lines.iter().enumerate() {
+        let timestamp = format_timestamp(line.time_ms);
+        if is_enhanced && !line.text.is_empty() {
+            lrc.push_str(&format!("[{}] <{}> {}", timestamp, timestamp, line.text));
+            if let Some(next_line) = document.lines.get(index + 1) {
+                lrc.push_str(&format!(" <{}>", format_timestamp(next_line.time_ms)));
+            }
+            lrc.push('\n');
+        } else {
+            lrc.push_str(&format!("[{}]{}\n", timestamp, line.text));
+        }
+    }
+
+    lrc
+}
+
+fn strip_word_timestamps(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open_index) = rest.find('<') {
+        match rest[open_index..].find('>') {
+            Some(close_length) if parse_timestamp(&rest[open_index + 1..open_index + close_length]).is_some() => {
+                stripped.push_str(&rest[..open_index]);
+                rest = &rest[open_index + close_length + 1..];
+            },
+            _ => {
+                stripped.push_str(&rest[..=open_index]);
+                rest = &rest[open_index + 1..];
+            },
+        }
+    }
+    stripped.push_str(rest);
+
+    stripped.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_timestamp_precisions() {
+        assert_eq!(parse_timestamp("01:23"), Some(83_000));
+        assert_eq!(parse_timestamp("01:23.4"), Some(83_400));
+        assert_eq!(parse_timestamp("01:23.45"), Some(83_450));
+        assert_eq!(parse_timestamp("01:23.456"), Some(83_456));
+        assert_eq!(parse_timestamp("01:23:45"), Some(83_450));
+        assert_eq!(parse_timestamp("01:60.00"), None);
+        assert_eq!(parse_timestamp("ti:Song"), None);
+    }
+
+    #[test]
+    fn parses_tags_and_sorts_lines() {
+        let document = parse_lrc("[ti:Song]\n[ar:Supercar]\n[00:05.00]second\n[00:01.00]first");
+        assert_eq!(document.title, "Song");
+        assert_eq!(document.artist, "Supercar");
+        assert_eq!(document.lines.len(), 2);
+        assert_eq!(document.lines[0].text, "first");
+        assert_eq!(document.lines[1].time_ms, 5_000);
+    }
+
+    #[test]
+    fn repeats_lines_with_several_timestamps() {
+        let document = parse_lrc("[00:01.00][00:10.00]chorus");
+        assert_eq!(document.lines.len(), 2);
+        assert_eq!(document.lines[1].time_ms, 10_000);
+        assert_eq!(document.lines[1].text, "chorus");
+    }
+
+    #[test]
+    fn strips_enhanced_word_timestamps() {
+        let document = parse_lrc("[00:01.00] <00:01.00> hello <00:01.50> world <00:02.00>");
+        assert_eq!(document.lines[0].text, "hello world");
+    }
+
+    #[test]
+    fn applies_offset() {
+        let document = parse_lrc("[offset:500]\n[00:01.00]a\n[00:00.20]b");
+        assert_eq!(document.lines[0].time_ms, 0);
+        assert_eq!(document.lines[1].time_ms, 500);
+
+        let document = parse_lrc("[offset:-500]\n[00:01.00]a");
+        assert_eq!(document.lines[0].time_ms, 1_500);
+    }
+
+    #[test]
+    fn clamps_extreme_offsets() {
+        let document = parse_lrc("[offset:-9223372036854775808]\n[00:01.00]a");
+        assert_eq!(document.lines[0].time_ms, 1_000 + LRC_MAX_OFFSET_MS as u32);
+
+        let document = parse_lrc("[offset:9223372036854775807]\n[00:01.00]a");
+        assert_eq!(document.lines[0].time_ms, 0);
+
+        let document = parse_lrc("[offset:-3600000]\n[71582:47.29]a");
+        assert_eq!(document.lines[0].time_ms, u32::MAX);
+    }
+
+    #[test]
+    fn matches_lrc_lines_skipping_blank_lines() {
+        let document = parse_lrc("[00:01.00]one\n[00:02.00]\n[00:03.00]two\n[00:04.00]three");
+        let (timestamps, left_over) = match_lrc_to_lines(&document, "one\n\ntwo");
+        assert_eq!(timestamps, vec![Some(1_000), None, Some(3_000)]);
+        assert_eq!(left_over, 1);
+    }
+
+    #[test]
+    fn finds_timestamps_going_back_in_time() {
+        assert_eq!(find_line_timestamps_error("00:01.00\n\n00:03.00", "a\nb\nc"), None);
+        assert!(find_line_timestamps_error("00:03.00\n00:01.00", "a\nb").is_some());
+        assert!(find_line_timestamps_error("00:01.00\n00:02.00", "a").is_some());
+    }
+
+    #[test]
+    fn formats_line_timestamps_without_trailing_blanks() {
+        assert_eq!(format_line_timestamps(&[Some(1_000), None, Some(83_450), None]), "00:01.00\n\n01:23.45");
+        assert_eq!(parse_line_timestamps("00:01.00\n\n01:23.45"), vec![Some(1_000), None, Some(83_450)]);
+    }
+}
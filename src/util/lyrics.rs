@@ -0,0 +1,217 @@
+/**
+ * Kanji, rōmaji and translated lyrics are stored as separate texts that are displayed
+ * line by line next to each other, so every line (and every blank line between
+ * stanzas) must be in the same place in all three.
+ */
+
+use crate::util::furigana::strip_furigana;
+
+#[allow(unused)]
+pub struct LyricsMisalignment {
+    pub line_number: usize,
+    pub message: String,
+}
+
+/**
+ * Finds the first line where the kanji, rōmaji and translation stop lining up.
+ * "translation_name" is the language name used in the message, such as "English".
+ */
+pub fn find_lyrics_misalignment(
+    kanji: &str,
+    romaji: &str,
+    translation: &str,
+    translation_name: &str,
+) -> Option<LyricsMisalignment> {
+    let columns = [
+        ("Kanji", get_lyrics_lines(kanji)),
+        ("Rōmaji", get_lyrics_lines(romaji)),
+        (translation_name, get_lyrics_lines(translation)),
+    ];
+    let line_count = columns.iter().map(|(_, lines)| lines.len()).max().unwrap_or(0);
+
+    for index in 0..line_count {
+        let line_number = index + 1;
+
+        let short_column = columns.iter().find(|(_, lines)| index >= lines.len());
+        if let Some((short_name, short_lines)) = short_column {
+            let (long_name, _) = columns.iter().find(|(_, lines)| index < lines.len()).unwrap();
+            return Some(LyricsMisalignment {
+                line_number,
+                message: with_stanza_counts(
+                    format!("Line {}: {} ends after {} lines, but {} continues.", line_number, short_name, short_lines.len(), long_name),
+                    &columns,
+                ),
+            });
+        }
+
+        let blank_column = columns.iter().find(|(_, lines)| lines[index].trim().is_empty());
+        let text_column = columns.iter().find(|(_, lines)| !lines[index].trim().is_empty());
+        if let (Some((blank_name, _)), Some((text_name, _))) = (blank_column, text_column) {
+            return Some(LyricsMisalignment {
+                line_number,
+                message: with_stanza_counts(
+                    format!("Line {}: {} has a blank line between stanzas, but {} does not.", line_number, blank_name, text_name),
+                    &columns,
+                ),
+            });
+        }
+    }
+
+    None
+}
+
+/**
+ * A line of lyrics that contains a search, split around the match.
+ */
+pub struct LyricsMatchingLine {
+    pub label: String,
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+}
+
+/**
+ * Looks through the kanji, then the rōmaji, then the translation. The full-text index
+ * also matches across furigana and punctuation, so lyrics it found can have no matching line.
+ */
+pub fn find_matching_line(
+    kanji: &str,
+    romaji: &str,
+    translation: &str,
+    translation_name: &str,
+    search: &str,
+) -> Option<LyricsMatchingLine> {
+    let search = search.trim();
+    let columns = [
+        ("Kanji", strip_furigana(kanji)),
+        ("Rōmaji", romaji.to_string()),
+        (translation_name, translation.to_string()),
+    ];
+
+    columns.iter().find_map(|(label, content)| {
+        content.lines().find_map(|line| {
+            let line = line.trim();
+            let (start, end) = find_case_insensitive(line, search)?;
+            Some(LyricsMatchingLine {
+                label: label.to_string(),
+                before: line[..start].to_string(),
+                matched: line[start..end].to_string(),
+                after: line[end..].to_string(),
+            })
+        })
+    })
+}
+
+/**
+ * Byte range of the first match in the text, comparing characters in lowercase.
+ */
+fn find_case_insensitive(text: &str, search: &str) -> Option<(usize, usize)> {
+    let search: Vec<char> = search.chars().flat_map(char::to_lowercase).collect();
+    if search.is_empty() {
+        return None;
+    }
+    for (start, _) in text.char_indices() {
+        let mut search_chars = search.iter();
+        for (index, character) in text[start..].char_indices() {
+            if !character.to_lowercase().all(|lowercase_character| search_chars.next() == Some(&lowercase_character)) {
+                break;
+            }
+            if search_chars.len() == 0 {
+                return Some((start, start + index + character.len_utf8()));
+            }
+        }
+    }
+    None
+}
+
+fn count_stanzas(lines: &[&str]) -> usize {
+    let mut stanza_count = 0;
+    let mut is_in_stanza = false;
+    for line in lines {
+        let is_blank = line.trim().is_empty();
+        if !is_blank && !is_in_stanza {
+            stanza_count += 1;
+        }
+        is_in_stanza = !is_blank;
+    }
+    stanza_count
+}
+
+fn get_lyrics_lines(text: &str) -> Vec<&str> {
+    text.trim().lines().collect()
+}
+
+fn with_stanza_counts(message: String, columns: &[(&str, Vec<&str>)]) -> String {
+    let stanza_counts: Vec<(&str, usize)> = columns.iter()
+        .map(|(name, lines)| (*name, count_stanzas(lines)))
+        .collect();
+    if stanza_counts.iter().all(|(_, count)| *count == stanza_counts[0].1) {
+        return message;
+    }
+    let stanza_summary = stanza_counts.iter()
+        .map(|(name, count)| format!("{} has {}", name, count))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("{} The stanza counts don't match: {}.", message, stanza_summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_lyrics_have_no_misalignment() {
+        let misalignment = find_lyrics_misalignment("一\n二\n\n三", "ichi\nni\n\nsan", "one\ntwo\n\nthree", "English");
+        assert!(misalignment.is_none());
+    }
+
+    #[test]
+    fn surrounding_blank_lines_are_ignored() {
+        let misalignment = find_lyrics_misalignment("\n一\n二\n", "ichi\nni", "one\ntwo\n\n", "English");
+        assert!(misalignment.is_none());
+    }
+
+    #[test]
+    fn finds_a_column_that_ends_early() {
+        let misalignment = find_lyrics_misalignment("一\n二\n三", "ichi\nni", "one\ntwo\nthree", "English").unwrap();
+        assert_eq!(misalignment.line_number, 3);
+        assert!(misalignment.message.starts_with("Line 3: Rōmaji ends after 2 lines, but Kanji continues."));
+    }
+
+    #[test]
+    fn names_the_translation_language() {
+        let misalignment = find_lyrics_misalignment("一\n二", "ichi\nni", "un", "Français").unwrap();
+        assert!(misalignment.message.starts_with("Line 2: Français ends after 1 lines, but Kanji continues."));
+    }
+
+    #[test]
+    fn finds_the_first_misplaced_blank_line() {
+        let misalignment = find_lyrics_misalignment("一\n\n二\n三", "ichi\nni\n\nsan", "one\n\ntwo\nthree", "English").unwrap();
+        assert_eq!(misalignment.line_number, 2);
+        assert!(misalignment.message.starts_with("Line 2: Kanji has a blank line between stanzas, but Rōmaji does not."));
+    }
+
+    #[test]
+    fn mentions_stanza_counts_only_when_they_differ() {
+        let misalignment = find_lyrics_misalignment("一\n\n二\n三", "ichi\nni\nsan\nshi", "one\n\ntwo\nthree", "English").unwrap();
+        assert!(misalignment.message.ends_with("The stanza counts don't match: Kanji has 2, Rōmaji has 1, English has 2."));
+
+        let misalignment = find_lyrics_misalignment("一\n二", "ichi", "one", "English").unwrap();
+        assert!(!misalignment.message.contains("stanza counts"));
+    }
+
+    #[test]
+    fn finds_the_matching_line_without_case_or_furigana() {
+        let matching_line = find_matching_line("空{そら}を見る", "Sora wo miru", "Look at the SKY", "English", "sky").unwrap();
+        assert_eq!(matching_line.label, "English");
+        assert_eq!(matching_line.before, "Look at the ");
+        assert_eq!(matching_line.matched, "SKY");
+        assert_eq!(matching_line.after, "");
+
+        let matching_line = find_matching_line("空{そら}を見る", "Sora wo miru", "", "English", "空を").unwrap();
+        assert_eq!(matching_line.label, "Kanji");
+        assert_eq!(matching_line.after, "見る");
+
+        assert!(find_matching_line("空{そら}", "sora", "sky", "English", "umi").is_none());
+    }
+}
@@ -1,13 +1,22 @@
 pub mod captcha;
+pub mod diff;
 pub mod error;
 pub mod filesystem;
 pub mod format;
+pub mod furigana;
 pub mod geolocation;
 pub mod image_upload;
+pub mod lrc;
+pub mod lyrics;
 pub mod password_reset_session;
 pub mod rate_limit;
+pub mod romaji;
+pub mod search_index;
+pub mod search_key;
 pub mod smtp;
 pub mod sql;
+pub mod totp;
+pub mod trash;
 pub mod tracing;
 pub mod user;
 pub mod video;
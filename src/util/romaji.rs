@@ -0,0 +1,463 @@
+/**
+ * Offline transliteration of hiragana and katakana to Hepburn rōmaji, for the lyrics
+ * editor to fill in and check the rōmaji column. Kanji can't be read without a
+ * dictionary, so they're left as they are for the contributor to fill in, and words
+ * aren't separated because kana doesn't mark where a word ends.
+ *
+ * The way long vowels are written comes from the [romaji] section of secrets.toml.
+ */
+
+use std::fs;
+use serde::Deserialize;
+use strum_macros::{ Display, EnumString };
+use tokio::sync::OnceCell;
+
+use crate::util::furigana::furigana_to_reading;
+
+pub static ROMAJI_LONG_VOWEL_STYLE: OnceCell<LongVowelStyle> = OnceCell::const_new();
+
+/**
+ * Submitted rōmaji that differs from the generated rōmaji by more than this percent
+ * of its letters gets a warning, small differences like particles are expected.
+ */
+static ROMAJI_WARNING_DIFFERENCE_PERCENT: usize = 30;
+
+/**
+ * Other romanization systems and long vowel spellings are folded into one spelling
+ * before comparing, so they aren't counted as differences.
+ */
+static ROMAJI_COMPARISON_FOLDS: [(&str, &str); 16] = [
+    ("shi", "si"),
+    ("sh", "sy"),
+    ("chi", "ti"),
+    ("ch", "ty"),
+    ("tsu", "tu"),
+    ("fu", "hu"),
+    ("ji", "zi"),
+    ("j", "zy"),
+    ("di", "zi"),
+    ("du", "zu"),
+    ("dy", "zy"),
+    ("wo", "o"),
+    ("ou", "o"),
+    ("mb", "nb"),
+    ("mp", "np"),
+    ("mm", "nm"),
+];
+
+/**
+ * How とうきょう is written: tōkyō, tôkyô, toukyou or tokyo.
+ * With the doubled style ー repeats the vowel before it, so ケーキ is keeki.
+ */
+#[derive(Clone, Copy, Debug, Default, Display, EnumString, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum LongVowelStyle {
+    #[default]
+    Macron,
+    Circumflex,
+    Doubled,
+    Omitted,
+}
+
+#[derive(Deserialize)]
+struct SecretsConfig {
+    #[serde(default)]
+    romaji: SecretsConfigRomaji,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct SecretsConfigRomaji {
+    long_vowel_style: String,
+}
+impl Default for SecretsConfigRomaji {
+    fn default() -> Self {
+        SecretsConfigRomaji {
+            long_vowel_style: String::from("macron"),
+        }
+    }
+}
+
+pub fn init_romaji() {
+    let secrets_toml = fs::read_to_string(format!(
+        "{}/config/secrets.toml",
+        env!("CARGO_MANIFEST_DIR")
+    )).expect("Failed to read secrets.toml file.");
+    let config: SecretsConfig = toml::from_str(&secrets_toml)
+        .expect("Failed to parse secrets.toml file.");
+
+    let long_vowel_style = config.romaji.long_vowel_style.parse::<LongVowelStyle>().unwrap_or_else(|_| {
+        tracing::warn!("Unknown rōmaji long vowel style \"{}\", using macron.", config.romaji.long_vowel_style);
+        LongVowelStyle::Macron
+    });
+
+    tracing::info!("Rōmaji long vowel style: {}", long_vowel_style);
+
+    ROMAJI_LONG_VOWEL_STYLE.set(long_vowel_style).expect("Rōmaji long vowel style already initialized.");
+}
+
+pub fn get_long_vowel_style() -> LongVowelStyle {
+    *ROMAJI_LONG_VOWEL_STYLE.get().unwrap_or(&LongVowelStyle::Macron)
+}
+
+enum KanaToken {
+    Syllable(String),
+    Sokuon,
+    LongVowelMark,
+    Other(String),
+}
+
+pub fn kana_to_romaji(text: &str, long_vowel_style: LongVowelStyle) -> String {
+    let tokens = tokenize_kana(text);
+
+    let mut romaji = String::with_capacity(text.len() * 2);
+    let mut has_sokuon = false;
+    let mut index = 0;
+    while index < tokens.len() {
+        match &tokens[index] {
+            KanaToken::Syllable(syllable) => {
+                if has_sokuon {
+                    if syllable.starts_with("ch") {
+                        romaji.push('t');
+                    } else if let Some(consonant) = syllable.chars().next().filter(|letter| !is_vowel(*letter)) {
+                        romaji.push(consonant);
+                    }
+                    has_sokuon = false;
+                }
+
+                let next_token = tokens.get(index + 1);
+                if syllable == "n" {
+                    // ん before a vowel or y is written n' so んや (n'ya) isn't read as にゃ (nya).
+                    if let Some(KanaToken::Syllable(next_syllable)) = next_token {
+                        if next_syllable.starts_with(|letter: char| is_vowel(letter) || letter == 'y') {
+                            romaji.push_str("n'");
+                            index += 1;
+                            continue;
+                        }
+                    }
+                }
+
+                let vowel = syllable.chars().last().filter(|letter| is_vowel(*letter));
+                let lengthening_vowel = match (vowel, next_token) {
+                    (Some(vowel), Some(KanaToken::LongVowelMark)) => Some(vowel),
+                    (Some(vowel), Some(KanaToken::Syllable(next_syllable))) => {
+                        next_syllable.chars().next()
+                            .filter(|next_vowel| next_syllable.len() == 1 && is_lengthening_vowel(vowel, *next_vowel))
+                    },
+                    _ => None,
+                };
+                match (vowel, lengthening_vowel) {
+                    (Some(vowel), Some(next_vowel)) => {
+                        romaji.push_str(&syllable[..syllable.len() - 1]);
+                        romaji.push_str(&write_long_vowel(vowel, next_vowel, long_vowel_style));
+                        index += 1;
+                    },
+                    _ => romaji.push_str(syllable),
+                }
+            },
+            KanaToken::Sokuon => {
+                has_sokuon = true;
+            },
+            KanaToken::LongVowelMark => {
+                romaji.push('-');
+            },
+            KanaToken::Other(text) => {
+                has_sokuon = false;
+                romaji.push_str(text);
+            },
+        }
+        index += 1;
+    }
+
+    romaji
+}
+
+/**
+ * Fills in the blank lines of the rōmaji from the kanji lines, lines that already have
+ * rōmaji are kept as they are. Kanji with furigana are read from the furigana.
+ */
+pub fn fill_romaji_lines(kanji: &str, romaji: &str, long_vowel_style: LongVowelStyle) -> String {
+    let kanji_reading = furigana_to_reading(kanji);
+    let kanji_lines: Vec<&str> = kanji_reading.lines().collect();
+    let romaji_lines: Vec<&str> = romaji.lines().collect();
+    (0..kanji_lines.len().max(romaji_lines.len()))
+        .map(|index| match romaji_lines.get(index) {
+            Some(romaji_line) if !romaji_line.trim().is_empty() => romaji_line.to_string(),
+            _ => kana_to_romaji(kanji_lines.get(index).unwrap_or(&""), long_vowel_style),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub struct RomajiLineWarning {
+    pub line_number: usize,
+    pub expected_romaji: String,
+}
+
+/**
+ * Compares each line of rōmaji with the rōmaji generated from the kanji line next to it.
+ * Only lines written entirely in kana, or with furigana for every kanji, can be checked.
+ */
+pub fn check_romaji_lines(kanji: &str, romaji: &str, long_vowel_style: LongVowelStyle) -> Vec<RomajiLineWarning> {
+    let kanji_reading = furigana_to_reading(kanji);
+    let mut warnings: Vec<RomajiLineWarning> = Vec::new();
+    for (index, (kanji_line, romaji_line)) in kanji_reading.lines().zip(romaji.lines()).enumerate() {
+        if romaji_line.trim().is_empty() || contains_kanji(kanji_line) || !contains_kana(kanji_line) {
+            continue;
+        }
+        let expected_romaji = kana_to_romaji(kanji_line, long_vowel_style);
+        if is_significantly_different(romaji_line, &expected_romaji) {
+            warnings.push(RomajiLineWarning {
+                line_number: index + 1,
+                expected_romaji,
+            });
+        }
+    }
+    warnings
+}
+
+pub fn contains_kana(text: &str) -> bool {
+    text.chars().any(|character| matches!(character, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}'))
+}
+
+pub fn contains_kanji(text: &str) -> bool {
+    text.chars().any(|character| matches!(character, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '々'))
+}
+
+fn tokenize_kana(text: &str) -> Vec<KanaToken> {
+    let characters: Vec<char> = text.chars().map(katakana_to_hiragana).collect();
+
+    let mut tokens: Vec<KanaToken> = Vec::with_capacity(characters.len());
+    let mut index = 0;
+    while index < characters.len() {
+        let character = characters[index];
+        if character == 'っ' {
+            tokens.push(KanaToken::Sokuon);
+        } else if character == 'ー' {
+            tokens.push(KanaToken::LongVowelMark);
+        } else if let Some(syllable) = characters.get(index + 1).and_then(|next| get_digraph_romaji(character, *next)) {
+            tokens.push(KanaToken::Syllable(syllable));
+            index += 1;
+        } else if let Some(syllable) = get_kana_romaji(character) {
+            tokens.push(KanaToken::Syllable(String::from(syllable)));
+        } else {
+            tokens.push(KanaToken::Other(get_punctuation_romaji(character)));
+        }
+        index += 1;
+    }
+    tokens
+}
+
+fn katakana_to_hiragana(character: char) -> char {
+    match character {
+        'ァ'..='ヶ' => char::from_u32(character as u32 - 0x60).unwrap_or(character),
+        _ => character,
+    }
+}
+
+fn get_kana_romaji(character: char) -> Option<&'static str> {
+    Some(match character {
+        'あ' | 'ぁ' => "a", 'い' | 'ぃ' | 'ゐ' => "i", 'う' | 'ぅ' => "u", 'え' | 'ぇ' | 'ゑ' => "e", 'お' | 'ぉ' | 'を' => "o",
+        'か' | 'ゕ' => "ka", 'き' => "ki", 'く' => "ku", 'け' | 'ゖ' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' | 'ゃ' => "ya", 'ゆ' | 'ゅ' => "yu", 'よ' | 'ょ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' | 'ゎ' => "wa", 'ん' => "n", 'ゔ' => "vu",
+        _ => return None,
+    })
+}
+
+/**
+ * Kana followed by a small ゃ, ゅ, ょ or a small vowel, such as きょ (kyo) or ファ (fa).
+ */
+fn get_digraph_romaji(character: char, next_character: char) -> Option<String> {
+    let vowel = match next_character {
+        'ゃ' => "a", 'ゅ' => "u", 'ょ' => "o",
+        'ぁ' => "a", 'ぃ' => "i", 'ぅ' => "u", 'ぇ' => "e", 'ぉ' => "o",
+        _ => return None,
+    };
+    let is_small_y = matches!(next_character, 'ゃ' | 'ゅ' | 'ょ');
+    let consonant = match (character, is_small_y) {
+        ('き', true) => "ky", ('ぎ', true) => "gy", ('に', true) => "ny", ('ひ', true) => "hy",
+        ('び', true) => "by", ('ぴ', true) => "py", ('み', true) => "my", ('り', true) => "ry",
+        ('し', _) => "sh", ('じ', _) | ('ぢ', true) => "j", ('ち', _) => "ch",
+        ('ふ', false) => "f", ('ゔ', false) => "v", ('て', false) | ('と', false) => "t",
+        ('で', false) | ('ど', false) => "d", ('う', false) => "w", ('い', false) => "y", ('つ', false) => "ts",
+        _ => return None,
+    };
+    Some(format!("{}{}", consonant, vowel))
+}
+
+fn get_punctuation_romaji(character: char) -> String {
+    match character {
+        '、' => String::from(","),
+        '。' => String::from("."),
+        '「' | '」' | '『' | '』' => String::from("\""),
+        '　' | '・' => String::from(" "),
+        '〜' => String::from("~"),
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(character as u32 - 0xFEE0).unwrap_or(character).to_string(),
+        _ => character.to_string(),
+    }
+}
+
+fn is_vowel(letter: char) -> bool {
+    matches!(letter, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/**
+ * おう, おお, うう, ああ and ええ are long vowels, いい and えい are written as they are.
+ */
+fn is_lengthening_vowel(vowel: char, next_vowel: char) -> bool {
+    matches!((vowel, next_vowel), ('o', 'u') | ('o', 'o') | ('u', 'u') | ('a', 'a') | ('e', 'e'))
+}
+
+fn write_long_vowel(vowel: char, next_vowel: char, long_vowel_style: LongVowelStyle) -> String {
+    match long_vowel_style {
+        LongVowelStyle::Macron => String::from(match vowel {
+            'a' => "ā", 'i' => "ī", 'u' => "ū", 'e' => "ē", _ => "ō",
+        }),
+        LongVowelStyle::Circumflex => String::from(match vowel {
+            'a' => "â", 'i' => "î", 'u' => "û", 'e' => "ê", _ => "ô",
+        }),
+        LongVowelStyle::Doubled => format!("{}{}", vowel, next_vowel),
+        LongVowelStyle::Omitted => vowel.to_string(),
+    }
+}
+
+fn is_significantly_different(submitted_romaji: &str, expected_romaji: &str) -> bool {
+    let submitted: Vec<char> = normalize_romaji(submitted_romaji).chars().collect();
+    let expected: Vec<char> = normalize_romaji(expected_romaji).chars().collect();
+    let length = submitted.len().max(expected.len());
+    length > 0 && get_edit_distance(&submitted, &expected) * 100 > length * ROMAJI_WARNING_DIFFERENCE_PERCENT
+}
+
+/**
+ * Folds other romanization systems and long vowel spellings into one spelling,
+ * see ROMAJI_COMPARISON_FOLDS. Expects lowercase rōmaji.
+ */
+pub fn fold_romaji_spelling(romaji: &str) -> String {
+    let mut folded = romaji.to_string();
+    for (from, to) in ROMAJI_COMPARISON_FOLDS {
+        folded = folded.replace(from, to);
+    }
+    folded
+}
+
+fn normalize_romaji(romaji: &str) -> String {
+    let mut normalized: String = romaji.to_lowercase().chars()
+        .filter_map(|letter| match letter {
+            'ā' | 'â' => Some('a'),
+            'ī' | 'î' => Some('i'),
+            'ū' | 'û' => Some('u'),
+            'ē' | 'ê' => Some('e'),
+            'ō' | 'ô' => Some('o'),
+            letter if letter.is_ascii_alphabetic() => Some(letter),
+            _ => None,
+        })
+        .collect();
+    normalized = fold_romaji_spelling(&normalized);
+
+    let mut collapsed = String::with_capacity(normalized.len());
+    for letter in normalized.chars() {
+        if is_vowel(letter) && collapsed.ends_with(letter) {
+            continue;
+        }
+        collapsed.push(letter);
+    }
+    collapsed
+}
+
+fn get_edit_distance(left: &[char], right: &[char]) -> usize {
+    let mut previous_row: Vec<usize> = (0..=right.len()).collect();
+    let mut current_row: Vec<usize> = vec![0; right.len() + 1];
+    for (left_index, left_letter) in left.iter().enumerate() {
+        current_row[0] = left_index + 1;
+        for (right_index, right_letter) in right.iter().enumerate() {
+            let substitution_cost = if left_letter == right_letter { 0 } else { 1 };
+            current_row[right_index + 1] = (previous_row[right_index] + substitution_cost)
+                .min(previous_row[right_index + 1] + 1)
+                .min(current_row[right_index] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterates_hiragana_and_katakana() {
+        assert_eq!(kana_to_romaji("さくら", LongVowelStyle::Macron), "sakura");
+        assert_eq!(kana_to_romaji("スーパーカー", LongVowelStyle::Macron), "sūpākā");
+        assert_eq!(kana_to_romaji("しゃしん", LongVowelStyle::Macron), "shashin");
+        assert_eq!(kana_to_romaji("フェアウェイ", LongVowelStyle::Macron), "feawei");
+    }
+
+    #[test]
+    fn doubles_the_consonant_after_small_tsu() {
+        assert_eq!(kana_to_romaji("きって", LongVowelStyle::Macron), "kitte");
+        assert_eq!(kana_to_romaji("まっちゃ", LongVowelStyle::Macron), "matcha");
+    }
+
+    #[test]
+    fn separates_n_before_vowels_and_y() {
+        assert_eq!(kana_to_romaji("きんえん", LongVowelStyle::Macron), "kin'en");
+        assert_eq!(kana_to_romaji("こんや", LongVowelStyle::Macron), "kon'ya");
+        assert_eq!(kana_to_romaji("しんぶん", LongVowelStyle::Macron), "shinbun");
+    }
+
+    #[test]
+    fn writes_long_vowels_in_each_style() {
+        assert_eq!(kana_to_romaji("とうきょう", LongVowelStyle::Macron), "tōkyō");
+        assert_eq!(kana_to_romaji("とうきょう", LongVowelStyle::Circumflex), "tôkyô");
+        assert_eq!(kana_to_romaji("とうきょう", LongVowelStyle::Doubled), "toukyou");
+        assert_eq!(kana_to_romaji("とうきょう", LongVowelStyle::Omitted), "tokyo");
+        assert_eq!(kana_to_romaji("ケーキ", LongVowelStyle::Doubled), "keeki");
+        assert_eq!(kana_to_romaji("いい", LongVowelStyle::Macron), "ii");
+    }
+
+    #[test]
+    fn keeps_kanji_and_converts_punctuation() {
+        assert_eq!(kana_to_romaji("空、そら。", LongVowelStyle::Macron), "空,sora.");
+        assert_eq!(kana_to_romaji("「ＡＢＣ」", LongVowelStyle::Macron), "\"ABC\"");
+    }
+
+    #[test]
+    fn fills_only_blank_romaji_lines() {
+        assert_eq!(
+            fill_romaji_lines("さくら\nそら\n\nうみ", "Sakura\n\n\n", LongVowelStyle::Macron),
+            "Sakura\nsora\n\numi",
+        );
+    }
+
+    #[test]
+    fn warns_about_romaji_that_differs_from_the_kana() {
+        let kanji = "とうきょう\nさくら\n空";
+        assert!(check_romaji_lines(kanji, "Toukyou\nsakura\nsora", LongVowelStyle::Macron).is_empty());
+        assert!(check_romaji_lines(kanji, "Tokyo\nsakura\nsora", LongVowelStyle::Macron).is_empty());
+
+        let warnings = check_romaji_lines(kanji, "tōkyō\numi no kaze\nnani", LongVowelStyle::Macron);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line_number, 2);
+        assert_eq!(warnings[0].expected_romaji, "sakura");
+    }
+
+    #[test]
+    fn folds_romanization_systems_to_one_spelling() {
+        assert_eq!(fold_romaji_spelling("shinjitsu"), fold_romaji_spelling("sinzitu"));
+        assert_eq!(fold_romaji_spelling("chizu"), fold_romaji_spelling("tizu"));
+        assert_eq!(fold_romaji_spelling("fuyu"), "huyu");
+        assert_eq!(fold_romaji_spelling("shinbun"), fold_romaji_spelling("shimbun"));
+    }
+}
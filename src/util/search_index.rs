@@ -0,0 +1,138 @@
+/**
+ * Bands, albums and songs are kept in memory with their stored search keys, so they can be
+ * ranked against a search without a trip to the database. Searches by name and the search
+ * box suggestions both rank against this index.
+ *
+ * The index is built when the application starts. Changes to the catalog mark it as stale,
+ * and it is built again on the next search.
+ */
+
+use std::error::Error;
+use std::sync::RwLock;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use tokio::sync::OnceCell;
+
+use crate::database::{ self, SearchResultType };
+use crate::util::search_key::{ create_search_key, rank_by_search_key };
+
+pub static SEARCH_INDEX: OnceCell<RwLock<Vec<SearchIndexEntry>>> = OnceCell::const_new();
+static SEARCH_INDEX_STALE: AtomicBool = AtomicBool::new(true);
+pub static SEARCH_SUGGESTION_LIMIT: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct SearchIndexEntry {
+    pub id: i32,
+    pub result_type: SearchResultType,
+    pub title: String,
+    pub description: String,
+    pub href: String,
+    search_key: String,
+}
+
+pub async fn init_search_index() {
+    SEARCH_INDEX
+        .set(RwLock::new(Vec::new()))
+        .expect("Search index already initialized.");
+
+    refresh_search_index().await;
+}
+
+/**
+ * Call after bands, albums or songs are added, renamed, deleted or restored.
+ */
+pub fn mark_search_index_stale() {
+    SEARCH_INDEX_STALE.store(true, Ordering::Relaxed);
+}
+
+/**
+ * The best matching bands, albums and songs together, for the search boxes.
+ */
+pub async fn find_search_suggestions(search: &str) -> Vec<SearchIndexEntry> {
+    find_search_index_entries(None, search, SEARCH_SUGGESTION_LIMIT).await
+}
+
+/**
+ * The entries of "result_type" matching the search, or of every type when it's None,
+ * best match first, up to "limit" of them.
+ */
+pub async fn find_search_index_entries(
+    result_type: Option<&SearchResultType>,
+    search: &str,
+    limit: usize,
+) -> Vec<SearchIndexEntry> {
+    if search.len() > 200 {
+        return Vec::new();
+    }
+    if SEARCH_INDEX_STALE.load(Ordering::Relaxed) {
+        refresh_search_index().await;
+    }
+
+    let Some(search_index) = SEARCH_INDEX.get() else {
+        return Vec::new();
+    };
+    let search_index = search_index.read().unwrap();
+    rank_by_search_key(
+        search_index.iter()
+            .filter(|entry| result_type.is_none_or(|result_type| entry.result_type == *result_type))
+            .collect(),
+        search,
+        |entry| &entry.search_key,
+        limit,
+    )
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/**
+ * Clears the stale mark before building, so a change made during the build marks it again.
+ * A failed build leaves the previous index in place.
+ */
+async fn refresh_search_index() {
+    SEARCH_INDEX_STALE.store(false, Ordering::Relaxed);
+
+    match create_search_index_entries().await {
+        Ok(entries) => {
+            tracing::info!("Search index entries: {}", entries.len());
+            if let Some(search_index) = SEARCH_INDEX.get() {
+                *search_index.write().unwrap() = entries;
+            }
+        },
+        Err(error) => {
+            tracing::warn!("Failed to build the search index. {:?}", error);
+            mark_search_index_stale();
+        },
+    }
+}
+
+/**
+ * A key that wasn't filled in yet is made here, so a new name can be found before the
+ * next start fills it in.
+ */
+async fn create_search_index_entries() -> Result<Vec<SearchIndexEntry>, Box<dyn Error>> {
+    Ok(
+        database::get_search_key_entries().await?
+            .into_iter()
+            .map(|(result_type, entry)| {
+                let href = match result_type {
+                    SearchResultType::Bands => format!("/lyrics/{}/", entry.band_slug),
+                    SearchResultType::Albums => format!("/lyrics/{}/{}/", entry.band_slug, entry.album_slug),
+                    _ => format!("/lyrics/{}/{}/{}/", entry.band_slug, entry.album_slug, entry.song_slug),
+                };
+                let search_key = if entry.search_key.is_empty() {
+                    create_search_key(&entry.title)
+                } else {
+                    entry.search_key
+                };
+                SearchIndexEntry {
+                    id: entry.id,
+                    result_type,
+                    title: entry.title,
+                    description: entry.description,
+                    href,
+                    search_key,
+                }
+            })
+            .collect()
+    )
+}
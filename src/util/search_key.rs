@@ -0,0 +1,276 @@
+/**
+ * Search keys let bands, albums and songs be found however their names are typed.
+ * Names mix Japanese and Latin scripts, so "Fairway", "フェアウェイ" and "feawei"
+ * should all find the same song.
+ *
+ * A search key is the name with full-width and half-width characters folded, kana
+ * written in rōmaji, differences between rōmaji spellings folded, and spaces and
+ * punctuation removed. English spellings are then folded toward how they sound
+ * written in katakana. Kanji are kept as they are.
+ *
+ * The keys are stored next to the names. Changing how keys are made needs a migration
+ * that clears the stored keys, so they're made again when the application starts.
+ */
+
+use crate::util::romaji::{ fold_romaji_spelling, kana_to_romaji, LongVowelStyle };
+
+/**
+ * English letters that katakana has no sound for, and vowel pairs that katakana writes
+ * as one vowel, such as "fair" (フェア). Applied in order, after the rōmaji folds.
+ */
+static LOANWORD_FOLDS: [(&str, &str); 13] = [
+    ("ph", "f"),
+    ("th", "s"),
+    ("ck", "k"),
+    ("qu", "kw"),
+    ("q", "k"),
+    ("x", "ks"),
+    ("c", "k"),
+    ("l", "r"),
+    ("v", "b"),
+    ("y", "i"),
+    ("ai", "e"),
+    ("ei", "e"),
+    ("ea", "e"),
+];
+
+static HALF_WIDTH_KATAKANA: &str = "ｦｧｨｩｪｫｬｭｮｯｰｱｲｳｴｵｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾅﾆﾇﾈﾉﾊﾋﾌﾍﾎﾏﾐﾑﾒﾓﾔﾕﾖﾗﾘﾙﾚﾛﾜﾝ";
+static FULL_WIDTH_KATAKANA: &str = "ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+pub fn create_search_key(name: &str) -> String {
+    let folded = fold_width(name).to_lowercase();
+    let romaji = kana_to_romaji(&folded, LongVowelStyle::Omitted);
+    let letters: String = romaji.chars()
+        .map(strip_diacritic)
+        .filter(|character| character.is_alphanumeric())
+        .collect();
+
+    let mut search_key = fold_romaji_spelling(&letters);
+    for (from, to) in LOANWORD_FOLDS {
+        search_key = search_key.replace(from, to);
+    }
+    collapse_repeated_letters(&drop_unvoiced_r(&search_key))
+}
+
+/**
+ * How well the search key of a name matches a search key, where a smaller match is better.
+ * The search can match anywhere in the name, with one letter wrong in searches of four
+ * or more letters and two in searches of eight or more. Matches are ranked by letters
+ * wrong, then by how much longer the name is than the search.
+ */
+pub fn match_search_key(search_key: &str, name_search_key: &str) -> Option<(usize, usize)> {
+    let search: Vec<char> = search_key.chars().collect();
+    let name: Vec<char> = name_search_key.chars().collect();
+    if search.is_empty() {
+        return None;
+    }
+    let allowed_typo_count = match search.len() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+
+    let typo_count = get_substring_edit_distance(&search, &name);
+    if typo_count > allowed_typo_count {
+        return None;
+    }
+    Some((typo_count, name.len().saturating_sub(search.len())))
+}
+
+/**
+ * The best matches for the search, up to "limit" of them.
+ */
+pub fn rank_by_search_key<T>(
+    items: Vec<T>,
+    search: &str,
+    get_search_key: impl Fn(&T) -> &str,
+    limit: usize,
+) -> Vec<T> {
+    let search_key = create_search_key(search);
+    let mut matches: Vec<((usize, usize), T)> = items.into_iter()
+        .filter_map(|item| Some((match_search_key(&search_key, get_search_key(&item))?, item)))
+        .collect();
+    matches.sort_by_key(|(rank, _)| *rank);
+    matches.into_iter().take(limit).map(|(_, item)| item).collect()
+}
+
+/**
+ * Full-width letters and digits become half-width, and half-width katakana become
+ * full-width, joining the voiced sound marks that follow them.
+ */
+fn fold_width(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        let character = match character {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(character as u32 - 0xFEE0).unwrap_or(character),
+            '\u{3000}' => ' ',
+            'ｦ'..='ﾝ' => HALF_WIDTH_KATAKANA.chars()
+                .position(|half_width| half_width == character)
+                .and_then(|index| FULL_WIDTH_KATAKANA.chars().nth(index))
+                .unwrap_or(character),
+            _ => character,
+        };
+        let voiced = match characters.peek() {
+            Some('ﾞ' | '゛' | '\u{3099}') => add_voiced_mark(character, false),
+            Some('ﾟ' | '゜' | '\u{309A}') => add_voiced_mark(character, true),
+            _ => None,
+        };
+        match voiced {
+            Some(voiced) => {
+                folded.push(voiced);
+                characters.next();
+            },
+            None => folded.push(character),
+        }
+    }
+    folded
+}
+
+/**
+ * Voiced kana come right after their unvoiced kana in Unicode, and semi-voiced right after that.
+ */
+fn add_voiced_mark(character: char, is_semi_voiced: bool) -> Option<char> {
+    let offset = if is_semi_voiced {
+        if !"ハヒフヘホはひふへほ".contains(character) {
+            return None;
+        }
+        2
+    } else {
+        match character {
+            'ウ' => return Some('ヴ'),
+            'う' => return Some('ゔ'),
+            _ if "カキクケコサシスセソタチツテトハヒフヘホかきくけこさしすせそたちつてとはひふへほ".contains(character) => 1,
+            _ => return None,
+        }
+    };
+    char::from_u32(character as u32 + offset)
+}
+
+fn strip_diacritic(character: char) -> char {
+    match character {
+        'ā' | 'â' | 'á' | 'à' | 'ä' | 'ã' | 'å' => 'a',
+        'ī' | 'î' | 'í' | 'ì' | 'ï' => 'i',
+        'ū' | 'û' | 'ú' | 'ù' | 'ü' => 'u',
+        'ē' | 'ê' | 'é' | 'è' | 'ë' => 'e',
+        'ō' | 'ô' | 'ó' | 'ò' | 'ö' | 'õ' | 'ø' => 'o',
+        'ñ' => 'n',
+        'ç' => 'c',
+        _ => character,
+    }
+}
+
+/**
+ * An "r" without a vowel after it isn't written in katakana, so "fair" and フェア match.
+ * Rōmaji always has a vowel after "r".
+ */
+fn drop_unvoiced_r(text: &str) -> String {
+    let characters: Vec<char> = text.chars().collect();
+    characters.iter().enumerate()
+        .filter(|(index, character)| {
+            **character != 'r' || characters.get(index + 1).is_some_and(|next| matches!(next, 'a' | 'i' | 'u' | 'e' | 'o'))
+        })
+        .map(|(_, character)| *character)
+        .collect()
+}
+
+/**
+ * Doubled letters are folded, such as the doubled consonants of small っ or "ll".
+ */
+fn collapse_repeated_letters(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for character in text.chars() {
+        if !collapsed.ends_with(character) {
+            collapsed.push(character);
+        }
+    }
+    collapsed
+}
+
+/**
+ * The fewest letters that have to change for the search to appear somewhere in the name.
+ */
+fn get_substring_edit_distance(search: &[char], name: &[char]) -> usize {
+    let mut previous_row: Vec<usize> = vec![0; name.len() + 1];
+    let mut current_row: Vec<usize> = vec![0; name.len() + 1];
+    for (search_index, search_letter) in search.iter().enumerate() {
+        current_row[0] = search_index + 1;
+        for (name_index, name_letter) in name.iter().enumerate() {
+            let substitution_cost = if search_letter == name_letter { 0 } else { 1 };
+            current_row[name_index + 1] = (previous_row[name_index] + substitution_cost)
+                .min(previous_row[name_index + 1] + 1)
+                .min(current_row[name_index] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row.into_iter().min().unwrap_or(search.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_katakana_and_romaji_spellings_share_a_key() {
+        let search_key = create_search_key("Fairway");
+        assert_eq!(create_search_key("フェアウェイ"), search_key);
+        assert_eq!(create_search_key("feawei"), search_key);
+        assert_eq!(create_search_key("FAIRWAY"), search_key);
+    }
+
+    #[test]
+    fn romaji_systems_and_long_vowels_share_a_key() {
+        assert_eq!(create_search_key("とうきょう"), create_search_key("Tōkyō"));
+        assert_eq!(create_search_key("Toukyou"), create_search_key("tokyo"));
+        assert_eq!(create_search_key("しゃしん"), create_search_key("syasin"));
+    }
+
+    #[test]
+    fn folds_full_width_and_half_width_characters() {
+        assert_eq!(create_search_key("ＳＵＰＥＲＣＡＲ"), create_search_key("supercar"));
+        assert_eq!(create_search_key("ｽｰﾊﾟｰｶｰ"), create_search_key("スーパーカー"));
+        assert_eq!(create_search_key("ｶﾞﾗｽ"), create_search_key("ガラス"));
+        assert_eq!(create_search_key("ｳﾞｧ"), create_search_key("ヴァ"));
+    }
+
+    #[test]
+    fn joins_combining_voiced_marks() {
+        assert_eq!(create_search_key("ハ\u{309A}ン"), create_search_key("パン"));
+        assert_eq!(create_search_key("か\u{3099}"), create_search_key("が"));
+    }
+
+    #[test]
+    fn drops_spaces_and_punctuation_but_keeps_kanji() {
+        assert_eq!(create_search_key("Lucky!"), create_search_key("lucky"));
+        assert_eq!(create_search_key("Three Out Change!!"), create_search_key("threeoutchange"));
+        assert_eq!(create_search_key("夜空"), "夜空");
+    }
+
+    #[test]
+    fn matches_anywhere_in_the_name_with_few_typos() {
+        let name = create_search_key("Yumegiwa Last Boy");
+        assert_eq!(match_search_key(&create_search_key("last boy"), &name).map(|(typos, _)| typos), Some(0));
+        assert_eq!(match_search_key(&create_search_key("lsat boy"), &name).map(|(typos, _)| typos), Some(1));
+        assert!(match_search_key(&create_search_key("boi"), &create_search_key("Lucky")).is_none());
+        assert!(match_search_key("", &name).is_none());
+    }
+
+    #[test]
+    fn short_searches_must_match_exactly() {
+        let name = create_search_key("Lucky");
+        assert!(match_search_key(&create_search_key("luk"), &name).is_some());
+        assert!(match_search_key(&create_search_key("lak"), &name).is_none());
+    }
+
+    #[test]
+    fn ranks_fewer_typos_then_shorter_names_first() {
+        let names = ["Fairway Lucky Star", "Fairway", "Fairwai", "Strobolights"];
+        let search_keys: Vec<(&str, String)> = names.iter().map(|name| (*name, create_search_key(name))).collect();
+        let ranked = rank_by_search_key(search_keys, "fairway", |(_, search_key)| search_key, 10);
+        let ranked_names: Vec<&str> = ranked.iter().map(|(name, _)| *name).collect();
+        assert_eq!(ranked_names, vec!["Fairway", "Fairwai", "Fairway Lucky Star"]);
+
+        let search_keys: Vec<(&str, String)> = names.iter().map(|name| (*name, create_search_key(name))).collect();
+        assert_eq!(rank_by_search_key(search_keys, "fairway", |(_, search_key)| search_key, 1).len(), 1);
+    }
+}
@@ -1,26 +1,299 @@
-use totp_rs::{ Algorithm, TOTP, Secret };
+/**
+ * Time-based one time passwords (RFC 6238) for two-factor authentication.
+ *
+ * TOTP secrets are encrypted with AES-256-GCM before they are stored in the database,
+ * using the key from the [totp] section of secrets.toml. Secrets that are waiting for the
+ * user to confirm their first code, and sign-ins that are waiting for a code, are only
+ * kept in memory.
+ */
+
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::Cursor;
+use std::time::{ SystemTime, UNIX_EPOCH };
+use aes_gcm::{
+    aead::{ Aead, AeadCore, KeyInit, OsRng },
+    Aes256Gcm, Key, Nonce,
+};
+use chrono::prelude::{ DateTime, Utc };
+use dashmap::DashMap;
+use image::{ ImageFormat, Luma };
 use qrcode::QrCode;
-use qrcode::render::unicode;
-use qrcode::render::minipng;
+use rand::{ thread_rng, Rng };
+use serde::Deserialize;
+use sha2::{ Digest, Sha256 };
+use tokio::sync::OnceCell;
+use totp_rs::{ Algorithm, Secret, TOTP };
+use urlencoding::encode;
+use uuid::Uuid;
+
+pub static TOTP_ENCRYPTION_KEY: OnceCell<Vec<u8>> = OnceCell::const_new();
+pub static PENDING_ENROLLMENTS: OnceCell<DashMap<i32, PendingEnrollment>> = OnceCell::const_new();
+pub static SIGN_IN_CHALLENGES: OnceCell<DashMap<String, SignInChallenge>> = OnceCell::const_new();
+pub static TOTP_ISSUER: &str = "SupercarBand";
+pub static TOTP_STEP_SECONDS: u64 = 30;
+pub static PENDING_ENROLLMENT_EXPIRY_SECONDS: i64 = 900;
+pub static SIGN_IN_CHALLENGE_EXPIRY_SECONDS: i64 = 300;
+pub static SIGN_IN_CHALLENGE_MAX_ATTEMPTS: u32 = 5;
+pub static RECOVERY_CODE_COUNT: usize = 10;
+static RECOVERY_CODE_CHARSET: &str = "abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Deserialize)]
+struct SecretsConfig {
+    totp: SecretsConfigTotp,
+}
 
-pub fn generate_secret() {
-    Secret::generate_secret();
+#[derive(Deserialize)]
+struct SecretsConfigTotp {
+    encryption_key: String,
 }
 
-pub fn generate_qr_code(secret: &str, username: &str) {
-    let totp = TOTP::new(
-        Algorithm::SHA1,
-        6,
-        1,
-        30,
-        secret.to_bytes(),
-    ).unwrap();
+#[derive(Clone, Debug)]
+pub struct PendingEnrollment {
+    pub secret: Vec<u8>,
+    pub timestamp: DateTime<Utc>,
+}
 
-    let otpath_url = totp.get_url(username, "supercarband.com");
-    let code = QrCode::new(otpath_url.as_bytes()).unwrap();
+#[derive(Clone, Debug)]
+pub struct SignInChallenge {
+    pub user_id: i32,
+    pub attempts: u32,
+    pub timestamp: DateTime<Utc>,
+}
 
-    let qr_text = code.render::<unicode::Dense1x2>().build();
+pub fn init_totp() {
+    let secrets_toml = fs::read_to_string(format!(
+        "{}/config/secrets.toml",
+        env!("CARGO_MANIFEST_DIR")
+    )).expect("Failed to read secrets.toml file.");
+    let config: SecretsConfig = toml::from_str(&secrets_toml)
+        .expect("Failed to parse secrets.toml file.");
+
+    let key = hex::decode(config.totp.encryption_key.trim())
+        .expect("The totp encryption_key in secrets.toml is not valid hex.");
+    if key.len() != 32 {
+        panic!("The totp encryption_key in secrets.toml must be 32 bytes (64 hex characters).");
+    }
+
+    TOTP_ENCRYPTION_KEY.set(key).expect("TOTP encryption key already initialized.");
+    PENDING_ENROLLMENTS.set(DashMap::new()).expect("Pending TOTP enrollments already initialized.");
+    SIGN_IN_CHALLENGES.set(DashMap::new()).expect("Sign in challenges already initialized.");
+}
+
+fn create_totp(secret: &[u8]) -> Result<TOTP, Box<dyn Error + Send + Sync>> {
+    Ok(
+        TOTP::new(Algorithm::SHA1, 6, 1, TOTP_STEP_SECONDS, secret.to_vec())?
+    )
+}
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; 20];
+    thread_rng().fill(&mut secret[..]);
+    secret
+}
+
+/**
+ * Base32 secret that can be typed into an authenticator app when the QR code can't be scanned.
+ */
+pub fn get_secret_base32(secret: &[u8]) -> String {
+    match Secret::Raw(secret.to_vec()).to_encoded() {
+        Secret::Encoded(encoded) => encoded,
+        Secret::Raw(_) => String::from(""),
+    }
+}
+
+pub fn get_otpauth_url(secret: &[u8], username: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits=6&period={}",
+        encode(TOTP_ISSUER),
+        encode(username),
+        get_secret_base32(secret),
+        encode(TOTP_ISSUER),
+        TOTP_STEP_SECONDS,
+    )
+}
+
+pub fn generate_qr_code(secret: &[u8], username: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let code = QrCode::new(get_otpauth_url(secret, username).as_bytes())?;
+    let image = code.render::<Luma<u8>>()
+        .min_dimensions(200, 200)
+        .build();
+    let mut png_bytes: Vec<u8> = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png_bytes), ImageFormat::Png)?;
+    Ok(png_bytes)
+}
+
+/**
+ * Returns the time step that the code matched, so callers can refuse to accept
+ * the same code twice.
+ */
+pub fn verify_code(secret: &[u8], code: &str) -> Option<u64> {
+    let code = code.trim().replace(' ', "");
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let totp = create_totp(secret).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let current_step = now / TOTP_STEP_SECONDS;
+    for step in [current_step.saturating_sub(1), current_step, current_step + 1] {
+        if totp.generate(step * TOTP_STEP_SECONDS) == code {
+            return Some(step);
+        }
+    }
+    None
+}
+
+pub fn encrypt_secret(secret: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let key = TOTP_ENCRYPTION_KEY.get().expect("TOTP encryption key not initialized.");
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, secret).map_err(|_| {
+        io::Error::new(io::ErrorKind::Other, "Failed to encrypt TOTP secret.")
+    })?;
+    Ok(
+        format!("{}{}", hex::encode(nonce), hex::encode(ciphertext))
+    )
+}
+
+pub fn decrypt_secret(encrypted_secret: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let key = TOTP_ENCRYPTION_KEY.get().expect("TOTP encryption key not initialized.");
+    let bytes = hex::decode(encrypted_secret)?;
+    if bytes.len() <= 12 {
+        return Err(Box::new(
+            io::Error::new(io::ErrorKind::Other, "Encrypted TOTP secret is too short.")
+        ));
+    }
+    let (nonce, ciphertext) = bytes.split_at(12);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    Ok(
+        cipher.decrypt(Nonce::from_slice(nonce), ciphertext).map_err(|_| {
+            io::Error::new(io::ErrorKind::Other, "Failed to decrypt TOTP secret.")
+        })?
+    )
+}
+
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| {
+                    let idx = rng.gen_range(0..RECOVERY_CODE_CHARSET.len());
+                    RECOVERY_CODE_CHARSET.chars().nth(idx).unwrap()
+                })
+                .collect();
+            format!("{}-{}", &chars[0..5], &chars[5..10])
+        })
+        .collect()
+}
+
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized = code
+        .trim()
+        .to_lowercase()
+        .replace(['-', ' '], "");
+    let mut hasher = Sha256::new();
+    hasher.update(normalized.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/**
+ * Enrollment secrets are kept here until the user confirms that their authenticator
+ * app produces a valid code, so a half-finished setup never locks anyone out.
+ */
+pub fn get_or_create_pending_enrollment(user_id: i32) -> Vec<u8> {
+    let pending_enrollments = PENDING_ENROLLMENTS.get().expect("Pending TOTP enrollments not initialized.");
+    let now = Utc::now();
+    pending_enrollments.retain(|_, enrollment| {
+        now.signed_duration_since(enrollment.timestamp).num_seconds() < PENDING_ENROLLMENT_EXPIRY_SECONDS
+    });
+    pending_enrollments
+        .entry(user_id)
+        .or_insert_with(|| PendingEnrollment {
+            secret: generate_secret(),
+            timestamp: now,
+        })
+        .secret
+        .clone()
+}
+
+pub fn get_pending_enrollment(user_id: i32) -> Option<Vec<u8>> {
+    let pending_enrollments = PENDING_ENROLLMENTS.get().expect("Pending TOTP enrollments not initialized.");
+    let now = Utc::now();
+    match pending_enrollments.get(&user_id) {
+        Some(enrollment) if now.signed_duration_since(enrollment.timestamp).num_seconds() < PENDING_ENROLLMENT_EXPIRY_SECONDS => {
+            Some(enrollment.secret.clone())
+        },
+        _ => None,
+    }
+}
+
+pub fn discard_pending_enrollment(user_id: i32) {
+    let pending_enrollments = PENDING_ENROLLMENTS.get().expect("Pending TOTP enrollments not initialized.");
+    pending_enrollments.remove(&user_id);
+}
+
+/**
+ * Created after a correct password for an account with 2FA enabled. The returned token
+ * stands in for the password on the second sign in step.
+ */
+pub fn create_sign_in_challenge(user_id: i32) -> String {
+    let sign_in_challenges = SIGN_IN_CHALLENGES.get().expect("Sign in challenges not initialized.");
+    let token = format!("{}", Uuid::new_v4());
+    let now = Utc::now();
+    sign_in_challenges.retain(|_, challenge| {
+        now.signed_duration_since(challenge.timestamp).num_seconds() < SIGN_IN_CHALLENGE_EXPIRY_SECONDS
+    });
+    sign_in_challenges.insert(
+        token.clone(),
+        SignInChallenge {
+            user_id,
+            attempts: 0,
+            timestamp: now,
+        }
+    );
+    token
+}
+
+/**
+ * Counts an attempt against the challenge and returns the user id, or None if the
+ * challenge expired or ran out of attempts.
+ */
+pub fn attempt_sign_in_challenge(token: &str) -> Option<i32> {
+    let sign_in_challenges = SIGN_IN_CHALLENGES.get().expect("Sign in challenges not initialized.");
+    let now = Utc::now();
+    let user_id = match sign_in_challenges.get_mut(token) {
+        Some(mut challenge) => {
+            if now.signed_duration_since(challenge.timestamp).num_seconds() >= SIGN_IN_CHALLENGE_EXPIRY_SECONDS
+                || challenge.attempts >= SIGN_IN_CHALLENGE_MAX_ATTEMPTS {
+                None
+            } else {
+                challenge.attempts += 1;
+                Some(challenge.user_id)
+            }
+        },
+        None => return None,
+    };
+    if user_id.is_none() {
+        sign_in_challenges.remove(token);
+    }
+    user_id
+}
+
+pub fn is_sign_in_challenge_active(token: &str) -> bool {
+    let sign_in_challenges = SIGN_IN_CHALLENGES.get().expect("Sign in challenges not initialized.");
+    let now = Utc::now();
+    match sign_in_challenges.get(token) {
+        Some(challenge) => {
+            now.signed_duration_since(challenge.timestamp).num_seconds() < SIGN_IN_CHALLENGE_EXPIRY_SECONDS
+                && challenge.attempts < SIGN_IN_CHALLENGE_MAX_ATTEMPTS
+        },
+        None => false,
+    }
+}
 
-    let png_bytes = code.render::<minipng::Color>().build();
-    // let image = code.render::<Luma<u8>>().build();
+pub fn discard_sign_in_challenge(token: &str) {
+    let sign_in_challenges = SIGN_IN_CHALLENGES.get().expect("Sign in challenges not initialized.");
+    sign_in_challenges.remove(token);
 }
@@ -0,0 +1,106 @@
+/**
+ * Deleted content stays in the trash (hidden with is_deleted=1) until a moderator approves
+ * the deletion, or until it has been there longer than the retention period from the
+ * [trash] section of secrets.toml, then it is purged for good.
+ */
+
+use std::fs;
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+use tokio::time::{ interval, Duration };
+
+use crate::database::{ self, DeletionContentType };
+
+pub static TRASH_RETENTION_DAYS: OnceCell<u32> = OnceCell::const_new();
+static TRASH_PURGE_INTERVAL_SECONDS: u64 = 3600;
+
+/**
+ * Content is purged before whatever contains it, so a band isn't refused
+ * because its albums were still waiting in the trash.
+ */
+static TRASH_PURGE_ORDER: [DeletionContentType; 8] = [
+    DeletionContentType::Lyrics,
+    DeletionContentType::Tabs,
+    DeletionContentType::Photo,
+    DeletionContentType::Video,
+    DeletionContentType::PhotoAlbum,
+    DeletionContentType::VideoCategory,
+    DeletionContentType::Album,
+    DeletionContentType::Band,
+];
+
+#[derive(Deserialize)]
+struct SecretsConfig {
+    #[serde(default)]
+    trash: SecretsConfigTrash,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct SecretsConfigTrash {
+    retention_days: u32,
+}
+impl Default for SecretsConfigTrash {
+    fn default() -> Self {
+        SecretsConfigTrash {
+            retention_days: 30,
+        }
+    }
+}
+
+pub fn init_trash() {
+    let secrets_toml = fs::read_to_string(format!(
+        "{}/config/secrets.toml",
+        env!("CARGO_MANIFEST_DIR")
+    )).expect("Failed to read secrets.toml file.");
+    let config: SecretsConfig = toml::from_str(&secrets_toml)
+        .expect("Failed to parse secrets.toml file.");
+
+    tracing::info!("Trash retention days: {}", config.trash.retention_days);
+
+    TRASH_RETENTION_DAYS.set(config.trash.retention_days).expect("Trash retention already initialized.");
+}
+
+/**
+ * 0 means deleted content is only purged when a moderator approves the deletion.
+ */
+pub fn get_trash_retention_days() -> u32 {
+    *TRASH_RETENTION_DAYS.get().unwrap_or(&0)
+}
+
+pub async fn init_trash_purge() {
+    let mut interval = interval(Duration::from_secs(TRASH_PURGE_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let retention_days = get_trash_retention_days();
+        if retention_days == 0 {
+            continue;
+        }
+
+        let mut expired_content = match database::get_expired_deleted_content(retention_days).await {
+            Ok(expired_content) => expired_content,
+            Err(error) => {
+                tracing::warn!("Error occurred when finding expired trash. {:?}", error);
+                continue;
+            },
+        };
+        expired_content.sort_by_key(|(content_type, _)| {
+            TRASH_PURGE_ORDER.iter().position(|purge_type| purge_type == content_type)
+        });
+
+        let mut purge_count: u32 = 0;
+        for (content_type, content_id) in expired_content {
+            match database::purge_deleted_content(&content_type, content_id, "").await {
+                Ok(_) => purge_count += 1,
+                Err(error) => {
+                    tracing::warn!("Error occurred when purging {} {} from the trash. {:?}", content_type, content_id, error);
+                },
+            }
+        }
+        if purge_count > 0 {
+            tracing::info!("Purged {} items from the trash.", purge_count);
+        }
+    }
+}
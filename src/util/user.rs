@@ -11,3 +11,11 @@ pub fn is_guest_user(username: &str) -> bool {
 pub fn create_user_profile_href(username: &str) -> String {
     format!("/members/{}/", encode(username))
 }
+
+pub fn create_user_messages_href(username: &str) -> String {
+    format!("/messages/{}/", encode(username))
+}
+
+pub fn create_user_block_href(username: &str) -> String {
+    format!("/members/{}/block/", encode(username))
+}
@@ -76,6 +76,34 @@ pub fn get_video_thumbnail_url(video_url: &str) -> String {
     }
 }
 
+/**
+ * Only YouTube embeds report their playback time to the page, which timed lyrics follow.
+ */
+pub fn is_synchronizable_video_url(video_url: &str) -> bool {
+    match get_root_domain(video_url) {
+        Some(root_domain) => {
+            let video_id = get_video_id(video_url, &root_domain);
+            (root_domain == "youtube.com" || root_domain == "youtu.be") && !video_id.is_empty()
+                && video_id.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_')
+        },
+        None => false,
+    }
+}
+
+/**
+ * Gets embed markup for the given video URL, with the API enabled so the page can follow along.
+ */
+pub fn create_synchronized_video_embed_iframe(video_url: &str) -> String {
+    if !is_synchronizable_video_url(video_url) {
+        return String::from("");
+    }
+    let root_domain = get_root_domain(video_url).unwrap_or_default();
+    format!(
+        r#"<iframe frameborder=0 width="720" height="400" src="https://www.youtube.com/embed/{}?enablejsapi=1" title="Video" data-karaoke-video></iframe>"#,
+        get_video_id(video_url, &root_domain),
+    )
+}
+
 /**
  * Retrieves the id of a video from its URL.
  */
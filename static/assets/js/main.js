@@ -219,6 +219,91 @@ function initializeTimestamp(element) {
     }
 }
 
+/*****************\
+| Furigana Toggle |
+\*****************/
+
+/**
+ * Remembers whether the reader wants to see kanji readings in lyrics.
+ */
+function initializeFuriganaToggle(element) {
+    element.checked = localStorage.getItem('furigana-hidden') !== 'true';
+    element.addEventListener('change', () => {
+        localStorage.setItem('furigana-hidden', element.checked ? 'false' : 'true');
+    });
+}
+
+/****************\
+| Karaoke Lyrics |
+\****************/
+
+const karaokeLyricsMessageEventMap = new WeakMap();
+
+/**
+ * Highlights the line of timed lyrics that is being sung while the linked YouTube video plays.
+ * The embed reports its playback time through postMessage once the page starts listening.
+ * Clicking a timed line skips the video to it.
+ */
+function initializeKaraokeLyrics(element) {
+    const video = element.querySelector('iframe[data-karaoke-video]');
+    if (!video) return;
+    const lines = [...element.querySelectorAll('[data-start-time]')];
+    const startTimes = lines.map((line) => parseInt(line.getAttribute('data-start-time'), 10) / 1000);
+    let currentLine = null;
+
+    function postCommand(message) {
+        video.contentWindow?.postMessage(JSON.stringify(message), '*');
+    }
+    function setCurrentTime(currentTime) {
+        let lineIndex = -1;
+        for (let i = 0; i < startTimes.length && startTimes[i] <= currentTime; i++) {
+            lineIndex = i;
+        }
+        const line = lines[lineIndex] ?? null;
+        if (line === currentLine) return;
+        currentLine?.classList.remove('combined-lyrics__line--current');
+        currentLine?.removeAttribute('aria-current');
+        line?.classList.add('combined-lyrics__line--current');
+        line?.setAttribute('aria-current', 'true');
+        currentLine = line;
+    }
+    function onMessage(event) {
+        if (event.source !== video.contentWindow || typeof event.data !== 'string') return;
+        let data;
+        try {
+            data = JSON.parse(event.data);
+        } catch (error) {
+            return;
+        }
+        if (typeof data?.info?.currentTime === 'number') {
+            setCurrentTime(data.info.currentTime);
+        }
+    }
+    window.addEventListener('message', onMessage);
+    karaokeLyricsMessageEventMap.set(element, onMessage);
+
+    // The embed may have loaded before this ran, so start listening right away as well.
+    const startListening = () => postCommand({ event: 'listening', id: 'karaoke-lyrics', channel: 'widget' });
+    video.addEventListener('load', startListening);
+    startListening();
+    lines.forEach((line, lineIndex) => {
+        line.classList.add('combined-lyrics__line--seekable');
+        line.addEventListener('click', () => {
+            postCommand({ event: 'command', func: 'seekTo', args: [startTimes[lineIndex], true] });
+            postCommand({ event: 'command', func: 'playVideo', args: [] });
+        });
+    });
+}
+
+/**
+ * Removes the global event(s).
+ */
+function teardownKaraokeLyrics(element) {
+    const callback = karaokeLyricsMessageEventMap.get(element);
+    if (!callback) return;
+    window.removeEventListener('message', callback);
+}
+
 /**************************\
 | Component Initialization |
 \**************************/
@@ -233,6 +318,8 @@ function initializeComponents(eventName, detail) {
         const is = element.getAttribute('data-is');
         switch (is) {
             case 'menu-toggle-button': initializeMenuToggleButton(element); break;
+            case 'furigana-toggle': initializeFuriganaToggle(element); break;
+            case 'karaoke-lyrics': initializeKaraokeLyrics(element); break;
             case 'tabs': initializeTabs(element); break;
             case 'timestamp': initializeTimestamp(element); break;
         }
@@ -247,6 +334,7 @@ function teardownComponents(eventName, detail) {
     target.querySelectorAll('[data-is]').forEach((element) => {
         const is = element.getAttribute('data-is');
         switch (is) {
+            case 'karaoke-lyrics': teardownKaraokeLyrics(element); break;
             case 'menu-toggle-button': teardownMenuToggleButton(element); break;
         }
     });